@@ -4,3 +4,4 @@ export { WindowManagerPage } from './window-manager.page';
 export { DockPage } from './dock.page';
 export { FinderPage } from './finder.page';
 export { CalculatorPage } from './calculator.page';
+export { TerminalPage } from './terminal.page';
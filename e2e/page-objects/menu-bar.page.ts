@@ -15,6 +15,7 @@ export class MenuBarPage {
   readonly clock: Locator;
   readonly wifiIcon: Locator;
   readonly batteryIcon: Locator;
+  readonly controlCenterIcon: Locator;
 
   constructor(page: Page) {
     this.page = page;
@@ -31,6 +32,7 @@ export class MenuBarPage {
     this.clock = page.locator('.status-clock');
     this.wifiIcon = page.locator('.wifi-icon');
     this.batteryIcon = page.locator('.battery-icon');
+    this.controlCenterIcon = page.locator('.control-center-icon');
   }
 
   async openMenu(menuLocator: Locator) {
@@ -50,6 +52,13 @@ export class MenuBarPage {
     return menuLocator.evaluate((el) => el.classList.contains('active'));
   }
 
+  /** Flip the Stage Manager switch in Control Center, then close it */
+  async toggleStageManager() {
+    await this.controlCenterIcon.click();
+    await this.page.locator('.cc-stage-manager').click();
+    await this.controlCenterIcon.click();
+  }
+
   async getClockText() {
     return this.clock.textContent();
   }
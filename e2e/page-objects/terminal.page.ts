@@ -0,0 +1,72 @@
+import { Page, Locator } from '@playwright/test';
+
+export class TerminalPage {
+  readonly page: Page;
+  readonly terminal: Locator;
+  readonly session: Locator;
+  readonly input: Locator;
+  readonly prompt: Locator;
+  readonly lines: Locator;
+  readonly editor: Locator;
+  readonly pager: Locator;
+  readonly tabs: Locator;
+
+  constructor(page: Page, windowLocator: Locator) {
+    this.page = page;
+    this.terminal = windowLocator.locator('.terminal');
+    // Inactive tabs stay mounted but hidden
+    this.session = this.terminal.locator('.terminal-session:visible');
+    this.input = this.session.locator('.terminal-input');
+    this.prompt = this.session.locator('.terminal-input-line .terminal-prompt');
+    this.lines = this.session.locator('.terminal-line');
+    this.editor = this.session.locator('.nano-editor');
+    this.pager = this.session.locator('.terminal-pager');
+    this.tabs = this.terminal.locator('.terminal-tab');
+  }
+
+  async run(command: string) {
+    await this.input.fill(command);
+    await this.input.press('Enter');
+  }
+
+  async newTab() {
+    await this.input.press('Meta+t');
+  }
+
+  async selectTab(title: string) {
+    await this.tabs.filter({ hasText: title }).click();
+  }
+
+  async getTabTitles(): Promise<string[]> {
+    return this.tabs.locator('.terminal-tab-title').allTextContents();
+  }
+
+  async getPrompt(): Promise<string> {
+    return (await this.prompt.textContent()) || '';
+  }
+
+  async getLastLine(): Promise<string> {
+    return (await this.lines.last().textContent()) || '';
+  }
+
+  async getOutput(): Promise<string[]> {
+    return this.lines.allTextContents();
+  }
+
+  async getEditorText(): Promise<string> {
+    const lines = await this.editor.locator('.nano-line').allTextContents();
+    return lines.join('\n');
+  }
+
+  async getEditorStatus(): Promise<string> {
+    return (await this.editor.locator('.nano-status').textContent()) || '';
+  }
+
+  async getPagerLines(): Promise<string[]> {
+    return this.pager.locator('.pager-line').allTextContents();
+  }
+
+  async getPagerStatus(): Promise<string> {
+    return (await this.pager.locator('.pager-status').textContent()) || '';
+  }
+}
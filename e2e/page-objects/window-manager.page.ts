@@ -47,6 +47,83 @@ export class WindowManagerPage {
     await this.page.mouse.up();
   }
 
+  /** Drag a window by its title bar until the pointer reaches (x, y), leaving the button held */
+  async dragTitleBarTo(windowLocator: Locator, x: number, y: number) {
+    const titleBar = this.getTitleBar(windowLocator);
+    const box = await titleBar.boundingBox();
+    if (!box) throw new Error('Window not found');
+
+    await this.page.mouse.move(box.x + box.width / 2, box.y + box.height / 2);
+    await this.page.mouse.down();
+    await this.page.mouse.move(x, y, { steps: 10 });
+  }
+
+  getSheet(windowLocator: Locator): Locator {
+    return windowLocator.locator('.sheet');
+  }
+
+  getSheetButton(windowLocator: Locator, label: string): Locator {
+    return this.getSheet(windowLocator).locator('.sheet-button', { hasText: label });
+  }
+
+  getStageStripItem(appName: string): Locator {
+    return this.page.locator('.stage-strip-item').filter({
+      has: this.page.locator('.stage-strip-title', { hasText: appName })
+    });
+  }
+
+  async isWindowInStrip(windowLocator: Locator) {
+    return windowLocator.evaluate((el) => el.classList.contains('in-strip'));
+  }
+
+  getSnapPreview(): Locator {
+    return this.page.locator('.snap-preview');
+  }
+
+  getMissionControlThumbnails(): Locator {
+    return this.page.locator('.mission-control-thumb');
+  }
+
+  getMissionControlThumbnail(title: string): Locator {
+    return this.getMissionControlThumbnails().filter({
+      has: this.page.locator('.mission-control-title', { hasText: title })
+    });
+  }
+
+  getSpaceButton(name: string): Locator {
+    return this.page.locator('.space-button').filter({
+      has: this.page.locator('.space-name', { hasText: name })
+    });
+  }
+
+  getAddSpaceButton(): Locator {
+    return this.page.locator('.space-add');
+  }
+
+  /** Open Mission Control and add Spaces until there are `count` */
+  async ensureSpaces(count: number) {
+    await this.page.keyboard.press('F3');
+    while ((await this.page.locator('.space-button').count()) < count) {
+      await this.getAddSpaceButton().click();
+    }
+  }
+
+  getAppSwitcherItem(appName: string): Locator {
+    return this.page.locator('.app-switcher-item').filter({
+      has: this.page.locator('.app-switcher-name', { hasText: appName })
+    });
+  }
+
+  async isMissionControlOpen() {
+    return this.page
+      .locator('.windows-container')
+      .evaluate((el) => el.classList.contains('mission-control'));
+  }
+
+  async isWindowTiled(windowLocator: Locator) {
+    return windowLocator.evaluate((el) => el.classList.contains('tiled'));
+  }
+
   async resizeWindow(
     windowLocator: Locator,
     handle: string,
@@ -93,4 +93,18 @@ test.describe('Calculator', () => {
     });
   });
 
+  test.describe('Window State', () => {
+    test('should keep the display and pending operation after reload', async ({ page }) => {
+      await calculator.pressDigit('1');
+      await calculator.pressDigit('2');
+      await calculator.pressOperator('+');
+      await calculator.pressDigit('3');
+
+      await page.reload();
+
+      expect(await calculator.getDisplayValue()).toBe('3');
+      await calculator.pressEquals();
+      expect(await calculator.getDisplayValue()).toBe('15');
+    });
+  });
 });
@@ -75,4 +75,19 @@ test.describe('Finder', () => {
       expect(statusText).toMatch(/\d+ items?/);
     });
   });
+
+  test.describe('Window State', () => {
+    test('should reopen in the same folder, view and history after reload', async ({ page }) => {
+      await finder.navigateToSidebarItem('Documents');
+      await finder.clickViewButton(1);
+
+      await page.reload();
+
+      expect(await finder.getToolbarTitle()).toBe('Documents');
+      expect(await finder.isSidebarItemSelected('Documents')).toBe(true);
+      expect(await finder.isViewButtonActive(1)).toBe(true);
+      await finder.navBackButton.click();
+      expect(await finder.getToolbarTitle()).not.toBe('Documents');
+    });
+  });
 });
@@ -1,5 +1,5 @@
 import { test, expect } from '@playwright/test';
-import { MenuBarPage, DesktopPage } from '../page-objects';
+import { MenuBarPage, DesktopPage, WindowManagerPage } from '../page-objects';
 
 test.describe('Menu Bar', () => {
   let menuBar: MenuBarPage;
@@ -79,4 +79,19 @@ test.describe('Menu Bar', () => {
       expect(percent).toMatch(/\d+%/);
     });
   });
+
+  test.describe('Force Quit', () => {
+    test('should list running apps and quit the selected one', async ({ page }) => {
+      await menuBar.clickDropdownItem(menuBar.appleMenu, 'Force Quit');
+      const modal = page.locator('.force-quit-modal');
+      await expect(modal.locator('.force-quit-item', { hasText: 'Terminal' })).toBeVisible();
+
+      await modal.locator('.force-quit-item', { hasText: 'Calculator' }).click();
+      await modal.locator('.modal-button', { hasText: 'Force Quit' }).click();
+
+      const windowManager = new WindowManagerPage(page);
+      await expect(windowManager.getWindow('Calculator')).toHaveCount(0);
+      await expect(modal.locator('.force-quit-item', { hasText: 'Calculator' })).toHaveCount(0);
+    });
+  });
 });
@@ -0,0 +1,155 @@
+import { test, expect } from '@playwright/test';
+import { WindowManagerPage, DesktopPage, FinderPage, TerminalPage, CalculatorPage } from '../page-objects';
+
+test.describe('Sheets', () => {
+  let windowManager: WindowManagerPage;
+
+  test.beforeEach(async ({ page }) => {
+    const desktop = new DesktopPage(page);
+    await desktop.goto();
+    await page.evaluate(() => localStorage.removeItem('virtualmac_textedit'));
+    await page.reload();
+    await page.waitForSelector('.desktop');
+    windowManager = new WindowManagerPage(page);
+  });
+
+  async function typeInTextEdit(text: string) {
+    const textEdit = windowManager.getWindow('TextEdit');
+    await textEdit.locator('.textedit-document').click();
+    await textEdit.page().keyboard.type(text);
+    return textEdit;
+  }
+
+  test.describe('Save Panel', () => {
+    test('should save a new document where the panel says', async ({ page }) => {
+      const textEdit = await typeInTextEdit('saved from a sheet');
+      await page.keyboard.press('Meta+s');
+
+      const sheet = windowManager.getSheet(textEdit);
+      await expect(sheet).toBeVisible();
+      await sheet.locator('.sheet-input').fill('sheet.txt');
+      await sheet.locator('.sheet-select').selectOption('/Desktop');
+      await windowManager.getSheetButton(textEdit, 'Save').click();
+      await expect(sheet).toHaveCount(0);
+
+      const terminal = new TerminalPage(page, windowManager.getWindow('Terminal'));
+      await terminal.run('cat /Desktop/sheet.txt');
+      expect(await terminal.getLastLine()).toBe('saved from a sheet');
+    });
+
+    test('should leave the document unsaved when cancelled with Escape', async ({ page }) => {
+      const textEdit = await typeInTextEdit('not saved');
+      await page.keyboard.press('Meta+s');
+
+      const sheet = windowManager.getSheet(textEdit);
+      await expect(sheet).toBeVisible();
+      await page.keyboard.press('Escape');
+      await expect(sheet).toHaveCount(0);
+      await expect(textEdit).toBeVisible();
+    });
+
+    test('should block only its own window', async ({ page }) => {
+      const textEdit = await typeInTextEdit('draft');
+      await page.keyboard.press('Meta+s');
+      await expect(windowManager.getSheet(textEdit)).toBeVisible();
+      expect(await textEdit.evaluate((el) => el.classList.contains('has-sheet'))).toBe(true);
+
+      // The content underneath doesn't take clicks, and the window won't close
+      const backdrop = textEdit.locator('.sheet-backdrop');
+      const box = await backdrop.boundingBox();
+      const hit = await page.evaluate(
+        ({ x, y }) => document.elementFromPoint(x, y)?.className,
+        { x: box!.x + 10, y: box!.y + box!.height - 10 }
+      );
+      expect(hit).toContain('sheet-backdrop');
+      await windowManager.getCloseButton(textEdit).click();
+      await expect(textEdit).toBeVisible();
+
+      // Other windows carry on as usual
+      const calculator = new CalculatorPage(page, windowManager.getWindow('Calculator'));
+      await calculator.pressClear();
+      await calculator.pressDigit('7');
+      await expect(calculator.displayText).toHaveText('7');
+    });
+  });
+
+  test.describe('Unsaved Changes', () => {
+    test('should ask before closing an edited document', async () => {
+      const textEdit = await typeInTextEdit('unsaved work');
+      await windowManager.getCloseButton(textEdit).click();
+
+      const sheet = windowManager.getSheet(textEdit);
+      await expect(sheet.locator('.sheet-title')).toHaveText(
+        'Do you want to save the changes made to the document “Untitled”?'
+      );
+      await windowManager.getSheetButton(textEdit, 'Cancel').click();
+      await expect(textEdit).toBeVisible();
+
+      await windowManager.getCloseButton(textEdit).click();
+      await windowManager.getSheetButton(textEdit, 'Don’t Save').click();
+      await expect(textEdit).toHaveCount(0);
+    });
+
+    test('should save through the panel and then close', async ({ page }) => {
+      const textEdit = await typeInTextEdit('keep me');
+      await page.keyboard.press('Meta+w');
+
+      await windowManager.getSheetButton(textEdit, 'Save').click();
+      const sheet = windowManager.getSheet(textEdit);
+      await sheet.locator('.sheet-input').fill('kept.txt');
+      await page.keyboard.press('Enter');
+      await expect(textEdit).toHaveCount(0);
+
+      const terminal = new TerminalPage(page, windowManager.getWindow('Terminal'));
+      await terminal.run('cat /Documents/kept.txt');
+      expect(await terminal.getLastLine()).toBe('keep me');
+    });
+
+    test('should ask before opening a file over an edited document', async ({ page }) => {
+      const terminal = new TerminalPage(page, windowManager.getWindow('Terminal'));
+      await terminal.run('echo from disk > /Desktop/incoming.txt');
+      const textEdit = await typeInTextEdit('unsaved work');
+
+      await terminal.run('open /Desktop/incoming.txt');
+      const document = textEdit.locator('.textedit-document');
+      await expect(windowManager.getSheet(textEdit)).toBeVisible();
+      await windowManager.getSheetButton(textEdit, 'Cancel').click();
+      await expect(document).toHaveText('unsaved work');
+
+      await terminal.run('open /Desktop/incoming.txt');
+      await windowManager.getSheetButton(textEdit, 'Don’t Save').click();
+      await expect(document).toHaveText('from disk');
+    });
+
+    test('should close an unedited document without asking', async () => {
+      const textEdit = windowManager.getWindow('TextEdit');
+      await windowManager.getCloseButton(textEdit).click();
+      await expect(textEdit).toHaveCount(0);
+    });
+  });
+
+  test.describe('Finder Delete Confirmation', () => {
+    test('should delete only after confirming', async ({ page }) => {
+      const terminal = new TerminalPage(page, windowManager.getWindow('Terminal'));
+      await terminal.run('echo bye > /Documents/doomed.txt');
+      await terminal.run('open /Documents');
+      const finderWindow = windowManager.getWindow('Finder');
+      const finder = new FinderPage(page, finderWindow);
+
+      await finder.getFileItem('doomed.txt').click({ button: 'right' });
+      await page.locator('.context-menu-item', { hasText: 'Move to Trash' }).click();
+      const sheet = windowManager.getSheet(finderWindow);
+      await expect(sheet.locator('.sheet-title')).toHaveText(
+        'Are you sure you want to delete “doomed.txt”?'
+      );
+      await page.keyboard.press('Escape');
+      await expect(sheet).toHaveCount(0);
+      await expect(finder.getFileItem('doomed.txt')).toBeVisible();
+
+      await finder.getFileItem('doomed.txt').click({ button: 'right' });
+      await page.locator('.context-menu-item', { hasText: 'Move to Trash' }).click();
+      await windowManager.getSheetButton(finderWindow, 'Delete').click();
+      await expect(finder.getFileItem('doomed.txt')).toHaveCount(0);
+    });
+  });
+});
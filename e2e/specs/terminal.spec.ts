@@ -0,0 +1,681 @@
+import { test, expect } from '@playwright/test';
+import { TerminalPage, WindowManagerPage, DesktopPage, FinderPage, DockPage } from '../page-objects';
+
+test.describe('Terminal', () => {
+  let terminal: TerminalPage;
+
+  test.beforeEach(async ({ page }) => {
+    const desktop = new DesktopPage(page);
+    await desktop.goto();
+    await page.evaluate(() => {
+      localStorage.removeItem('virtualmac_terminal');
+      localStorage.removeItem('virtualmac_defaults');
+    });
+    await page.reload();
+    await page.waitForSelector('.desktop');
+    const windowManager = new WindowManagerPage(page);
+    const termWindow = windowManager.getWindow('Terminal');
+    await termWindow.evaluate((el) => {
+      el.dispatchEvent(new MouseEvent('mousedown', { bubbles: true }));
+    });
+    terminal = new TerminalPage(page, termWindow);
+  });
+
+  test.describe('Environment', () => {
+    test('should expand exported variables', async () => {
+      await terminal.run('export GREETING="hello world"');
+      await terminal.run('echo $GREETING from ${USER}');
+      expect(await terminal.getLastLine()).toBe('hello world from guest');
+    });
+
+    test('should not expand variables in single quotes', async () => {
+      await terminal.run("echo '$HOME'");
+      expect(await terminal.getLastLine()).toBe('$HOME');
+    });
+
+    test('should remove variables with unset', async () => {
+      await terminal.run('export FOO=bar');
+      await terminal.run('unset FOO');
+      await terminal.run('echo "[$FOO]"');
+      expect(await terminal.getLastLine()).toBe('[]');
+    });
+
+    test('should list variables with env', async () => {
+      await terminal.run('env');
+      const output = await terminal.getOutput();
+      expect(output).toContain('USER=guest');
+      expect(output).toContain('PWD=/');
+    });
+  });
+
+  test.describe('Prompt', () => {
+    test('should show the default zsh prompt', async () => {
+      expect(await terminal.getPrompt()).toBe('guest@virtualmac ~ % ');
+    });
+
+    test('should follow PS1', async () => {
+      await terminal.run("PS1='%d $ '");
+      await terminal.run('cd Documents');
+      expect(await terminal.getPrompt()).toBe('/Documents $ ');
+    });
+  });
+
+  test.describe('cd', () => {
+    test('should return to the previous directory with cd -', async () => {
+      await terminal.run('cd Documents');
+      await terminal.run('cd /Downloads');
+      await terminal.run('cd -');
+      expect(await terminal.getLastLine()).toBe('~/Documents');
+      await terminal.run('pwd');
+      expect(await terminal.getLastLine()).toBe('/Documents');
+    });
+  });
+
+  test.describe('Scripting', () => {
+    test('should run a script with sh and ./', async () => {
+      await terminal.run("echo 'for n in 1 2 3; do echo \"item $n of $1\"; done' > /loop.sh");
+      await terminal.run('sh /loop.sh list');
+      expect(await terminal.getLastLine()).toBe('item 3 of list');
+      await terminal.run('./loop.sh again');
+      expect(await terminal.getLastLine()).toBe('item 3 of again');
+    });
+
+    test('should support if, while and functions', async () => {
+      await terminal.run('count() { i=0; while [ $i -lt $1 ]; do i=$((i+1)); done; echo $i; }');
+      await terminal.run('if [ "$(count 4)" = 4 ]; then echo counted; else echo wrong; fi');
+      expect(await terminal.getLastLine()).toBe('counted');
+    });
+
+    test('should report exit codes and stop on set -e', async () => {
+      await terminal.run("echo 'set -e; echo start; false; echo unreachable' > /strict.sh");
+      await terminal.run('sh /strict.sh; echo "status $?"');
+      const output = await terminal.getOutput();
+      expect(output).toContain('start');
+      expect(output).not.toContain('unreachable');
+      expect(await terminal.getLastLine()).toBe('status 1');
+    });
+
+    test('should source ~/.zshrc when a session starts', async ({ page }) => {
+      await terminal.run("echo 'export FROM_RC=loaded' > /.zshrc");
+      await page.reload();
+      await page.waitForSelector('.desktop');
+      const windowManager = new WindowManagerPage(page);
+      terminal = new TerminalPage(page, windowManager.getWindow('Terminal'));
+      await terminal.run('echo $FROM_RC');
+      expect(await terminal.getLastLine()).toBe('loaded');
+    });
+  });
+
+  test.describe('Paths and globs', () => {
+    test('should canonicalize .. and ~ paths', async () => {
+      await terminal.run('cd /Documents/Work');
+      await terminal.run('cd ../../Desktop/./Projects/..');
+      await terminal.run('pwd');
+      expect(await terminal.getLastLine()).toBe('/Desktop');
+      await terminal.run('cat ~/Desktop/Notes.txt > /dev/null && echo readable');
+      expect(await terminal.getLastLine()).toBe('readable');
+    });
+
+    test('should expand globs against the file system', async () => {
+      await terminal.run('echo /D*/*.txt');
+      expect(await terminal.getLastLine()).toBe('/Desktop/Notes.txt /Documents/Notes.txt');
+      await terminal.run('echo "*.txt"');
+      expect(await terminal.getLastLine()).toBe('*.txt');
+    });
+
+    test('should report globs with no matches', async () => {
+      await terminal.run('ls *.nomatch');
+      expect(await terminal.getLastLine()).toBe('zsh: no matches found: *.nomatch');
+    });
+
+    test('should complete paths through the same resolver', async () => {
+      await terminal.run('cd /Documents');
+      await terminal.input.fill('cat ../Desk');
+      await terminal.input.press('Tab');
+      await expect(terminal.input).toHaveValue('cat ../Desktop/');
+    });
+  });
+
+  test.describe('Colors', () => {
+    test('should render ANSI colors from printf', async () => {
+      await terminal.run("printf '\\e[1;31mred\\e[0m plain\\n'");
+      expect(await terminal.getLastLine()).toBe('red plain');
+      const red = terminal.lines.last().locator('span', { hasText: 'red' });
+      await expect(red).toHaveCSS('color', 'rgb(194, 54, 33)');
+      await expect(red).toHaveCSS('font-weight', '700');
+    });
+
+    test('should color directories with ls -G', async () => {
+      await terminal.run('ls -G /');
+      const desktop = terminal.lines.last().locator('span', { hasText: 'Desktop' });
+      await expect(desktop).toHaveCSS('color', 'rgb(73, 46, 225)');
+    });
+
+    test('should highlight grep matches with --color', async () => {
+      await terminal.run("printf 'apple\\ncherry\\n' > /fruit.txt");
+      await terminal.run('grep --color=always err /fruit.txt');
+      expect(await terminal.getLastLine()).toBe('cherry');
+      const match = terminal.lines.last().locator('span');
+      await expect(match).toHaveText('err');
+      await terminal.run('grep --color=auto err /fruit.txt | cat');
+      await expect(terminal.lines.last().locator('span')).toHaveCount(0);
+    });
+  });
+
+  test.describe('Tabs', () => {
+    test('should keep cwd, environment and scrollback per tab', async () => {
+      await terminal.run('cd /Documents');
+      await terminal.run('export TAB_VAR=first');
+      await terminal.newTab();
+      await expect(terminal.tabs).toHaveCount(2);
+      await terminal.run('pwd');
+      expect(await terminal.getLastLine()).toBe('/Documents');
+      await terminal.run('cd /Downloads');
+      await terminal.run('echo "[$TAB_VAR]"');
+      expect(await terminal.getLastLine()).toBe('[]');
+      expect(await terminal.getTabTitles()).toEqual(['Documents', 'Downloads']);
+
+      await terminal.selectTab('Documents');
+      expect(await terminal.getOutput()).toContain('guest@virtualmac ~/Documents % export TAB_VAR=first');
+      await terminal.run('echo $TAB_VAR');
+      expect(await terminal.getLastLine()).toBe('first');
+    });
+
+    test('should restore open tabs after reload', async ({ page }) => {
+      await terminal.run('cd /Documents');
+      await terminal.newTab();
+      await terminal.run('cd /Downloads');
+      await page.reload();
+      await page.waitForSelector('.desktop');
+      const windowManager = new WindowManagerPage(page);
+      terminal = new TerminalPage(page, windowManager.getWindow('Terminal'));
+      expect(await terminal.getTabTitles()).toEqual(['Documents', 'Downloads']);
+      await terminal.run('pwd');
+      expect(await terminal.getLastLine()).toBe('/Downloads');
+    });
+
+    test('should restore each tab\'s scrollback after reload', async ({ page }) => {
+      await terminal.run('echo first-tab-output');
+      await terminal.newTab();
+      await terminal.run('echo second-tab-output');
+      await page.reload();
+      await page.waitForSelector('.desktop');
+      const windowManager = new WindowManagerPage(page);
+      terminal = new TerminalPage(page, windowManager.getWindow('Terminal'));
+      expect(await terminal.getOutput()).toContain('second-tab-output');
+      await terminal.tabs.first().click();
+      expect(await terminal.getOutput()).toContain('first-tab-output');
+      expect(await terminal.getOutput()).not.toContain('second-tab-output');
+    });
+
+    test('should keep tabs per window, each shell on its own tty', async ({ page }) => {
+      await terminal.newTab();
+      const windowManager = new WindowManagerPage(page);
+      const fileMenu = page
+        .locator('.menu-item')
+        .filter({ has: page.locator(':scope > span', { hasText: /^File$/ }) });
+      await fileMenu.click();
+      await fileMenu.locator('.dropdown-item', { hasText: 'New Window' }).click();
+
+      const windows = windowManager.getWindow('Terminal');
+      await expect(windows).toHaveCount(2);
+      const first = new TerminalPage(page, windows.first());
+      const second = new TerminalPage(page, windows.nth(1));
+      await expect(first.tabs).toHaveCount(2);
+      await expect(second.tabs).toHaveCount(0);
+      expect((await second.getOutput())[0]).toBe('Last login: Thu Jan 16 09:00:00 on ttys002');
+
+      await second.newTab();
+      await expect(second.tabs).toHaveCount(2);
+      await expect(first.tabs).toHaveCount(2);
+    });
+
+    test('should close a tab with exit', async () => {
+      await terminal.newTab();
+      await expect(terminal.tabs).toHaveCount(2);
+      await terminal.run('exit');
+      await expect(terminal.tabs).toHaveCount(0);
+    });
+  });
+
+  test.describe('History', () => {
+    test('should list commands with history', async () => {
+      await terminal.run('echo first');
+      await terminal.run('pwd');
+      await terminal.run('history');
+      const output = await terminal.getOutput();
+      expect(output).toContain('    1  echo first');
+      expect(output).toContain('    2  pwd');
+      expect(await terminal.getLastLine()).toBe('    3  history');
+    });
+
+    test('should expand !!, !n and !prefix', async () => {
+      await terminal.run('echo alpha');
+      await terminal.run('echo beta');
+      await terminal.run('!!');
+      expect(await terminal.getLastLine()).toBe('beta');
+      await terminal.run('!1');
+      expect(await terminal.getLastLine()).toBe('alpha');
+      await terminal.run('cd /Documents');
+      await terminal.run('cd /');
+      await terminal.run('!cd && pwd');
+      expect(await terminal.getLastLine()).toBe('/');
+      await terminal.run('!nothing');
+      expect(await terminal.getLastLine()).toBe('zsh: event not found: nothing');
+    });
+
+    test('should expand inside double quotes but not single quotes', async () => {
+      await terminal.run('echo beta');
+      await terminal.run('echo "it\'s !!"');
+      expect(await terminal.getLastLine()).toBe("it's echo beta");
+      await terminal.run("echo 'not !!'");
+      expect(await terminal.getLastLine()).toBe('not !!');
+    });
+
+    test('should find commands with Ctrl+R', async () => {
+      await terminal.run('echo apple pie');
+      await terminal.run('echo banana');
+      await terminal.input.press('Control+r');
+      await terminal.page.keyboard.type('apple');
+      const search = terminal.session.locator('.terminal-search');
+      await expect(search).toContainText("(reverse-i-search)`apple': echo apple pie");
+      await expect(search.locator('.terminal-search-match')).toHaveText('apple');
+      await terminal.page.keyboard.press('Enter');
+      expect(await terminal.getLastLine()).toBe('apple pie');
+    });
+  });
+
+  test.describe('Aliases and functions', () => {
+    test('should define, list and remove aliases', async () => {
+      await terminal.run("alias ll='ls -a'");
+      await terminal.run('ll /Desktop');
+      expect(await terminal.getLastLine()).toContain('Notes.txt');
+      await terminal.run('alias');
+      expect(await terminal.getLastLine()).toBe("ll='ls -a'");
+      await terminal.run('unalias ll');
+      await terminal.run('ll');
+      expect(await terminal.getLastLine()).toBe('command not found: ll');
+    });
+
+    test('should keep aliases and functions after reload', async ({ page }) => {
+      await terminal.run("alias greet='echo hello'");
+      await terminal.run('shout() { echo "$1!"; }');
+      await page.reload();
+      await page.waitForSelector('.desktop');
+      const windowManager = new WindowManagerPage(page);
+      terminal = new TerminalPage(page, windowManager.getWindow('Terminal'));
+      await terminal.run('greet world');
+      expect(await terminal.getLastLine()).toBe('hello world');
+      await terminal.run('shout hey');
+      expect(await terminal.getLastLine()).toBe('hey!');
+      await terminal.run('functions shout');
+      expect(await terminal.getOutput()).toContain('shout () {');
+    });
+
+    test('should leave functions alone unless unset is given -f', async () => {
+      await terminal.run('twin() { echo function; }');
+      await terminal.run('twin=variable');
+      await terminal.run('unset twin');
+      await terminal.run('twin');
+      expect(await terminal.getLastLine()).toBe('function');
+      await terminal.run('unset -f twin');
+      await terminal.run('twin');
+      expect(await terminal.getLastLine()).toBe('command not found: twin');
+    });
+
+    test('should share aliases between tabs', async () => {
+      await terminal.run("alias here='pwd'");
+      await terminal.newTab();
+      await terminal.run('here');
+      expect(await terminal.getLastLine()).toBe('/');
+    });
+  });
+
+  test.describe('open', () => {
+    test('should launch an app with -a', async ({ page }) => {
+      await terminal.run('open -a calculator');
+      const windowManager = new WindowManagerPage(page);
+      await expect(windowManager.getWindow('Calculator')).toBeVisible();
+    });
+
+    test('should open the current directory in Finder', async ({ page }) => {
+      await terminal.run('cd /Documents && open .');
+      const windowManager = new WindowManagerPage(page);
+      const finder = new FinderPage(page, windowManager.getWindow('Finder'));
+      await expect(finder.toolbar.locator('.finder-toolbar-title')).toHaveText('Documents');
+    });
+
+    test('should open a text file in TextEdit', async ({ page }) => {
+      await terminal.run('echo hello from the shell > /Desktop/greeting.txt');
+      await terminal.run('open /Desktop/greeting.txt');
+      const windowManager = new WindowManagerPage(page);
+      const textEdit = windowManager.getWindow('TextEdit');
+      await expect(textEdit.locator('.textedit-document')).toHaveText('hello from the shell');
+    });
+
+    test('should open each file given in a window of its own', async ({ page }) => {
+      await terminal.run('echo first > /Desktop/a.txt');
+      await terminal.run('echo second > /Desktop/b.txt');
+      await terminal.run('open /Desktop/a.txt /Desktop/b.txt');
+      const windowManager = new WindowManagerPage(page);
+      const documents = windowManager.getWindow('TextEdit').locator('.textedit-document');
+      await expect(documents).toHaveCount(2);
+      await expect(documents.filter({ hasText: 'first' })).toHaveCount(1);
+      await expect(documents.filter({ hasText: 'second' })).toHaveCount(1);
+    });
+
+    test('should open a file and a folder together', async ({ page }) => {
+      await terminal.run('echo notes > /Documents/notes.txt');
+      await terminal.run('cd /Documents && open notes.txt /Desktop');
+      const windowManager = new WindowManagerPage(page);
+      await expect(windowManager.getWindow('TextEdit').locator('.textedit-document')).toHaveText('notes');
+      const finder = new FinderPage(page, windowManager.getWindow('Finder'));
+      await expect(finder.toolbar.locator('.finder-toolbar-title')).toHaveText('Desktop');
+    });
+
+    test('should report missing files and unknown apps', async () => {
+      await terminal.run('open missing.txt');
+      expect(await terminal.getLastLine()).toBe('The file /missing.txt does not exist.');
+      await terminal.run('open -a Nonexistent');
+      expect(await terminal.getLastLine()).toBe("Unable to find application named 'Nonexistent'");
+    });
+  });
+
+  test.describe('Processes', () => {
+    test('should list app windows with ps', async () => {
+      await terminal.run('ps -x | grep -c Calculator.app');
+      expect(await terminal.getLastLine()).toBe('1');
+      await terminal.run('ps');
+      expect(await terminal.getLastLine()).toMatch(/^\s*\d+ ttys000\s+\d+:\d+\.\d+ -zsh$/);
+    });
+
+    test('should close a window when its process is killed', async ({ page }) => {
+      await terminal.run('killall Calculator');
+      const windowManager = new WindowManagerPage(page);
+      await expect(windowManager.getWindow('Calculator')).toHaveCount(0);
+    });
+
+    test('should refuse to kill system processes', async () => {
+      await terminal.run('kill 1');
+      expect(await terminal.getLastLine()).toBe('kill: kill 1 failed: operation not permitted');
+      await terminal.run('kill 99999');
+      expect(await terminal.getLastLine()).toBe('kill: kill 99999 failed: no such process');
+    });
+
+    test('should show a process summary with top', async () => {
+      await terminal.run('top -o cpu -n 3');
+      const output = (await terminal.getOutput()).join('\n');
+      expect(output).toContain('Processes:');
+      expect(output).toMatch(/PID\s+COMMAND\s+%CPU/);
+    });
+  });
+
+  test.describe('Help and manuals', () => {
+    test('should print usage with --help', async () => {
+      await terminal.run('ls --help');
+      const output = (await terminal.getOutput()).join('\n');
+      expect(output).toContain('usage: ls [-aG] [file ...]');
+      expect(output).toContain('-a');
+    });
+
+    test('should report unknown options with usage', async () => {
+      await terminal.run('mkdir -z foo');
+      const output = await terminal.getOutput();
+      expect(output.slice(-2)).toEqual(['mkdir: illegal option -- z', 'usage: mkdir [-p] directory ...']);
+      await terminal.run('grep -z x; echo $?');
+      expect(await terminal.getLastLine()).toBe('2');
+    });
+
+    test('should list commands with help', async () => {
+      await terminal.run('help | grep -c man');
+      expect(Number(await terminal.getLastLine())).toBeGreaterThan(0);
+    });
+
+    test('should page and search a manual', async ({ page }) => {
+      await terminal.run('man grep');
+      await expect(terminal.pager).toBeVisible();
+      expect((await terminal.getPagerLines())[0]).toMatch(/^GREP\(1\)/);
+      expect(await terminal.getPagerStatus()).toContain('Manual page grep(1) line 1');
+      await page.keyboard.type('/GREP_COLOR');
+      await page.keyboard.press('Enter');
+      expect((await terminal.getPagerLines())[0]).toContain('GREP_COLOR');
+      await expect(terminal.pager.locator('.pager-match').first()).toHaveText('GREP_COLOR');
+      await page.keyboard.type('g');
+      expect((await terminal.getPagerLines())[0]).toMatch(/^GREP\(1\)/);
+      await page.keyboard.type('q');
+      await expect(terminal.pager).not.toBeVisible();
+      await expect(terminal.input).toBeFocused();
+    });
+
+    test('should print a manual into a pipe', async () => {
+      await terminal.run('man ls | grep -c SYNOPSIS');
+      expect(await terminal.getLastLine()).toBe('1');
+      await terminal.run('man nonexistent');
+      expect(await terminal.getLastLine()).toBe('No manual entry for nonexistent');
+    });
+
+    test('should search manuals regardless of case', async () => {
+      await terminal.run('man -k base64');
+      expect(await terminal.getLastLine()).toContain('encode and decode using Base64');
+      await terminal.run('man -k sha');
+      expect(await terminal.getLastLine()).toContain('print or check SHA checksums');
+    });
+  });
+
+  test.describe('less', () => {
+    test.beforeEach(async () => {
+      await terminal.run('rm -f /big.txt; i=1; while [ $i -le 200 ]; do echo line $i >> /big.txt; i=$((i+1)); done');
+    });
+
+    test('should page piped output', async ({ page }) => {
+      await terminal.run('cat /big.txt | less');
+      await expect(terminal.pager).toBeVisible();
+      expect((await terminal.getPagerLines())[0]).toBe('line 1');
+      expect(await terminal.getPagerStatus()).toBe(':');
+      await page.keyboard.press(' ');
+      expect((await terminal.getPagerLines())[0]).not.toBe('line 1');
+      await page.keyboard.press('b');
+      expect((await terminal.getPagerLines())[0]).toBe('line 1');
+      await page.keyboard.press('ArrowDown');
+      expect((await terminal.getPagerLines())[0]).toBe('line 2');
+      await page.keyboard.press('G');
+      expect(await terminal.getPagerStatus()).toBe('(END)');
+      await page.keyboard.press('q');
+      await expect(terminal.pager).not.toBeVisible();
+    });
+
+    test('should search forward and backward', async ({ page }) => {
+      await terminal.run('less /big.txt');
+      expect(await terminal.getPagerStatus()).toBe('/big.txt');
+      await page.keyboard.type('/line 1[0-9]0$');
+      await page.keyboard.press('Enter');
+      expect((await terminal.getPagerLines())[0]).toBe('line 100');
+      await page.keyboard.press('n');
+      expect((await terminal.getPagerLines())[0]).toBe('line 110');
+      await page.keyboard.press('N');
+      expect((await terminal.getPagerLines())[0]).toBe('line 100');
+      await page.keyboard.type('/nothing here');
+      await page.keyboard.press('Enter');
+      expect(await terminal.getPagerStatus()).toContain('Pattern not found');
+      await page.keyboard.press('q');
+      await expect(terminal.pager).not.toBeVisible();
+    });
+
+    test('should copy its input when not writing to the terminal', async () => {
+      await terminal.run('less -N /big.txt | grep -c line');
+      expect(await terminal.getLastLine()).toBe('200');
+    });
+
+    test('should keep files without a trailing newline on their own lines', async () => {
+      await terminal.run('echo -n first > /a.txt; echo -n second > /b.txt');
+      await terminal.run('less /a.txt /b.txt | grep -c "^first$"');
+      expect(await terminal.getLastLine()).toBe('1');
+      await terminal.run('less /a.txt /b.txt | grep -c "^::::::::::::::$"');
+      expect(await terminal.getLastLine()).toBe('4');
+    });
+  });
+
+  test.describe('Clipboard', () => {
+    test('should copy and paste with pbcopy and pbpaste', async () => {
+      await terminal.run('echo copied text | pbcopy');
+      await terminal.run('pbpaste');
+      expect(await terminal.getLastLine()).toBe('copied text');
+      await terminal.run('pbpaste | grep -c copied');
+      expect(await terminal.getLastLine()).toBe('1');
+    });
+
+    test('should require input for pbcopy', async () => {
+      await terminal.run('pbcopy; echo $?');
+      expect(await terminal.getLastLine()).toBe('1');
+    });
+
+    test('should share files copied in Finder', async ({ page }) => {
+      await terminal.run('echo notes > /Documents/clip.txt');
+      await terminal.run('open /Documents');
+      const windowManager = new WindowManagerPage(page);
+      const finder = new FinderPage(page, windowManager.getWindow('Finder'));
+      await finder.getFileItem('clip.txt').click({ button: 'right' });
+      await page.locator('.context-menu-item', { hasText: 'Copy' }).click();
+      await finder.contentArea.click({ button: 'right', position: { x: 5, y: 5 } });
+      await page.locator('.context-menu-item', { hasText: 'Paste Item' }).click();
+      await expect(finder.getFileItem('clip copy.txt')).toBeVisible();
+
+      await windowManager.getWindow('Terminal').evaluate((el) => {
+        el.dispatchEvent(new MouseEvent('mousedown', { bubbles: true }));
+      });
+      await terminal.run('pbpaste');
+      expect(await terminal.getLastLine()).toBe('clip.txt');
+    });
+  });
+
+  test.describe('defaults', () => {
+    test('should write, read and delete a preference', async () => {
+      await terminal.run('defaults write com.example.app count -int 3');
+      await terminal.run('defaults read com.example.app count');
+      expect(await terminal.getLastLine()).toBe('3');
+      await terminal.run('defaults read-type com.example.app count');
+      expect(await terminal.getLastLine()).toBe('Type is integer');
+      await terminal.run('defaults delete com.example.app count');
+      await terminal.run('defaults read com.example.app count');
+      expect(await terminal.getLastLine()).toBe(
+        'The domain/default pair of (com.example.app, count) does not exist'
+      );
+    });
+
+    test('should switch to dark mode live', async ({ page }) => {
+      await terminal.run('defaults write -g AppleInterfaceStyle Dark');
+      await expect(page.locator('html')).toHaveAttribute('data-theme', 'dark');
+      await terminal.run('defaults delete -g AppleInterfaceStyle');
+      await expect(page.locator('html')).toHaveAttribute('data-theme', 'light');
+    });
+
+    test('should update the Dock live', async ({ page }) => {
+      const dock = new DockPage(page);
+      await terminal.run('defaults write com.apple.dock persistent-apps -array Finder Terminal');
+      await expect(dock.getDockItem('Safari')).toHaveCount(0);
+      await expect(dock.getDockItem('Terminal')).toBeVisible();
+      await terminal.run('defaults delete com.apple.dock persistent-apps');
+      await expect(dock.getDockItem('Safari')).toBeVisible();
+    });
+
+    test('should open Finder in the preferred view', async ({ page }) => {
+      await terminal.run('defaults write com.apple.finder FXPreferredViewStyle Nlsv');
+      await terminal.run('open /Documents');
+      const windowManager = new WindowManagerPage(page);
+      const finder = new FinderPage(page, windowManager.getWindow('Finder'));
+      expect(await finder.isViewButtonActive(1)).toBe(true);
+    });
+  });
+
+  test.describe('Checksums and encodings', () => {
+    test('should encode and decode base64', async () => {
+      await terminal.run('echo hello | base64');
+      expect(await terminal.getLastLine()).toBe('aGVsbG8K');
+      await terminal.run('echo aGVsbG8K | base64 -d');
+      expect(await terminal.getLastLine()).toBe('hello');
+    });
+
+    test('should print digests', async () => {
+      await terminal.run('printf abc | shasum -a 256');
+      expect(await terminal.getLastLine()).toBe(
+        'ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  -'
+      );
+      await terminal.run('md5 -s abc');
+      expect(await terminal.getLastLine()).toBe('MD5 ("abc") = 900150983cd24fb0d6963f7d28e17f72');
+    });
+
+    test('should verify files with shasum -c', async () => {
+      await terminal.run('echo data > /import.txt');
+      await terminal.run('shasum -a 256 /import.txt > /import.sha256');
+      await terminal.run('shasum -c /import.sha256');
+      expect(await terminal.getLastLine()).toBe('/import.txt: OK');
+      await terminal.run('echo changed > /import.txt');
+      await terminal.run('shasum -c /import.sha256; echo $?');
+      expect(await terminal.getLastLine()).toBe('1');
+    });
+
+    test('should dump bytes in hex', async () => {
+      await terminal.run('echo hello | xxd');
+      expect(await terminal.getLastLine()).toBe(
+        '00000000: 6865 6c6c 6f0a                           hello.'
+      );
+      await terminal.run('echo hello | hexdump -C | grep -c "|hello.|"');
+      expect(await terminal.getLastLine()).toBe('1');
+      await terminal.run('echo hello | xxd -p | xxd -r -p');
+      expect(await terminal.getLastLine()).toBe('hello');
+    });
+
+    test('should reject bad hex and huge lengths without crashing', async () => {
+      await terminal.run('echo aéb | xxd -r -p');
+      expect(await terminal.getLastLine()).toBe('xxd: invalid hex input');
+      await terminal.run('echo 68656c6c6f0 | xxd -r -p');
+      expect(await terminal.getLastLine()).toBe('hello');
+      // The largest number a usize holds in wasm32
+      await terminal.run('echo hello | xxd -s 1 -l 4294967295 -p');
+      expect(await terminal.getLastLine()).toBe('656c6c6f0a');
+      await terminal.run('echo hello | hexdump -s 1 -n 4294967295 -C | grep -c "|ello.|"');
+      expect(await terminal.getLastLine()).toBe('1');
+      await terminal.run('echo hello | xxd -c 4294967295');
+      expect(await terminal.getLastLine()).toContain('6865 6c6c 6f0a');
+    });
+
+    test('should describe files by type', async () => {
+      await terminal.run('echo hi > /Desktop/note.txt');
+      await terminal.run('file /Desktop/note.txt');
+      expect(await terminal.getLastLine()).toBe('/Desktop/note.txt: Plain Text, ASCII text');
+      await terminal.run('file -b /Desktop');
+      expect(await terminal.getLastLine()).toBe('directory');
+    });
+  });
+
+  test.describe('nano', () => {
+    test('should edit and save a file', async ({ page }) => {
+      await terminal.run('nano /Desktop/todo.txt');
+      await expect(terminal.editor).toBeVisible();
+      expect(await terminal.getEditorStatus()).toContain('New File');
+      await page.keyboard.type('buy milk');
+      await page.keyboard.press('Enter');
+      await page.keyboard.type('walk dog');
+      await page.keyboard.press('Control+o');
+      await page.keyboard.press('Enter');
+      expect(await terminal.getEditorStatus()).toContain('Wrote 2 lines');
+      await page.keyboard.press('Control+x');
+      await expect(terminal.editor).not.toBeVisible();
+      await terminal.run('cat /Desktop/todo.txt');
+      expect(await terminal.getLastLine()).toBe('walk dog');
+    });
+
+    test('should search and ask before discarding changes', async ({ page }) => {
+      await terminal.run('nano /Desktop/Notes.txt');
+      await page.keyboard.press('Control+w');
+      expect(await terminal.getEditorStatus()).toContain('Search');
+      await page.keyboard.press('Escape');
+      await page.keyboard.type('x');
+      await page.keyboard.press('Control+x');
+      expect(await terminal.getEditorStatus()).toContain('Save modified buffer');
+      await page.keyboard.press('n');
+      await expect(terminal.editor).not.toBeVisible();
+    });
+  });
+});
+
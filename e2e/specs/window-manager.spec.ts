@@ -1,5 +1,8 @@
-import { test, expect } from '@playwright/test';
-import { WindowManagerPage, DesktopPage } from '../page-objects';
+import { test, expect, Page } from '@playwright/test';
+import { WindowManagerPage, DesktopPage, DockPage, MenuBarPage } from '../page-objects';
+
+const MENU_BAR_HEIGHT = 25;
+const DOCK_HEIGHT = 82;
 
 test.describe('Window Manager', () => {
   let windowManager: WindowManagerPage;
@@ -46,7 +49,6 @@ test.describe('Window Manager', () => {
     });
 
     test('should not allow dragging window above menu bar', async () => {
-      const MENU_BAR_HEIGHT = 25;
       const finder = windowManager.getWindow('Finder');
 
       // Try to drag window far above the menu bar
@@ -85,6 +87,29 @@ test.describe('Window Manager', () => {
       const newPos = await windowManager.getWindowPosition(finder);
       expect(newPos!.height).toBeGreaterThanOrEqual(100);
     });
+
+    test('should stop at the app\'s minimum and maximum size', async () => {
+      const calculator = windowManager.getWindow('Calculator');
+
+      await windowManager.resizeWindow(calculator, 'se', -500, -500);
+      let box = await windowManager.getWindowPosition(calculator);
+      expect(box!.width).toBe(240);
+      expect(box!.height).toBe(420);
+
+      await windowManager.resizeWindow(calculator, 'se', 600, 600);
+      box = await windowManager.getWindowPosition(calculator);
+      expect(box!.width).toBe(360);
+      expect(box!.height).toBeLessThanOrEqual(640);
+    });
+
+    test('should not resize the title bar under the menu bar', async () => {
+      const finder = windowManager.getWindow('Finder');
+
+      await windowManager.resizeWindow(finder, 'n', 0, -1000);
+
+      const box = await windowManager.getWindowPosition(finder);
+      expect(box!.y).toBeGreaterThanOrEqual(MENU_BAR_HEIGHT);
+    });
   });
 
   test.describe('Traffic Light Buttons', () => {
@@ -133,4 +158,715 @@ test.describe('Window Manager', () => {
       await expect(finder).not.toHaveClass(/maximized/);
     });
   });
+
+  test.describe('Window Tiling', () => {
+    test('should preview and snap to the left half when dragged to the left edge', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const viewport = page.viewportSize()!;
+
+      await windowManager.dragTitleBarTo(finder, 0, viewport.height / 2);
+      await expect(windowManager.getSnapPreview()).toBeVisible();
+      await page.mouse.up();
+
+      await expect(windowManager.getSnapPreview()).toHaveCount(0);
+      await expect(finder).toHaveClass(/tiled/);
+      const box = await windowManager.getWindowPosition(finder);
+      expect(box!.x).toBe(0);
+      expect(box!.y).toBe(25);
+      expect(Math.abs(box!.width - viewport.width / 2)).toBeLessThanOrEqual(1);
+      expect(box!.height).toBe(viewport.height - MENU_BAR_HEIGHT - DOCK_HEIGHT);
+    });
+
+    test('should snap to a quarter when dragged to a corner', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const viewport = page.viewportSize()!;
+
+      await windowManager.dragTitleBarTo(finder, viewport.width - 1, viewport.height - 1);
+      await page.mouse.up();
+
+      const box = await windowManager.getWindowPosition(finder);
+      expect(Math.abs(box!.x - viewport.width / 2)).toBeLessThanOrEqual(1);
+      expect(box!.y).toBeGreaterThan(MENU_BAR_HEIGHT);
+      expect(box!.x + box!.width).toBe(viewport.width);
+      expect(box!.y + box!.height).toBe(viewport.height - DOCK_HEIGHT);
+    });
+
+    test('should fill the screen when dragged to the top edge', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const viewport = page.viewportSize()!;
+
+      await windowManager.dragTitleBarTo(finder, viewport.width / 2, 0);
+      await page.mouse.up();
+
+      const box = await windowManager.getWindowPosition(finder);
+      expect(box!.width).toBe(viewport.width);
+      expect(box!.height).toBe(viewport.height - MENU_BAR_HEIGHT - DOCK_HEIGHT);
+    });
+
+    test('should return to its previous size when dragged out of a tile', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const viewport = page.viewportSize()!;
+      const original = await windowManager.getWindowPosition(finder);
+
+      await windowManager.dragTitleBarTo(finder, 0, viewport.height / 2);
+      await page.mouse.up();
+      await expect(finder).toHaveClass(/tiled/);
+
+      await windowManager.dragWindow(finder, 200, 100);
+
+      await expect(finder).not.toHaveClass(/tiled/);
+      const box = await windowManager.getWindowPosition(finder);
+      expect(box!.width).toBe(original!.width);
+      expect(box!.height).toBe(original!.height);
+    });
+
+    test('should tile with Control-Option shortcuts and restore with Control-Option-Delete', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const viewport = page.viewportSize()!;
+      await windowManager.getTitleBar(finder).click();
+      const original = await windowManager.getWindowPosition(finder);
+
+      await page.keyboard.press('Control+Alt+ArrowRight');
+      let box = await windowManager.getWindowPosition(finder);
+      expect(box!.x + box!.width).toBe(viewport.width);
+      expect(box!.height).toBe(viewport.height - MENU_BAR_HEIGHT - DOCK_HEIGHT);
+
+      await page.keyboard.press('Control+Alt+KeyU');
+      box = await windowManager.getWindowPosition(finder);
+      expect(box!.x).toBe(0);
+      expect(box!.y).toBe(25);
+
+      await page.keyboard.press('Control+Alt+Backspace');
+      await expect(finder).not.toHaveClass(/tiled/);
+      box = await windowManager.getWindowPosition(finder);
+      expect(box).toEqual(original);
+    });
+
+    test('should tile from Window > Move & Resize', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const viewport = page.viewportSize()!;
+      await windowManager.getTitleBar(finder).click();
+
+      const windowMenu = page
+        .locator('.menu-item')
+        .filter({ has: page.locator(':scope > span', { hasText: /^Window$/ }) });
+      await windowMenu.click();
+      await windowMenu.locator('.dropdown-submenu', { hasText: 'Move & Resize' }).hover();
+      await windowMenu
+        .locator('.submenu-dropdown .dropdown-item')
+        .filter({ has: page.locator('span', { hasText: /^Bottom$/ }) })
+        .click();
+
+      await expect(finder).toHaveClass(/tiled/);
+      const box = await windowManager.getWindowPosition(finder);
+      expect(box!.width).toBe(viewport.width);
+      expect(box!.y + box!.height).toBe(viewport.height - DOCK_HEIGHT);
+    });
+
+    test('should leave other Control-Option keys to the rest of the desktop', async ({ page }) => {
+      const calculator = windowManager.getWindow('Calculator');
+      await windowManager.getTitleBar(calculator).click();
+
+      // Control stands in for Command, so Control-Option-W still closes
+      await page.keyboard.press('Control+Alt+KeyW');
+
+      await expect(calculator).toHaveCount(0);
+    });
+  });
+
+  test.describe('Mission Control', () => {
+    test('should lay out every open window with its title on F3', async ({ page }) => {
+      const visibleWindows = await page.locator('.window:not(.minimized)').count();
+
+      await page.keyboard.press('F3');
+
+      await expect(page.locator('.windows-container')).toHaveClass(/mission-control/);
+      await expect(windowManager.getMissionControlThumbnails()).toHaveCount(visibleWindows);
+      await expect(windowManager.getMissionControlThumbnail('Finder')).toBeVisible();
+      await expect(windowManager.getMissionControlThumbnail('Calculator')).toBeVisible();
+    });
+
+    test('should not overlap thumbnails', async ({ page }) => {
+      await page.keyboard.press('Control+ArrowUp');
+
+      const thumbs = windowManager.getMissionControlThumbnails();
+      await expect(thumbs.first()).toBeVisible();
+      const boxes = [];
+      for (const thumb of await thumbs.all()) {
+        boxes.push((await thumb.boundingBox())!);
+      }
+      for (let i = 0; i < boxes.length; i++) {
+        for (let j = i + 1; j < boxes.length; j++) {
+          const [a, b] = [boxes[i], boxes[j]];
+          const apart =
+            a.x + a.width <= b.x || b.x + b.width <= a.x ||
+            a.y + a.height <= b.y || b.y + b.height <= a.y;
+          expect(apart).toBe(true);
+        }
+      }
+    });
+
+    test('should bring the clicked window to front and leave', async () => {
+      const calculator = windowManager.getWindow('Calculator');
+      await windowManager.getTitleBar(windowManager.getWindow('Finder')).click();
+      expect(await windowManager.isWindowActive(calculator)).toBe(false);
+
+      await windowManager.page.keyboard.press('F3');
+      await windowManager.getMissionControlThumbnail('Calculator').click();
+
+      await expect(windowManager.getMissionControlThumbnails()).toHaveCount(0);
+      expect(await windowManager.isWindowActive(calculator)).toBe(true);
+      await expect(windowManager.page.locator('.windows-container')).not.toHaveClass(/mission-control/);
+      await expect(calculator).not.toHaveCSS('transform', /matrix/);
+    });
+
+    test('should leave on Escape, returning windows to their frames', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const before = await windowManager.getWindowPosition(finder);
+
+      await page.keyboard.press('F3');
+      await expect(windowManager.getMissionControlThumbnails().first()).toBeVisible();
+      await page.keyboard.press('Escape');
+
+      await expect(page.locator('.windows-container')).not.toHaveClass(/mission-control/);
+      expect(await windowManager.getWindowPosition(finder)).toEqual(before);
+    });
+  });
+
+  test.describe('Spaces', () => {
+    test('should start with one desktop in Mission Control and add another', async ({ page }) => {
+      await page.keyboard.press('F3');
+      await expect(page.locator('.space-button')).toHaveCount(1);
+
+      await windowManager.getAddSpaceButton().click();
+
+      await expect(page.locator('.space-button')).toHaveCount(2);
+      await expect(windowManager.getSpaceButton('Desktop 1')).toHaveClass(/current/);
+    });
+
+    test('should switch to an empty Space with Control-Right and back with Control-Left', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      await windowManager.ensureSpaces(2);
+      await page.keyboard.press('Escape');
+
+      await page.keyboard.press('Control+ArrowRight');
+      await expect(finder).toBeHidden();
+
+      await page.keyboard.press('Control+ArrowLeft');
+      await expect(finder).toBeVisible();
+    });
+
+    test('should switch Spaces from Mission Control', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      await windowManager.ensureSpaces(2);
+
+      await windowManager.getSpaceButton('Desktop 2').click();
+
+      await expect(windowManager.getMissionControlThumbnails()).toHaveCount(0);
+      await expect(finder).toBeHidden();
+    });
+
+    test('should carry the active window to the next Space with Control-Shift-Right', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const calculator = windowManager.getWindow('Calculator');
+      await windowManager.ensureSpaces(2);
+      await page.keyboard.press('Escape');
+      await windowManager.getTitleBar(finder).click();
+
+      await page.keyboard.press('Control+Shift+ArrowRight');
+
+      await expect(finder).toBeVisible();
+      await expect(calculator).toBeHidden();
+    });
+
+    test('should move a window by dropping its thumbnail on a Space', async ({ page }) => {
+      const calculator = windowManager.getWindow('Calculator');
+      await windowManager.ensureSpaces(2);
+
+      const thumb = windowManager.getMissionControlThumbnail('Calculator');
+      const from = (await thumb.boundingBox())!;
+      const to = (await windowManager.getSpaceButton('Desktop 2').boundingBox())!;
+      await page.mouse.move(from.x + from.width / 2, from.y + from.height / 2);
+      await page.mouse.down();
+      await page.mouse.move(to.x + to.width / 2, to.y + to.height / 2, { steps: 10 });
+      await page.mouse.up();
+
+      await expect(thumb).toHaveCount(0);
+      await expect(page.locator('.windows-container')).toHaveClass(/mission-control/);
+
+      await windowManager.getSpaceButton('Desktop 2').click();
+      await expect(calculator).toBeVisible();
+      await expect(windowManager.getWindow('Finder')).toBeHidden();
+    });
+
+    test('should keep Space assignments across reloads', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      await windowManager.ensureSpaces(2);
+      await page.keyboard.press('Escape');
+      await windowManager.getTitleBar(finder).click();
+      await page.keyboard.press('Control+Shift+ArrowRight');
+
+      await page.reload();
+      await expect(finder).toBeVisible();
+      await expect(windowManager.getWindow('Calculator')).toBeHidden();
+
+      await page.keyboard.press('Control+ArrowLeft');
+      await expect(finder).toBeHidden();
+      await expect(windowManager.getWindow('Calculator')).toBeVisible();
+    });
+
+    test('should move windows of a removed Space to the one before it', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      await windowManager.ensureSpaces(2);
+      await page.keyboard.press('Escape');
+      await windowManager.getTitleBar(finder).click();
+      await page.keyboard.press('Control+Shift+ArrowRight');
+
+      await page.keyboard.press('F3');
+      const desktop2 = windowManager.getSpaceButton('Desktop 2');
+      await desktop2.hover();
+      await desktop2.locator('.space-remove').click();
+
+      await expect(page.locator('.space-button')).toHaveCount(1);
+      await page.keyboard.press('Escape');
+      await expect(finder).toBeVisible();
+      await expect(windowManager.getWindow('Calculator')).toBeVisible();
+    });
+  });
+
+  test.describe('Full Screen', () => {
+    test('should enter full screen with Control-Command-F on a Space of its own', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const viewport = page.viewportSize()!;
+      await windowManager.getTitleBar(finder).click();
+
+      await page.keyboard.press('Control+Meta+KeyF');
+
+      await expect(finder).toHaveClass(/fullscreen/);
+      await expect(windowManager.getWindow('Calculator')).toBeHidden();
+      const box = await windowManager.getWindowPosition(finder);
+      expect(box).toEqual({ x: 0, y: 0, width: viewport.width, height: viewport.height });
+
+      await page.keyboard.press('F3');
+      await expect(page.locator('.space-button')).toHaveCount(2);
+      await expect(windowManager.getSpaceButton('Finder')).toHaveClass(/current/);
+    });
+
+    test('should hide the menu bar and Dock until the pointer reaches the screen edge', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const viewport = page.viewportSize()!;
+      await windowManager.getTitleBar(finder).click();
+      await page.keyboard.press('Control+Meta+KeyF');
+
+      const menuBar = page.locator('.menu-bar');
+      const dock = page.locator('.dock-wrapper');
+      await expect(menuBar).toHaveClass(/autohide/);
+      await expect(dock).toHaveClass(/autohide/);
+      await page.mouse.move(viewport.width / 2, viewport.height / 2);
+      await expect.poll(async () => (await menuBar.boundingBox())!.y).toBeLessThan(0);
+
+      await page.mouse.move(viewport.width / 2, 1);
+      await expect.poll(async () => (await menuBar.boundingBox())!.y).toBe(0);
+
+      await page.mouse.move(viewport.width / 2, viewport.height - 1);
+      await expect
+        .poll(async () => {
+          const box = (await dock.boundingBox())!;
+          return box.y + box.height;
+        })
+        .toBeLessThan(viewport.height);
+    });
+
+    test('should exit full screen back to the original Space', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const before = await windowManager.getWindowPosition(finder);
+      await windowManager.getTitleBar(finder).click();
+      await page.keyboard.press('Control+Meta+KeyF');
+      await expect(finder).toHaveClass(/fullscreen/);
+
+      await page.keyboard.press('Control+Meta+KeyF');
+
+      await expect(finder).not.toHaveClass(/fullscreen/);
+      await expect(windowManager.getWindow('Calculator')).toBeVisible();
+      await expect(page.locator('.menu-bar')).not.toHaveClass(/autohide/);
+      expect(await windowManager.getWindowPosition(finder)).toEqual(before);
+      await page.keyboard.press('F3');
+      await expect(page.locator('.space-button')).toHaveCount(1);
+    });
+
+    test('should remove the full-screen Space when its window closes', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      await windowManager.getTitleBar(finder).click();
+      await page.keyboard.press('Control+Meta+KeyF');
+
+      await windowManager.getCloseButton(finder).click();
+
+      await expect(windowManager.getWindow('Calculator')).toBeVisible();
+      await page.keyboard.press('F3');
+      await expect(page.locator('.space-button')).toHaveCount(1);
+    });
+
+    test('should not minimize a full-screen window', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      await windowManager.getTitleBar(finder).click();
+      await page.keyboard.press('Control+Meta+KeyF');
+      await expect(finder).toHaveClass(/fullscreen/);
+
+      await page.keyboard.press('Meta+KeyM');
+      await windowManager.getMinimizeButton(finder).click({ force: true });
+      await page.waitForTimeout(500);
+
+      expect(await windowManager.isWindowMinimized(finder)).toBe(false);
+      await expect(finder).toHaveClass(/fullscreen/);
+      await expect(page.locator('.minimized-item')).toHaveCount(0);
+    });
+
+    test('should stay in full screen after a reload', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      await windowManager.getTitleBar(finder).click();
+      await page.keyboard.press('Control+Meta+KeyF');
+      await expect(finder).toHaveClass(/fullscreen/);
+
+      await page.reload();
+
+      await expect(finder).toHaveClass(/fullscreen/);
+      await expect(page.locator('.menu-bar')).toHaveClass(/autohide/);
+      await page.keyboard.press('Control+ArrowLeft');
+      await expect(windowManager.getWindow('Calculator')).toBeVisible();
+      await expect(page.locator('.menu-bar')).not.toHaveClass(/autohide/);
+    });
+
+    test('should toggle from View > Enter Full Screen', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      await windowManager.getTitleBar(finder).click();
+
+      const viewMenu = page
+        .locator('.menu-item')
+        .filter({ has: page.locator(':scope > span', { hasText: /^View$/ }) });
+      await viewMenu.click();
+      await viewMenu.locator('.dropdown-item', { hasText: 'Enter Full Screen' }).click();
+
+      await expect(finder).toHaveClass(/fullscreen/);
+    });
+  });
+
+  test.describe('Window Menu and Cycling', () => {
+    const menu = (page: Page, name: string) =>
+      page.locator('.menu-item').filter({ has: page.locator(':scope > span', { hasText: new RegExp(`^${name}$`) }) });
+
+    const newFinderWindow = async (page: Page) => {
+      await windowManager.getTitleBar(windowManager.getWindow('Finder')).click();
+      await menu(page, 'File').click();
+      await menu(page, 'File').locator('.dropdown-item', { hasText: 'New Window' }).click();
+      await expect(windowManager.getWindow('Finder')).toHaveCount(2);
+    };
+
+    test('should open another window of the active app from File > New Window', async ({ page }) => {
+      await newFinderWindow(page);
+
+      const finders = windowManager.getWindow('Finder');
+      expect(await windowManager.isWindowActive(finders.nth(1))).toBe(true);
+      await expect(menu(page, 'File')).not.toHaveClass(/active/);
+    });
+
+    test('should cycle through the active app windows with Command-backtick', async ({ page }) => {
+      await newFinderWindow(page);
+      const finders = windowManager.getWindow('Finder');
+
+      await page.keyboard.press('Meta+Backquote');
+      expect(await windowManager.isWindowActive(finders.nth(0))).toBe(true);
+
+      await page.keyboard.press('Meta+Backquote');
+      expect(await windowManager.isWindowActive(finders.nth(1))).toBe(true);
+      expect(await windowManager.isWindowActive(windowManager.getWindow('Calculator'))).toBe(false);
+    });
+
+    test('should minimize the active window with Command-M', async ({ page }) => {
+      const calculator = windowManager.getWindow('Calculator');
+      await windowManager.getTitleBar(calculator).click();
+
+      await page.keyboard.press('Meta+KeyM');
+
+      await expect(calculator).toHaveClass(/minimized/);
+    });
+
+    test('should minimize every window of the app with Option-Command-M', async ({ page }) => {
+      await newFinderWindow(page);
+
+      await page.keyboard.press('Meta+Alt+KeyM');
+
+      await expect(windowManager.getWindow('Finder').nth(0)).toHaveClass(/minimized/);
+      await expect(windowManager.getWindow('Finder').nth(1)).toHaveClass(/minimized/);
+      await expect(windowManager.getWindow('Calculator')).not.toHaveClass(/minimized/);
+    });
+
+    test('should hide other apps with Option-Command-H', async ({ page }) => {
+      const calculator = windowManager.getWindow('Calculator');
+      await windowManager.getTitleBar(calculator).click();
+
+      await page.keyboard.press('Meta+Alt+KeyH');
+
+      await expect(calculator).toBeVisible();
+      await expect(windowManager.getWindow('Finder')).toBeHidden();
+    });
+
+    test('should list the app windows in the Window menu and focus the chosen one', async ({ page }) => {
+      await newFinderWindow(page);
+      const windowMenu = menu(page, 'Window');
+
+      await windowMenu.click();
+      const listed = windowMenu.locator('.window-list-item');
+      await expect(listed).toHaveCount(2);
+      await expect(listed.nth(1).locator('.dropdown-check')).toHaveText('✓');
+
+      await listed.nth(0).click();
+
+      expect(await windowManager.isWindowActive(windowManager.getWindow('Finder').nth(0))).toBe(true);
+      await expect(windowMenu).not.toHaveClass(/active/);
+    });
+
+    test('should raise every window of the app with Bring All to Front', async ({ page }) => {
+      await newFinderWindow(page);
+      const calculator = windowManager.getWindow('Calculator');
+      const finders = windowManager.getWindow('Finder');
+      await windowManager.getTitleBar(calculator).click();
+      await windowManager.getTitleBar(finders.nth(0)).click({ position: { x: 100, y: 10 } });
+
+      const windowMenu = menu(page, 'Window');
+      await windowMenu.click();
+      await windowMenu.locator('.dropdown-item', { hasText: 'Bring All to Front' }).click();
+
+      const calculatorZ = await windowManager.getZIndex(calculator);
+      expect(await windowManager.getZIndex(finders.nth(0))).toBeGreaterThan(calculatorZ);
+      expect(await windowManager.getZIndex(finders.nth(1))).toBeGreaterThan(calculatorZ);
+    });
+  });
+
+  test.describe('Hidden Apps', () => {
+    const hideCalculator = async (page: Page) => {
+      const calculator = windowManager.getWindow('Calculator');
+      await windowManager.getTitleBar(calculator).click();
+      await page.keyboard.press('Meta+KeyH');
+      await expect(calculator).toBeHidden();
+    };
+
+    test('should hide every window of the app without Dock thumbnails', async ({ page }) => {
+      await hideCalculator(page);
+
+      await expect(windowManager.getWindow('Calculator')).not.toHaveClass(/minimized/);
+      await expect(page.locator('.minimized-item')).toHaveCount(0);
+    });
+
+    test('should show a hidden app again from its Dock icon', async ({ page }) => {
+      await hideCalculator(page);
+
+      await new DockPage(page).clickDockItem('Calculator');
+
+      const calculator = windowManager.getWindow('Calculator');
+      await expect(calculator).toBeVisible();
+      expect(await windowManager.isWindowActive(calculator)).toBe(true);
+    });
+
+    test('should show a translucent icon in the App Switcher and switch back to it', async ({ page }) => {
+      await hideCalculator(page);
+
+      await page.keyboard.down('Meta');
+      await page.keyboard.press('Tab');
+      const item = windowManager.getAppSwitcherItem('Calculator');
+      await expect(item).toHaveClass(/hidden-app/);
+      await expect(windowManager.getAppSwitcherItem('Finder')).not.toHaveClass(/hidden-app/);
+      await item.hover();
+      await page.keyboard.up('Meta');
+
+      await expect(page.locator('.app-switcher-backdrop')).toHaveCount(0);
+      await expect(windowManager.getWindow('Calculator')).toBeVisible();
+    });
+
+    test('should hide other apps and show them all again', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      await windowManager.getTitleBar(finder).click();
+
+      await page.keyboard.press('Meta+Alt+KeyH');
+      await expect(windowManager.getWindow('Calculator')).toBeHidden();
+      await expect(windowManager.getWindow('Notes')).toBeHidden();
+      await expect(finder).toBeVisible();
+
+      const appMenu = page.locator('.menu-item.app-name');
+      await appMenu.click();
+      await appMenu.locator('.dropdown-item', { hasText: 'Show All' }).click();
+      await expect(windowManager.getWindow('Calculator')).toBeVisible();
+      await expect(windowManager.getWindow('Notes')).toBeVisible();
+    });
+  });
+
+  test.describe('Viewport Constraints', () => {
+    test('should pull windows back within reach when the viewport shrinks', async ({ page }) => {
+      const textEdit = windowManager.getWindow('TextEdit');
+
+      await page.setViewportSize({ width: 800, height: 500 });
+
+      await expect
+        .poll(async () => (await windowManager.getWindowPosition(textEdit))!.x)
+        .toBeLessThanOrEqual(800 - 100);
+      for (const title of ['Finder', 'Terminal', 'Notes', 'Calculator']) {
+        const box = await windowManager.getWindowPosition(windowManager.getWindow(title));
+        expect(box!.x).toBeLessThanOrEqual(800 - 100);
+        expect(box!.y).toBeGreaterThanOrEqual(MENU_BAR_HEIGHT);
+        expect(box!.height).toBeLessThanOrEqual(500 - MENU_BAR_HEIGHT);
+      }
+    });
+
+    test('should clamp saved frames that no longer fit when loading', async ({ page }) => {
+      await page.evaluate(() => {
+        const state = JSON.parse(localStorage.getItem('virtualmac_desktop')!);
+        state.windows[0].x = 5000;
+        state.windows[0].y = -300;
+        localStorage.setItem('virtualmac_desktop', JSON.stringify(state));
+      });
+
+      await page.reload();
+
+      const viewport = page.viewportSize()!;
+      const box = await windowManager.getWindowPosition(windowManager.getWindow('Finder'));
+      expect(box!.x).toBeLessThanOrEqual(viewport.width - 100);
+      expect(box!.y).toBe(MENU_BAR_HEIGHT);
+    });
+
+    test('should keep maximized and tiled windows filling the new viewport', async ({ page }) => {
+      const finder = windowManager.getWindow('Finder');
+      const calculator = windowManager.getWindow('Calculator');
+      await windowManager.getMaximizeButton(finder).click();
+      await windowManager.getTitleBar(calculator).click();
+      await page.keyboard.press('Control+Alt+ArrowRight');
+
+      await page.setViewportSize({ width: 900, height: 600 });
+
+      await expect
+        .poll(async () => (await windowManager.getWindowPosition(finder))!.width)
+        .toBe(900);
+      const tiled = await windowManager.getWindowPosition(calculator);
+      expect(tiled!.x).toBe(450);
+      expect(tiled!.width).toBe(450);
+      expect(tiled!.height).toBe(600 - MENU_BAR_HEIGHT - DOCK_HEIGHT);
+    });
+  });
+
+  test.describe('Stage Manager', () => {
+    test('should move other apps into the strip when turned on from Control Center', async ({ page }) => {
+      const menuBar = new MenuBarPage(page);
+      const finder = windowManager.getWindow('Finder');
+      await windowManager.getTitleBar(finder).click();
+
+      await menuBar.toggleStageManager();
+
+      await expect(page.locator('.windows-container')).toHaveClass(/stage-manager/);
+      expect(await windowManager.isWindowInStrip(finder)).toBe(false);
+      for (const title of ['Terminal', 'TextEdit', 'Calculator']) {
+        expect(await windowManager.isWindowInStrip(windowManager.getWindow(title))).toBe(true);
+        await expect(windowManager.getStageStripItem(title)).toBeVisible();
+      }
+      await expect(page.locator('.stage-strip-item')).toHaveCount(3);
+
+      await menuBar.toggleStageManager();
+      await expect(page.locator('.stage-strip-item')).toHaveCount(0);
+      expect(await windowManager.isWindowInStrip(windowManager.getWindow('Terminal'))).toBe(false);
+    });
+
+    test('should swap an app onto the stage, centered, when its strip group is clicked', async ({ page }) => {
+      const menuBar = new MenuBarPage(page);
+      await windowManager.getTitleBar(windowManager.getWindow('Finder')).click();
+      await menuBar.toggleStageManager();
+
+      const calculator = windowManager.getWindow('Calculator');
+      await windowManager.getStageStripItem('Calculator').click();
+
+      await expect.poll(() => windowManager.isWindowInStrip(calculator)).toBe(false);
+      expect(await windowManager.isWindowActive(calculator)).toBe(true);
+      expect(await windowManager.isWindowInStrip(windowManager.getWindow('Finder'))).toBe(true);
+      await expect(windowManager.getStageStripItem('Finder')).toBeVisible();
+
+      // Centered in the room beside the strip
+      const viewport = page.viewportSize()!;
+      await expect
+        .poll(async () => {
+          const box = (await windowManager.getWindowPosition(calculator))!;
+          return Math.round(box.x + box.width / 2);
+        })
+        .toBe(Math.round(170 + (viewport.width - 170) / 2));
+    });
+
+    test('should put the stage app back where it was when turned off', async ({ page }) => {
+      const menuBar = new MenuBarPage(page);
+      const calculator = windowManager.getWindow('Calculator');
+      const before = await windowManager.getWindowPosition(calculator);
+      await windowManager.getTitleBar(windowManager.getWindow('Finder')).click();
+      await menuBar.toggleStageManager();
+
+      await windowManager.getStageStripItem('Calculator').click();
+      await expect
+        .poll(async () => (await windowManager.getWindowPosition(calculator))!.x)
+        .not.toBe(before!.x);
+
+      await menuBar.toggleStageManager();
+      await expect
+        .poll(async () => windowManager.getWindowPosition(calculator))
+        .toEqual(before);
+    });
+
+    test('should center a new window of the stage app with the rest of it', async ({ page }) => {
+      const menuBar = new MenuBarPage(page);
+      const fileMenu = page
+        .locator('.menu-item')
+        .filter({ has: page.locator(':scope > span', { hasText: /^File$/ }) });
+      await windowManager.getTitleBar(windowManager.getWindow('Finder')).click();
+      await menuBar.toggleStageManager();
+
+      await fileMenu.click();
+      await fileMenu.locator('.dropdown-item', { hasText: 'New Window' }).click();
+
+      const finders = windowManager.getWindow('Finder');
+      await expect(finders).toHaveCount(2);
+      const viewport = page.viewportSize()!;
+      await expect
+        .poll(async () => {
+          const boxes = await Promise.all(
+            (await finders.all()).map((finder) => windowManager.getWindowPosition(finder))
+          );
+          const left = Math.min(...boxes.map((box) => box!.x));
+          const right = Math.max(...boxes.map((box) => box!.x + box!.width));
+          return Math.round((left + right) / 2);
+        })
+        .toBe(Math.round(170 + (viewport.width - 170) / 2));
+      for (const finder of await finders.all()) {
+        expect((await windowManager.getWindowPosition(finder))!.x).toBeGreaterThanOrEqual(170);
+        expect(await windowManager.isWindowInStrip(finder)).toBe(false);
+      }
+    });
+
+    test('should keep every window of the stage app together', async ({ page }) => {
+      const menuBar = new MenuBarPage(page);
+      const fileMenu = page
+        .locator('.menu-item')
+        .filter({ has: page.locator(':scope > span', { hasText: /^File$/ }) });
+      await windowManager.getTitleBar(windowManager.getWindow('Finder')).click();
+      await fileMenu.click();
+      await fileMenu.locator('.dropdown-item', { hasText: 'New Window' }).click();
+      await menuBar.toggleStageManager();
+
+      const finders = windowManager.getWindow('Finder');
+      await expect(finders).toHaveCount(2);
+      for (const finder of await finders.all()) {
+        expect(await windowManager.isWindowInStrip(finder)).toBe(false);
+      }
+      await expect(windowManager.getStageStripItem('Finder')).toHaveCount(0);
+    });
+
+    test('should stay on after a reload', async ({ page }) => {
+      const menuBar = new MenuBarPage(page);
+      await menuBar.toggleStageManager();
+      await page.reload();
+      await expect(page.locator('.windows-container')).toHaveClass(/stage-manager/);
+      await expect(page.locator('.cc-stage-manager .cc-focus-status')).toHaveText('On');
+    });
+  });
 });
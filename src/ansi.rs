@@ -0,0 +1,299 @@
+//! ANSI escape sequence handling for the Terminal.
+//!
+//! Commands emit SGR sequences (`ESC [ ... m`) for color and emphasis; the
+//! Terminal turns each output line into styled spans. Other CSI sequences
+//! (cursor movement, erase) are dropped.
+
+/// Reset all attributes
+pub const RESET: &str = "\x1b[0m";
+
+/// Terminal.app's default 16-color palette
+const PALETTE: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xc2, 0x36, 0x21),
+    (0x25, 0xbc, 0x24),
+    (0xad, 0xad, 0x27),
+    (0x49, 0x2e, 0xe1),
+    (0xd3, 0x38, 0xd3),
+    (0x33, 0xbb, 0xc8),
+    (0xcb, 0xcc, 0xcd),
+    (0x81, 0x83, 0x83),
+    (0xfc, 0x39, 0x1f),
+    (0x31, 0xe7, 0x22),
+    (0xea, 0xec, 0x23),
+    (0x58, 0x33, 0xff),
+    (0xf9, 0x35, 0xf8),
+    (0x14, 0xf0, 0xf0),
+    (0xe9, 0xeb, 0xeb),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Color {
+    /// 256-color palette index (0-15 are the basic colors)
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::Rgb(r, g, b) => (r, g, b),
+            Color::Indexed(n @ 0..=15) => PALETTE[n as usize],
+            // 6x6x6 color cube
+            Color::Indexed(n @ 16..=231) => {
+                let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+                let n = n - 16;
+                (level(n / 36), level((n / 6) % 6), level(n % 6))
+            }
+            // Grayscale ramp
+            Color::Indexed(n) => {
+                let v = 8 + (n - 232) * 10;
+                (v, v, v)
+            }
+        }
+    }
+
+    fn css(self) -> String {
+        let (r, g, b) = self.rgb();
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    /// SGR parameters selecting this color as foreground (`base` 30) or
+    /// background (`base` 40)
+    fn sgr(self, base: u16) -> String {
+        match self {
+            Color::Indexed(n) if n < 8 => (base + n as u16).to_string(),
+            Color::Indexed(n) if n < 16 => (base + 60 + (n - 8) as u16).to_string(),
+            Color::Indexed(n) => format!("{};5;{}", base + 8, n),
+            Color::Rgb(r, g, b) => format!("{};2;{};{};{}", base + 8, r, g, b),
+        }
+    }
+}
+
+/// Text attributes set by SGR sequences
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub inverse: bool,
+    pub strikethrough: bool,
+}
+
+impl Style {
+    /// Apply the parameters of one SGR sequence
+    fn apply(&mut self, params: &[u16]) {
+        if params.is_empty() {
+            *self = Style::default();
+            return;
+        }
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.inverse = true,
+                9 => self.strikethrough = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.inverse = false,
+                29 => self.strikethrough = false,
+                n @ 30..=37 => self.fg = Some(Color::Indexed((n - 30) as u8)),
+                39 => self.fg = None,
+                n @ 40..=47 => self.bg = Some(Color::Indexed((n - 40) as u8)),
+                49 => self.bg = None,
+                n @ 90..=97 => self.fg = Some(Color::Indexed((n - 90 + 8) as u8)),
+                n @ 100..=107 => self.bg = Some(Color::Indexed((n - 100 + 8) as u8)),
+                n @ (38 | 48) => {
+                    let color = match params.get(i + 1) {
+                        Some(5) => {
+                            let color = params.get(i + 2).map(|&v| Color::Indexed(v as u8));
+                            i += 2;
+                            color
+                        }
+                        Some(2) => {
+                            let channel = |k: usize| params.get(i + k).copied().unwrap_or(0) as u8;
+                            let color = Color::Rgb(channel(2), channel(3), channel(4));
+                            i += 4;
+                            Some(color)
+                        }
+                        _ => None,
+                    };
+                    if n == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    /// The SGR sequence that recreates this style from the default
+    fn to_sgr(self) -> String {
+        if self == Style::default() {
+            return String::new();
+        }
+        let mut codes: Vec<String> = Vec::new();
+        for (on, code) in [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+            (self.inverse, "7"),
+            (self.strikethrough, "9"),
+        ] {
+            if on {
+                codes.push(code.to_string());
+            }
+        }
+        if let Some(fg) = self.fg {
+            codes.push(fg.sgr(30));
+        }
+        if let Some(bg) = self.bg {
+            codes.push(bg.sgr(40));
+        }
+        format!("\x1b[{}m", codes.join(";"))
+    }
+
+    /// Inline CSS for a span in this style
+    pub fn css(&self) -> String {
+        let (fg, bg) = if self.inverse {
+            (
+                self.bg.map_or("var(--terminal-bg)".to_string(), Color::css),
+                Some(
+                    self.fg
+                        .map_or("var(--terminal-text)".to_string(), Color::css),
+                ),
+            )
+        } else {
+            (
+                self.fg.map_or(String::new(), Color::css),
+                self.bg.map(Color::css),
+            )
+        };
+        let mut css = String::new();
+        if !fg.is_empty() {
+            css.push_str(&format!("color: {};", fg));
+        }
+        if let Some(bg) = bg {
+            css.push_str(&format!("background: {};", bg));
+        }
+        if self.bold {
+            css.push_str("font-weight: bold;");
+        }
+        if self.dim {
+            css.push_str("opacity: 0.6;");
+        }
+        if self.italic {
+            css.push_str("font-style: italic;");
+        }
+        match (self.underline, self.strikethrough) {
+            (true, true) => css.push_str("text-decoration: underline line-through;"),
+            (true, false) => css.push_str("text-decoration: underline;"),
+            (false, true) => css.push_str("text-decoration: line-through;"),
+            (false, false) => {}
+        }
+        css
+    }
+}
+
+/// A run of text in a single style
+#[derive(Clone, Debug, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+/// Split text into styled spans, starting from (and updating) `style`
+pub fn parse(text: &str, style: &mut Style) -> Vec<Span> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            current.push(c);
+            continue;
+        }
+        if chars.peek() != Some(&'[') {
+            // Lone ESC or a non-CSI sequence: drop the introducer
+            chars.next();
+            continue;
+        }
+        chars.next();
+        let mut params = String::new();
+        let mut command = None;
+        for c in chars.by_ref() {
+            if ('\x40'..='\x7e').contains(&c) {
+                command = Some(c);
+                break;
+            }
+            params.push(c);
+        }
+        if command != Some('m') {
+            continue;
+        }
+        if !current.is_empty() {
+            spans.push(Span {
+                text: std::mem::take(&mut current),
+                style: *style,
+            });
+        }
+        let params: Vec<u16> = params
+            .split([';', ':'])
+            .map(|p| p.parse().unwrap_or(0))
+            .collect();
+        // `ESC[m` is a reset
+        style.apply(if params == [0] { &[] } else { &params });
+    }
+    if !current.is_empty() {
+        spans.push(Span {
+            text: current,
+            style: *style,
+        });
+    }
+    spans
+}
+
+/// Split output into lines that each render correctly on their own: a style
+/// still active at a line break is re-opened at the start of the next line.
+pub fn split_lines(text: &str) -> Vec<String> {
+    let mut style = Style::default();
+    text.lines()
+        .map(|line| {
+            let prefix = style.to_sgr();
+            parse(line, &mut style);
+            if prefix.is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect()
+}
+
+/// Wrap text in an SGR sequence and a reset
+pub fn paint(text: &str, sgr: &str) -> String {
+    format!("\x1b[{}m{}{}", sgr, text, RESET)
+}
+
+/// Remove every escape sequence, leaving the plain text
+pub fn strip(text: &str) -> String {
+    parse(text, &mut Style::default())
+        .into_iter()
+        .map(|span| span.text)
+        .collect()
+}
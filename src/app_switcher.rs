@@ -2,61 +2,80 @@ use leptos::prelude::*;
 use wasm_bindgen::prelude::*;
 use web_sys::KeyboardEvent;
 
+use crate::system_state::SystemState;
+use crate::window_manager::AppType;
+
 /// Represents an app that can be switched to
 #[derive(Clone, Debug, PartialEq)]
 pub struct SwitchableApp {
     pub name: String,
     pub icon: &'static str,
+    pub is_hidden: bool,
 }
 
 impl SwitchableApp {
-    fn all() -> Vec<Self> {
-        vec![
-            SwitchableApp {
-                name: "Finder".to_string(),
-                icon: "\u{1F4C1}",
-            },
-            SwitchableApp {
-                name: "Calculator".to_string(),
-                icon: "\u{1F5A9}",
-            },
-            SwitchableApp {
-                name: "Notes".to_string(),
-                icon: "\u{1F4DD}",
-            },
-            SwitchableApp {
-                name: "Safari".to_string(),
-                icon: "\u{1F310}",
-            },
-            SwitchableApp {
-                name: "Messages".to_string(),
-                icon: "\u{1F4AC}",
-            },
-            SwitchableApp {
-                name: "Mail".to_string(),
-                icon: "\u{2709}",
-            },
-            SwitchableApp {
-                name: "Photos".to_string(),
-                icon: "\u{1F5BC}",
-            },
-            SwitchableApp {
-                name: "Music".to_string(),
-                icon: "\u{1F3B5}",
-            },
-        ]
+    /// Running apps, most recently used first. Finder is always running.
+    fn running(system_state: SystemState, recent: &[String]) -> Vec<Self> {
+        let mut running = system_state.open_windows.get_untracked();
+        if !running.iter().any(|app| app == "Finder") {
+            running.push("Finder".to_string());
+        }
+        let mut names: Vec<String> = recent
+            .iter()
+            .filter(|app| running.contains(app))
+            .cloned()
+            .collect();
+        for app in running {
+            if !names.contains(&app) {
+                names.push(app);
+            }
+        }
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let app_type = AppType::from_name(&name)?;
+                Some(SwitchableApp {
+                    is_hidden: system_state.is_app_hidden(&name),
+                    name,
+                    icon: app_type.icon(),
+                })
+            })
+            .collect()
     }
 }
 
 /// App switcher overlay component (Cmd+Tab)
 #[component]
 pub fn AppSwitcher() -> impl IntoView {
+    let system_state = expect_context::<SystemState>();
     let (is_visible, set_is_visible) = signal(false);
     let (selected_index, set_selected_index) = signal(0usize);
     let (cmd_held, set_cmd_held) = signal(false);
+    let apps = RwSignal::new(Vec::<SwitchableApp>::new());
+    let recent = RwSignal::new(Vec::<String>::new());
+
+    // Keep apps in the order they were last used
+    Effect::new(move |_| {
+        let active = system_state.active_app.get();
+        recent.update(|recent| {
+            recent.retain(|app| *app != active);
+            recent.insert(0, active);
+        });
+    });
 
-    let apps = SwitchableApp::all();
-    let app_count = apps.len();
+    let close = move || {
+        set_is_visible.set(false);
+        set_cmd_held.set(false);
+        set_selected_index.set(0);
+    };
+
+    // Switch to the chosen app, bringing it back if hidden
+    let select = move |index: usize| {
+        if let Some(app) = apps.with_untracked(|apps| apps.get(index).cloned()) {
+            system_state.request_open_app(&app.name);
+        }
+        close();
+    };
 
     // Set up global keyboard listener for Cmd+Tab
     Effect::new(move |_| {
@@ -69,16 +88,21 @@ pub fn AppSwitcher() -> impl IntoView {
 
                 if !is_visible.get() {
                     // First press: show switcher, select second item (cycling from current)
+                    apps.set(
+                        recent
+                            .with_untracked(|recent| SwitchableApp::running(system_state, recent)),
+                    );
                     set_is_visible.set(true);
                     set_cmd_held.set(true);
                     // Start at index 1 (next app) if there are multiple apps
-                    if app_count > 1 {
+                    if apps.with_untracked(Vec::len) > 1 {
                         set_selected_index.set(1);
                     } else {
                         set_selected_index.set(0);
                     }
                 } else {
                     // Subsequent Tab presses while visible: cycle through apps
+                    let app_count = apps.with_untracked(Vec::len).max(1);
                     if e.shift_key() {
                         // Shift+Tab: cycle backward
                         set_selected_index.update(|i| {
@@ -100,9 +124,7 @@ pub fn AppSwitcher() -> impl IntoView {
             // Escape to close without selecting
             if e.code() == "Escape" && is_visible.get() {
                 e.prevent_default();
-                set_is_visible.set(false);
-                set_cmd_held.set(false);
-                set_selected_index.set(0);
+                close();
             }
         }) as Box<dyn FnMut(KeyboardEvent)>);
 
@@ -115,11 +137,7 @@ pub fn AppSwitcher() -> impl IntoView {
                 && is_visible.get()
                 && cmd_held.get()
             {
-                // Selection happens - close the switcher
-                set_is_visible.set(false);
-                set_cmd_held.set(false);
-                // Note: In a real implementation, this would trigger window focus
-                // For now, we just close the overlay
+                select(selected_index.get_untracked());
             }
         }) as Box<dyn FnMut(KeyboardEvent)>);
 
@@ -137,14 +155,7 @@ pub fn AppSwitcher() -> impl IntoView {
     });
 
     // Handle clicking outside to close
-    let on_backdrop_click = move |_| {
-        set_is_visible.set(false);
-        set_cmd_held.set(false);
-        set_selected_index.set(0);
-    };
-
-    // Create app items once as static data
-    let apps_signal = StoredValue::new(SwitchableApp::all());
+    let on_backdrop_click = move |_| close();
 
     view! {
         <Show when=move || is_visible.get()>
@@ -152,29 +163,30 @@ pub fn AppSwitcher() -> impl IntoView {
                 <div class="app-switcher-container" on:click=move |e: web_sys::MouseEvent| e.stop_propagation()>
                     <div class="app-switcher-apps">
                         {move || {
-                            apps_signal.get_value().into_iter().enumerate().map(|(index, app)| {
+                            apps.get().into_iter().enumerate().map(|(index, app)| {
                                 let is_selected = move || selected_index.get() == index;
+                                let is_hidden = app.is_hidden;
                                 let item_class = move || {
+                                    let mut classes = vec!["app-switcher-item"];
                                     if is_selected() {
-                                        "app-switcher-item selected"
-                                    } else {
-                                        "app-switcher-item"
+                                        classes.push("selected");
+                                    }
+                                    if is_hidden {
+                                        classes.push("hidden-app");
                                     }
+                                    classes.join(" ")
                                 };
-                                let name = app.name.clone();
-                                let icon = app.icon;
 
                                 view! {
                                     <div
                                         class=item_class
                                         on:mouseenter=move |_| set_selected_index.set(index)
-                                        on:click=move |_| {
-                                            set_is_visible.set(false);
-                                            set_cmd_held.set(false);
-                                        }
+                                        on:click=move |_| select(index)
                                     >
-                                        <div class="app-switcher-icon">{icon}</div>
-                                        <div class="app-switcher-name">{name}</div>
+                                        <div class="app-switcher-icon">
+                                            {app.icon}
+                                        </div>
+                                        <div class="app-switcher-name">{app.name}</div>
                                     </div>
                                 }
                             }).collect::<Vec<_>>()
@@ -5,6 +5,8 @@ use wasm_bindgen::prelude::*;
 #[allow(unused_imports)]
 use wasm_bindgen::JsCast;
 
+use crate::window_manager::use_window_app_state;
+
 #[allow(dead_code)]
 const STORAGE_KEY: &str = "virtualmac_calculator";
 #[allow(dead_code)]
@@ -58,7 +60,7 @@ fn load_from_storage() -> CalculatorState {
     CalculatorState::new()
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 enum Operation {
     None,
     Add,
@@ -67,12 +69,36 @@ enum Operation {
     Divide,
 }
 
+/// The calculation in progress, kept with the window across reloads
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct CalculatorWindowState {
+    display: String,
+    stored_value: f64,
+    operation: Operation,
+    clear_on_next: bool,
+}
+
+impl Default for CalculatorWindowState {
+    fn default() -> Self {
+        Self {
+            display: String::from("0"),
+            stored_value: 0.0,
+            operation: Operation::None,
+            clear_on_next: false,
+        }
+    }
+}
+
 #[component]
 pub fn Calculator() -> impl IntoView {
-    let (display, set_display) = signal(String::from("0"));
-    let (stored_value, set_stored_value) = signal(0.0f64);
-    let (current_op, set_current_op) = signal(Operation::None);
-    let (clear_on_next, set_clear_on_next) = signal(false);
+    let window_state = use_window_app_state();
+    let saved = window_state
+        .and_then(|w| w.restore::<CalculatorWindowState>())
+        .unwrap_or_default();
+    let (display, set_display) = signal(saved.display);
+    let (stored_value, set_stored_value) = signal(saved.stored_value);
+    let (current_op, set_current_op) = signal(saved.operation);
+    let (clear_on_next, set_clear_on_next) = signal(saved.clear_on_next);
     let (active_operator, set_active_operator) = signal::<Option<Operation>>(None);
 
     // Memory state with persistence
@@ -84,6 +110,19 @@ pub fn Calculator() -> impl IntoView {
         save_to_storage(&current_state);
     });
 
+    // Keep the calculation with the window
+    Effect::new(move |_| {
+        let state = CalculatorWindowState {
+            display: display.get(),
+            stored_value: stored_value.get(),
+            operation: current_op.get(),
+            clear_on_next: clear_on_next.get(),
+        };
+        if let Some(window_state) = window_state {
+            window_state.save(&state);
+        }
+    });
+
     // Memory operations
     let memory_add = move || {
         let current = strip_separators(&display.get());
@@ -0,0 +1,221 @@
+//! The system pasteboard, shared by Terminal (`pbcopy`/`pbpaste`), TextEdit,
+//! Notes and Finder, and mirrored to the browser clipboard where the browser
+//! allows it.
+
+use leptos::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = document)]
+    fn execCommand(command: &str, show_ui: bool, value: &str) -> bool;
+}
+
+/// What was last copied
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClipboardContent {
+    Text(String),
+    /// Paths of files and folders copied in Finder
+    Files(Vec<String>),
+}
+
+#[derive(Clone, Copy)]
+pub struct Clipboard {
+    content: RwSignal<Option<ClipboardContent>>,
+    /// Whether the browser clipboard holds the same text, so what it offers
+    /// on paste is at least as recent as our copy
+    synced: RwSignal<bool>,
+}
+
+impl Clipboard {
+    pub fn new() -> Self {
+        Self {
+            content: RwSignal::new(None),
+            synced: RwSignal::new(false),
+        }
+    }
+
+    /// Current contents (tracked, so menus can enable Paste)
+    pub fn content(&self) -> Option<ClipboardContent> {
+        self.content.get()
+    }
+
+    /// Contents as plain text; copied files paste as their names
+    pub fn text(&self) -> Option<String> {
+        self.content.with_untracked(|content| match content {
+            Some(ClipboardContent::Text(text)) => Some(text.clone()),
+            Some(ClipboardContent::Files(paths)) => Some(file_names(paths)),
+            None => None,
+        })
+    }
+
+    /// Paths copied in Finder, if the clipboard holds files
+    pub fn files(&self) -> Vec<String> {
+        self.content.with_untracked(|content| match content {
+            Some(ClipboardContent::Files(paths)) => paths.clone(),
+            _ => Vec::new(),
+        })
+    }
+
+    pub fn copy_text(&self, text: String) {
+        self.write_system(&text);
+        self.content.set(Some(ClipboardContent::Text(text)));
+    }
+
+    pub fn copy_files(&self, paths: Vec<String>) {
+        self.write_system(&file_names(&paths));
+        self.content.set(Some(ClipboardContent::Files(paths)));
+    }
+
+    /// Record text the browser already put on the system clipboard (a
+    /// native copy, or text read back from the browser)
+    pub fn record_system_text(&self, text: String) {
+        self.synced.set(true);
+        self.content.set(Some(ClipboardContent::Text(text)));
+    }
+
+    /// The text a paste should insert, given what the browser's paste event
+    /// carries. The browser's text wins unless our last copy never reached
+    /// it, in which case it's stale.
+    fn paste_text(&self, system: Option<String>) -> Option<String> {
+        let system = system.filter(|text| !text.is_empty());
+        let stale = self.content.with_untracked(Option::is_some) && !self.synced.get_untracked();
+        match system {
+            Some(text) if !stale => {
+                if self.text().as_ref() != Some(&text) {
+                    self.record_system_text(text.clone());
+                }
+                Some(text)
+            }
+            _ => self.text(),
+        }
+    }
+
+    /// Handle a paste into an editable element. The browser pastes its own
+    /// clipboard as usual, unless that's stale and our text goes in instead.
+    pub fn handle_paste(&self, e: &web_sys::Event) {
+        use wasm_bindgen::JsCast;
+        let Some(e) = e.dyn_ref::<web_sys::ClipboardEvent>() else {
+            return;
+        };
+        let system = e
+            .clipboard_data()
+            .and_then(|data| data.get_data("text/plain").ok());
+        let Some(text) = self.paste_text(system.clone()) else {
+            return;
+        };
+        if system.as_deref() != Some(text.as_str()) {
+            e.prevent_default();
+            #[cfg(target_arch = "wasm32")]
+            execCommand("insertText", false, &text);
+        }
+    }
+
+    /// Copy text to the browser clipboard, marking the clipboard synced once
+    /// the browser accepts it
+    fn write_system(&self, text: &str) {
+        self.synced.set(false);
+        #[cfg(target_arch = "wasm32")]
+        {
+            let Some(clipboard) = system_clipboard() else {
+                return;
+            };
+            let promise = clipboard.write_text(text);
+            let synced = self.synced;
+            wasm_bindgen_futures::spawn_local(async move {
+                if wasm_bindgen_futures::JsFuture::from(promise).await.is_ok() {
+                    synced.set(true);
+                }
+            });
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = text;
+    }
+
+    /// Pick up text copied in another app or tab, if the browser lets us
+    /// read the clipboard
+    #[cfg(target_arch = "wasm32")]
+    pub fn refresh_from_system(&self) {
+        let Some(clipboard) = system_clipboard() else {
+            return;
+        };
+        let this = *self;
+        wasm_bindgen_futures::spawn_local(async move {
+            let Ok(value) = wasm_bindgen_futures::JsFuture::from(clipboard.read_text()).await
+            else {
+                return;
+            };
+            if let Some(text) = value.as_string().filter(|text| !text.is_empty()) {
+                // Copied files are mirrored as their names; keep the paths
+                if this.text().as_ref() != Some(&text) {
+                    this.record_system_text(text);
+                } else {
+                    this.synced.set(true);
+                }
+            }
+        });
+    }
+}
+
+impl Default for Clipboard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn file_names(paths: &[String]) -> String {
+    paths
+        .iter()
+        .map(|path| path.rsplit('/').next().unwrap_or(path))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The async clipboard API, which is missing outside secure contexts
+#[cfg(target_arch = "wasm32")]
+fn system_clipboard() -> Option<web_sys::Clipboard> {
+    use wasm_bindgen::JsCast;
+    let navigator = web_sys::window()?.navigator();
+    js_sys::Reflect::get(&navigator, &"clipboard".into())
+        .ok()
+        .filter(|value| !value.is_undefined() && !value.is_null())
+        .map(|value| value.unchecked_into())
+}
+
+/// Keep the clipboard in step with copies made natively in the page (text
+/// selected in TextEdit, Notes or Terminal) and in other apps
+pub fn listen_for_system_copies(clipboard: Clipboard) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        use wasm_bindgen::JsCast;
+
+        let Some(window) = web_sys::window() else {
+            return;
+        };
+        let on_copy = Closure::wrap(Box::new(move || {
+            let selection = web_sys::window()
+                .and_then(|w| w.get_selection().ok().flatten())
+                .and_then(|s| s.to_string().as_string());
+            if let Some(text) = selection.filter(|text| !text.is_empty()) {
+                clipboard.record_system_text(text);
+            }
+        }) as Box<dyn Fn()>);
+        if let Some(document) = window.document() {
+            for event in ["copy", "cut"] {
+                let _ = document
+                    .add_event_listener_with_callback(event, on_copy.as_ref().unchecked_ref());
+            }
+        }
+        on_copy.forget();
+
+        let on_focus = Closure::wrap(Box::new(move || {
+            clipboard.refresh_from_system();
+        }) as Box<dyn Fn()>);
+        let _ = window.add_event_listener_with_callback("focus", on_focus.as_ref().unchecked_ref());
+        on_focus.forget();
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = clipboard;
+}
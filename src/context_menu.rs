@@ -1,5 +1,7 @@
 use leptos::prelude::*;
 
+use crate::clipboard::{Clipboard, ClipboardContent};
+
 /// Represents the type of context for the menu
 #[derive(Clone, Debug, PartialEq)]
 #[allow(dead_code)]
@@ -55,7 +57,6 @@ impl ContextMenuItem {
         self
     }
 
-    #[allow(dead_code)]
     pub fn disabled(mut self) -> Self {
         self.disabled = true;
         self
@@ -71,12 +72,22 @@ impl ContextMenuItem {
     }
 }
 
-/// Get menu items based on context type
-fn get_menu_items(menu_type: &ContextMenuType) -> Vec<ContextMenuItem> {
+/// Get menu items based on context type; `can_paste` enables Paste Item
+/// when files are on the clipboard
+fn get_menu_items(menu_type: &ContextMenuType, can_paste: bool) -> Vec<ContextMenuItem> {
+    let paste_item = if can_paste {
+        ContextMenuItem::new("Paste Item").with_shortcut("⌘V")
+    } else {
+        ContextMenuItem::new("Paste Item")
+            .with_shortcut("⌘V")
+            .disabled()
+    };
     match menu_type {
         ContextMenuType::Desktop => vec![
             ContextMenuItem::new("New Folder"),
             ContextMenuItem::separator(),
+            paste_item,
+            ContextMenuItem::separator(),
             ContextMenuItem::new("Get Info"),
             ContextMenuItem::new("Change Desktop Background..."),
             ContextMenuItem::separator(),
@@ -169,7 +180,10 @@ pub fn ContextMenu(
         set_state.update(|s| s.visible = false);
     };
 
-    let items = move || get_menu_items(&state.get().menu_type);
+    let clipboard = use_context::<Clipboard>();
+    let can_paste =
+        move || clipboard.is_some_and(|c| matches!(c.content(), Some(ClipboardContent::Files(_))));
+    let items = move || get_menu_items(&state.get().menu_type, can_paste());
 
     // Store on_action in a signal so it can be used in closures
     let on_action_stored = StoredValue::new(on_action);
@@ -201,10 +215,14 @@ pub fn ContextMenu(
                         };
                         let shortcut = item.shortcut;
                         let label = item.label;
+                        let disabled = item.disabled;
                         view! {
                             <div
                                 class=class
                                 on:click=move |_| {
+                                    if disabled {
+                                        return;
+                                    }
                                     // Call the action callback if provided
                                     if let Some(callback) = on_action_stored.get_value() {
                                         callback.run(label.to_string());
@@ -1,33 +1,14 @@
 use crate::context_menu::{show_context_menu, ContextMenuState, ContextMenuType};
+use crate::preferences::{Preferences, DOCK_DOMAIN};
 use crate::system_state::{MinimizedWindow, SystemState};
 use leptos::ev::MouseEvent;
 use leptos::prelude::*;
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 #[allow(unused_imports)]
 use wasm_bindgen::prelude::wasm_bindgen;
 use wasm_bindgen::JsCast;
 
-#[allow(dead_code)]
-const STORAGE_KEY: &str = "virtualmac_dock";
-#[allow(dead_code)]
-const CURRENT_SCHEMA_VERSION: u32 = 1;
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct DockState {
-    schema_version: u32,
-    pinned_apps: Vec<String>,
-}
-
-impl DockState {
-    fn default_with_pins() -> Self {
-        Self {
-            schema_version: CURRENT_SCHEMA_VERSION,
-            pinned_apps: default_pinned_apps(),
-        }
-    }
-}
-
+/// Apps pinned when the Dock preferences don't list any
 fn default_pinned_apps() -> Vec<String> {
     vec![
         "Finder".to_string(),
@@ -45,41 +26,17 @@ fn default_pinned_apps() -> Vec<String> {
     ]
 }
 
-fn save_to_storage(state: &DockState) {
-    #[cfg(target_arch = "wasm32")]
-    {
-        if let Some(window) = web_sys::window() {
-            if let Ok(Some(storage)) = window.local_storage() {
-                if let Ok(json) = serde_json::to_string(state) {
-                    let _ = storage.set_item(STORAGE_KEY, &json);
-                }
-            }
-        }
-    }
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        let _ = state;
-    }
-}
-
-fn load_from_storage() -> DockState {
-    #[cfg(target_arch = "wasm32")]
+/// Largest scale an icon grows to under the pointer, or none when
+/// magnification is turned off
+fn max_scale(preferences: Preferences) -> f64 {
+    if preferences
+        .get_bool(DOCK_DOMAIN, "magnification")
+        .unwrap_or(true)
     {
-        if let Some(window) = web_sys::window() {
-            if let Ok(Some(storage)) = window.local_storage() {
-                if let Ok(Some(json)) = storage.get_item(STORAGE_KEY) {
-                    if let Ok(state) = serde_json::from_str::<DockState>(&json) {
-                        if state.schema_version == CURRENT_SCHEMA_VERSION
-                            && !state.pinned_apps.is_empty()
-                        {
-                            return state;
-                        }
-                    }
-                }
-            }
-        }
+        1.8
+    } else {
+        1.0
     }
-    DockState::default_with_pins()
 }
 
 /// Represents a dock item (app icon)
@@ -139,6 +96,7 @@ fn DockIcon(
 
     // Capture system_state at component creation time, not in event handler
     let system_state = expect_context::<SystemState>();
+    let preferences = expect_context::<Preferences>();
 
     // Calculate position-based scale when mouse moves
     Effect::new(move |_| {
@@ -148,7 +106,7 @@ fn DockIcon(
         if hovering && mx > 0.0 {
             // Approximate item center based on index (56px item width + 6px gap)
             let item_center = 62.0 * (index as f64) + 24.0;
-            let new_scale = calculate_scale(item_center, mx, 1.8, 120.0);
+            let new_scale = calculate_scale(item_center, mx, max_scale(preferences), 120.0);
             set_scale.set(new_scale);
         } else {
             set_scale.set(1.0);
@@ -216,6 +174,7 @@ fn TrashIcon(
     index: usize,
     context_menu_state: WriteSignal<ContextMenuState>,
 ) -> impl IntoView {
+    let preferences = expect_context::<Preferences>();
     let (scale, set_scale) = signal(1.0);
 
     Effect::new(move |_| {
@@ -224,7 +183,7 @@ fn TrashIcon(
 
         if hovering && mx > 0.0 {
             let item_center = 62.0 * (index as f64) + 24.0;
-            let new_scale = calculate_scale(item_center, mx, 1.8, 120.0);
+            let new_scale = calculate_scale(item_center, mx, max_scale(preferences), 120.0);
             set_scale.set(new_scale);
         } else {
             set_scale.set(1.0);
@@ -295,7 +254,7 @@ pub fn Dock(context_menu_state: WriteSignal<ContextMenuState>) -> impl IntoView
     let (mouse_x, set_mouse_x) = signal(0.0);
     let (is_hovering, set_is_hovering) = signal(false);
 
-    let (dock_state, _set_dock_state) = signal(load_from_storage());
+    let preferences = expect_context::<Preferences>();
 
     let app_catalog: HashMap<&'static str, (&'static str, &'static str)> = HashMap::from([
         ("Finder", ("📂", "finder")),
@@ -312,17 +271,13 @@ pub fn Dock(context_menu_state: WriteSignal<ContextMenuState>) -> impl IntoView
         ("Terminal", (">_", "terminal")),
     ]);
 
-    Effect::new(move |_| {
-        let current_state = dock_state.get();
-        save_to_storage(&current_state);
-    });
-
     let dock_items = move || {
         let running_apps = system_state.open_windows.get();
         let active_app = system_state.active_app.get();
-        dock_state
-            .get()
-            .pinned_apps
+        preferences
+            .get_strings(DOCK_DOMAIN, "persistent-apps")
+            .filter(|apps| !apps.is_empty())
+            .unwrap_or_else(default_pinned_apps)
             .into_iter()
             .filter_map(|app_name| {
                 let app_name_str = app_name.as_str();
@@ -363,7 +318,13 @@ pub fn Dock(context_menu_state: WriteSignal<ContextMenuState>) -> impl IntoView
     let has_minimized = move || !minimized_windows().is_empty();
 
     view! {
-        <div class="dock-wrapper">
+        <div class=move || {
+            if system_state.full_screen.get() {
+                "dock-wrapper autohide"
+            } else {
+                "dock-wrapper"
+            }
+        }>
             <div class="dock-container">
                 <div
                     class="dock"
@@ -412,7 +373,7 @@ pub fn Dock(context_menu_state: WriteSignal<ContextMenuState>) -> impl IntoView
                                 let idx = num_apps();
                                 if hovering && mx > 0.0 {
                                     let item_center = 62.0 * (idx as f64) + 44.0; // +44 for separator
-                                    let scale = calculate_scale(item_center, mx, 1.8, 120.0);
+                                    let scale = calculate_scale(item_center, mx, max_scale(preferences), 120.0);
                                     format!(
                                         "scale({}) translateY({}px)",
                                         scale,
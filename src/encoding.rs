@@ -0,0 +1,380 @@
+//! Byte encodings and message digests behind Terminal's `base64`, `shasum`,
+//! `md5`, `xxd` and `hexdump`.
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode bytes as standard base64 with padding
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (index, &byte)| {
+                group | (byte as u32) << (16 - 8 * index)
+            });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (group >> (18 - 6 * index)) & 0x3f;
+                text.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// Decode base64, ignoring whitespace. Fails on any other character outside
+/// the alphabet.
+pub fn base64_decode(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+    let mut group = 0u32;
+    let mut count = 0;
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+        if c == '=' {
+            break;
+        }
+        let Some(value) = BASE64_ALPHABET.iter().position(|&a| a as char == c) else {
+            return Err("invalid input".to_string());
+        };
+        group = group << 6 | value as u32;
+        count += 1;
+        if count == 4 {
+            bytes.extend_from_slice(&group.to_be_bytes()[1..]);
+            group = 0;
+            count = 0;
+        }
+    }
+    match count {
+        0 => {}
+        2 => bytes.push((group >> 4) as u8),
+        3 => bytes.extend_from_slice(&((group >> 2) as u16).to_be_bytes()),
+        _ => return Err("invalid input".to_string()),
+    }
+    Ok(bytes)
+}
+
+/// Lowercase hex digits for a digest
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Pad a message into 64-byte blocks as MD5 and SHA-1/2 do, with the bit
+/// length written big- or little-endian
+fn pad_message(message: &[u8], big_endian: bool) -> Vec<u8> {
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    let bits = (message.len() as u64).wrapping_mul(8);
+    if big_endian {
+        padded.extend_from_slice(&bits.to_be_bytes());
+    } else {
+        padded.extend_from_slice(&bits.to_le_bytes());
+    }
+    padded
+}
+
+/// floor(abs(sin(i + 1)) * 2^32) for each round
+const MD5_CONSTANTS: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+pub fn md5(message: &[u8]) -> Vec<u8> {
+    const SHIFTS: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5,
+        9, 14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10,
+        15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    for block in pad_message(message, false).chunks(64) {
+        let words: Vec<u32> = block
+            .chunks(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect();
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a
+                .wrapping_add(f)
+                .wrapping_add(MD5_CONSTANTS[i])
+                .wrapping_add(words[g])
+                .rotate_left(SHIFTS[i]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(rotated);
+        }
+        for (word, value) in state.iter_mut().zip([a, b, c, d]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+    state.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+pub fn sha1(message: &[u8]) -> Vec<u8> {
+    let mut state: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    for block in pad_message(message, true).chunks(64) {
+        let mut words = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            words[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, &word) in words.iter().enumerate() {
+            let (f, k) = match i / 20 {
+                0 => ((b & c) | (!b & d), 0x5a827999),
+                1 => (b ^ c ^ d, 0x6ed9eba1),
+                2 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+    state.iter().flat_map(|word| word.to_be_bytes()).collect()
+}
+
+const SHA256_ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-256 compression over the padded message, from the given initial
+/// state; SHA-224 differs only in its initial state and output length
+fn sha256_from(message: &[u8], mut state: [u32; 8]) -> [u32; 8] {
+    for block in pad_message(message, true).chunks(64) {
+        let mut words = [0u32; 64];
+        for (i, word) in block.chunks(4).enumerate() {
+            words[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = words[i - 15].rotate_right(7)
+                ^ words[i - 15].rotate_right(18)
+                ^ (words[i - 15] >> 3);
+            let s1 = words[i - 2].rotate_right(17)
+                ^ words[i - 2].rotate_right(19)
+                ^ (words[i - 2] >> 10);
+            words[i] = words[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(words[i - 7])
+                .wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for (&word, &constant) in words.iter().zip(&SHA256_ROUND_CONSTANTS) {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let temp1 = h
+                .wrapping_add(s1)
+                .wrapping_add(choice)
+                .wrapping_add(constant)
+                .wrapping_add(word);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let temp2 = s0.wrapping_add(majority);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1.wrapping_add(temp2);
+        }
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+    state
+}
+
+pub fn sha224(message: &[u8]) -> Vec<u8> {
+    let state = sha256_from(
+        message,
+        [
+            0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7,
+            0xbefa4fa4,
+        ],
+    );
+    state[..7]
+        .iter()
+        .flat_map(|word| word.to_be_bytes())
+        .collect()
+}
+
+pub fn sha256(message: &[u8]) -> Vec<u8> {
+    let state = sha256_from(
+        message,
+        [
+            0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+            0x5be0cd19,
+        ],
+    );
+    state.iter().flat_map(|word| word.to_be_bytes()).collect()
+}
+
+/// A byte as shown in the text column of a dump
+fn printable(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+/// Most bytes `xxd -c` puts on a line, as in real xxd
+pub const XXD_MAX_COLUMNS: usize = 256;
+
+/// `xxd`'s default dump: offset, hex in pairs of bytes, and the text
+pub fn xxd(bytes: &[u8], columns: usize, start: usize) -> String {
+    let columns = columns.clamp(1, XXD_MAX_COLUMNS);
+    // Hex digits plus a space after every second byte
+    let width = columns * 2 + (columns - 1) / 2;
+    let mut text = String::new();
+    for (row, chunk) in bytes.chunks(columns).enumerate() {
+        let mut hex_part = String::new();
+        for (index, byte) in chunk.iter().enumerate() {
+            if index > 0 && index % 2 == 0 {
+                hex_part.push(' ');
+            }
+            hex_part.push_str(&format!("{:02x}", byte));
+        }
+        let ascii: String = chunk.iter().map(|&byte| printable(byte)).collect();
+        text.push_str(&format!(
+            "{:08x}: {:<width$}  {}\n",
+            start + row * columns,
+            hex_part,
+            ascii,
+            width = width
+        ));
+    }
+    text
+}
+
+/// `xxd -p`: plain hex, 30 bytes to a line
+pub fn xxd_plain(bytes: &[u8]) -> String {
+    bytes
+        .chunks(30)
+        .map(|chunk| format!("{}\n", hex(chunk)))
+        .collect()
+}
+
+/// Turn an `xxd` dump (or plain hex with `plain`) back into bytes
+pub fn xxd_reverse(text: &str, plain: bool) -> Result<Vec<u8>, String> {
+    let mut digits = String::new();
+    for line in text.lines() {
+        if plain {
+            digits.extend(line.chars().filter(|c| !c.is_whitespace()));
+            continue;
+        }
+        // The hex column runs from after the offset to the two spaces
+        // before the text column
+        let Some((_, rest)) = line.split_once(": ") else {
+            continue;
+        };
+        let hex_part = rest.split("  ").next().unwrap_or_default();
+        digits.extend(hex_part.chars().filter(|c| !c.is_whitespace()));
+    }
+    // Pair up hex digits as bytes, ignoring an odd one at the end
+    let nibbles = digits
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| "invalid hex input".to_string())?;
+    Ok(nibbles
+        .chunks_exact(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect())
+}
+
+/// `hexdump`'s default format: 16-bit little-endian words in hex
+pub fn hexdump(bytes: &[u8], start: usize) -> String {
+    if bytes.is_empty() {
+        return String::new();
+    }
+    dump_rows(bytes, start, |offset, chunk| {
+        let words: Vec<String> = chunk
+            .chunks(2)
+            .map(|pair| {
+                let word = pair[0] as u16 | (*pair.get(1).unwrap_or(&0) as u16) << 8;
+                format!("{:04x}", word)
+            })
+            .collect();
+        format!("{:07x} {}", offset, words.join(" "))
+    }) + &format!("{:07x}\n", start + bytes.len())
+}
+
+/// `hexdump -C`: bytes in hex and the text between bars
+pub fn hexdump_canonical(bytes: &[u8], start: usize) -> String {
+    if bytes.is_empty() {
+        return String::new();
+    }
+    dump_rows(bytes, start, |offset, chunk| {
+        let mut hex_part = String::new();
+        for (index, byte) in chunk.iter().enumerate() {
+            if index == 8 {
+                hex_part.push(' ');
+            }
+            hex_part.push_str(&format!("{:02x} ", byte));
+        }
+        let ascii: String = chunk.iter().map(|&byte| printable(byte)).collect();
+        format!("{:08x}  {:<50}|{}|", offset, hex_part, ascii)
+    }) + &format!("{:08x}\n", start + bytes.len())
+}
+
+/// Format 16-byte rows, collapsing runs of identical rows into `*` as
+/// hexdump does
+fn dump_rows(bytes: &[u8], start: usize, format_row: impl Fn(usize, &[u8]) -> String) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&[u8]> = None;
+    let mut collapsed = false;
+    for (row, chunk) in bytes.chunks(16).enumerate() {
+        if chunk.len() == 16 && previous == Some(chunk) {
+            if !collapsed {
+                text.push_str("*\n");
+                collapsed = true;
+            }
+            continue;
+        }
+        collapsed = false;
+        previous = Some(chunk);
+        text.push_str(&format_row(start + row * 16, chunk));
+        text.push('\n');
+    }
+    text
+}
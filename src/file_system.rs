@@ -305,6 +305,25 @@ impl VirtualFileSystem {
         self.save_to_storage();
     }
 
+    /// Copy a file, or a directory and everything in it
+    pub fn copy(&self, from: &str, to: &str) {
+        let Some(entry) = self.get(from) else {
+            return;
+        };
+        if entry.is_directory() {
+            // List first, so copying a folder into itself terminates
+            let children = self.list_dir(from);
+            self.create_dir(to);
+            for child in children {
+                let target = format!("{}/{}", normalize_path(to), child.metadata.name);
+                self.copy(&child.metadata.path, &target);
+            }
+        } else {
+            let content = entry.content.unwrap_or_default();
+            self.write_file(to, &content, &entry.metadata.icon);
+        }
+    }
+
     /// Delete a file or directory
     pub fn delete(&self, path: &str) {
         let normalized_path = normalize_path(path);
@@ -0,0 +1,190 @@
+//! Registry of known file types: the kind Finder shows for a file and the
+//! app that opens it by default.
+
+use crate::window_manager::AppType;
+
+/// Where app bundles live; opening one launches the app itself
+const APPLICATIONS_DIR: &str = "/Applications";
+
+pub struct FileType {
+    pub extensions: &'static [&'static str],
+    /// Description for Finder's "Kind" column
+    pub kind: &'static str,
+    /// App that opens this type, if any
+    pub app: Option<AppType>,
+}
+
+const FILE_TYPES: &[FileType] = &[
+    FileType {
+        extensions: &["txt", "text"],
+        kind: "Plain Text",
+        app: Some(AppType::TextEdit),
+    },
+    FileType {
+        extensions: &["md", "markdown"],
+        kind: "Markdown Document",
+        app: Some(AppType::TextEdit),
+    },
+    FileType {
+        extensions: &["rtf"],
+        kind: "Rich Text Document",
+        app: Some(AppType::TextEdit),
+    },
+    FileType {
+        extensions: &["html", "htm"],
+        kind: "HTML Document",
+        app: Some(AppType::TextEdit),
+    },
+    FileType {
+        extensions: &["json"],
+        kind: "JSON Document",
+        app: Some(AppType::TextEdit),
+    },
+    FileType {
+        extensions: &["csv"],
+        kind: "Comma-Separated Values",
+        app: Some(AppType::TextEdit),
+    },
+    FileType {
+        extensions: &["log"],
+        kind: "Log File",
+        app: Some(AppType::TextEdit),
+    },
+    FileType {
+        extensions: &["sh", "zsh", "bash"],
+        kind: "Shell Script",
+        app: Some(AppType::TextEdit),
+    },
+    FileType {
+        extensions: &["pdf"],
+        kind: "PDF Document",
+        app: None,
+    },
+    FileType {
+        extensions: &["png", "jpg", "jpeg", "gif"],
+        kind: "Image",
+        app: None,
+    },
+    FileType {
+        extensions: &["mp3", "wav", "aac"],
+        kind: "Audio",
+        app: None,
+    },
+    FileType {
+        extensions: &["mp4", "mov", "avi"],
+        kind: "Video",
+        app: None,
+    },
+    FileType {
+        extensions: &["zip", "tar", "gz"],
+        kind: "Archive",
+        app: None,
+    },
+    FileType {
+        extensions: &["dmg"],
+        kind: "Disk Image",
+        app: None,
+    },
+    FileType {
+        extensions: &["xlsx", "xls"],
+        kind: "Spreadsheet",
+        app: None,
+    },
+    FileType {
+        extensions: &["docx", "doc"],
+        kind: "Word Document",
+        app: None,
+    },
+];
+
+/// The extension of a file name, without the dot
+fn extension(name: &str) -> Option<&str> {
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && !ext.is_empty() => Some(ext),
+        _ => None,
+    }
+}
+
+/// Look up the registered type for a file name by its extension
+pub fn lookup(name: &str) -> Option<&'static FileType> {
+    let ext = extension(name)?.to_lowercase();
+    FILE_TYPES
+        .iter()
+        .find(|file_type| file_type.extensions.contains(&ext.as_str()))
+}
+
+/// Get the "Kind" description for a file
+pub fn kind(name: &str, is_folder: bool) -> String {
+    if is_folder {
+        return "Folder".to_string();
+    }
+    if let Some(file_type) = lookup(name) {
+        return file_type.kind.to_string();
+    }
+    match extension(name) {
+        Some(ext) => format!("{} Document", ext.to_uppercase()),
+        None => "Document".to_string(),
+    }
+}
+
+/// What `file` reports for a file: its registered kind, with the text
+/// encoding for text formats, or for types the registry doesn't know, a
+/// guess from the content
+pub fn describe(path: &str, is_folder: bool, content: &str) -> String {
+    if is_folder {
+        return "directory".to_string();
+    }
+    if let Some((_, None)) = launch_target(path, false) {
+        return "application".to_string();
+    }
+    if content.is_empty() {
+        return "empty".to_string();
+    }
+    let mut text = if content.is_ascii() {
+        "ASCII text".to_string()
+    } else {
+        "Unicode text, UTF-8 text".to_string()
+    };
+    if !content.ends_with('\n') {
+        text.push_str(", with no line terminators");
+    }
+    let name = path.rsplit('/').next().unwrap_or(path);
+    match lookup(name) {
+        Some(file_type) if file_type.app == Some(AppType::TextEdit) => {
+            format!("{}, {}", file_type.kind, text)
+        }
+        Some(file_type) => file_type.kind.to_string(),
+        None => match content.strip_prefix("#!") {
+            Some(rest) => {
+                let interpreter = rest.split_whitespace().next().unwrap_or_default();
+                let interpreter = interpreter.rsplit('/').next().unwrap_or(interpreter);
+                format!("{} script text executable, {}", interpreter, text)
+            }
+            None => text,
+        },
+    }
+}
+
+/// The app that opens a file by default. Files without an extension are
+/// treated as plain text.
+pub fn default_app(name: &str) -> Option<AppType> {
+    match extension(name) {
+        Some(_) => lookup(name).and_then(|file_type| file_type.app.clone()),
+        None => Some(AppType::TextEdit),
+    }
+}
+
+/// What opening `path` launches: the app, and the document it should show
+/// (folders open in Finder; app bundles launch themselves)
+pub fn launch_target(path: &str, is_folder: bool) -> Option<(AppType, Option<String>)> {
+    if is_folder {
+        return Some((AppType::Finder, Some(path.to_string())));
+    }
+    let (parent, name) = path.rsplit_once('/').unwrap_or(("", path));
+    if parent == APPLICATIONS_DIR {
+        if let Some(app) = AppType::from_name(name) {
+            return Some((app, None));
+        }
+    }
+    default_app(name).map(|app| (app, Some(path.to_string())))
+}
@@ -1,12 +1,19 @@
 use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::JsValue;
 
+use crate::clipboard::Clipboard;
 use crate::context_menu::{show_context_menu, ContextMenu, ContextMenuState, ContextMenuType};
 use crate::drag_drop::use_drag_drop;
-use crate::file_system::{use_file_system, FileEntry};
+use crate::file_system::{use_file_system, FileEntry, VirtualFileSystem};
+use crate::file_types;
+use crate::preferences::{Preferences, Value, FINDER_DOMAIN};
+use crate::sheet::{use_window_sheet, ButtonRole, SheetButton};
+use crate::system_state::SystemState;
+use crate::window_manager::use_window_app_state;
 
 /// View mode for Finder content area
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum ViewMode {
     #[default]
     Icons,
@@ -15,6 +22,29 @@ pub enum ViewMode {
     Gallery,
 }
 
+impl ViewMode {
+    /// The code Finder's FXPreferredViewStyle preference uses for this view
+    pub fn style_code(&self) -> &'static str {
+        match self {
+            ViewMode::Icons => "icnv",
+            ViewMode::List => "Nlsv",
+            ViewMode::Column => "clmv",
+            ViewMode::Gallery => "glyv",
+        }
+    }
+
+    pub fn from_style_code(code: &str) -> Option<Self> {
+        [
+            ViewMode::Icons,
+            ViewMode::List,
+            ViewMode::Column,
+            ViewMode::Gallery,
+        ]
+        .into_iter()
+        .find(|mode| mode.style_code() == code)
+    }
+}
+
 /// Represents a file or folder item for display
 #[derive(Clone, Debug, PartialEq)]
 pub struct FileItem {
@@ -70,33 +100,34 @@ fn format_size(size: usize) -> String {
     }
 }
 
-/// Get the "Kind" description for a file
-fn get_file_kind(name: &str, is_folder: bool) -> String {
-    if is_folder {
-        "Folder".to_string()
-    } else if let Some(ext) = name.rsplit('.').next() {
-        if ext == name {
-            // No extension found
-            "Document".to_string()
-        } else {
-            match ext.to_lowercase().as_str() {
-                "txt" => "Plain Text".to_string(),
-                "pdf" => "PDF Document".to_string(),
-                "png" | "jpg" | "jpeg" | "gif" => "Image".to_string(),
-                "mp3" | "wav" | "aac" => "Audio".to_string(),
-                "mp4" | "mov" | "avi" => "Video".to_string(),
-                "zip" | "tar" | "gz" => "Archive".to_string(),
-                "dmg" => "Disk Image".to_string(),
-                "xlsx" | "xls" => "Spreadsheet".to_string(),
-                "docx" | "doc" => "Word Document".to_string(),
-                _ => format!("{} Document", ext.to_uppercase()),
-            }
-        }
+/// Path of an item inside a folder
+fn join_path(dir: &str, name: &str) -> String {
+    if dir == "/" {
+        format!("/{}", name)
     } else {
-        "Document".to_string()
+        format!("{}/{}", dir, name)
     }
 }
 
+/// Name for a pasted item that doesn't clash with the folder's contents:
+/// "notes.txt", then "notes copy.txt", "notes copy 2.txt", ...
+fn copy_name(fs: &VirtualFileSystem, dir: &str, name: &str) -> String {
+    if !fs.exists(&join_path(dir, name)) {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+        _ => (name, String::new()),
+    };
+    let mut candidate = format!("{} copy{}", stem, ext);
+    let mut counter = 1;
+    while fs.exists(&join_path(dir, &candidate)) {
+        counter += 1;
+        candidate = format!("{} copy {}{}", stem, counter, ext);
+    }
+    candidate
+}
+
 /// Sidebar item for favorites
 #[derive(Clone, Debug)]
 struct SidebarItem {
@@ -105,19 +136,66 @@ struct SidebarItem {
     path: Option<&'static str>,
 }
 
+/// Where a Finder window is browsing, kept with the window across reloads
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct FinderWindowState {
+    path: String,
+    view_mode: ViewMode,
+    history: Vec<String>,
+    history_index: usize,
+    sidebar: String,
+}
+
 /// The Finder application component
 #[component]
 pub fn Finder() -> impl IntoView {
     let fs = use_file_system();
     let drag_drop = use_drag_drop();
+    let system_state = expect_context::<SystemState>();
+    let clipboard = expect_context::<Clipboard>();
+    let window_state = use_window_app_state();
+    let window_sheet = use_window_sheet();
+    let saved = window_state.and_then(|w| w.restore::<FinderWindowState>());
     let (selected_sidebar, set_selected_sidebar) = signal("Recents");
-    let (current_path, set_current_path) = signal("/".to_string());
+    let (current_path, set_current_path) = signal(
+        saved
+            .as_ref()
+            .map_or_else(|| "/".to_string(), |s| s.path.clone()),
+    );
     let (selected_items, set_selected_items) = signal(Vec::<String>::new());
-    let (path_history, set_path_history) = signal(vec!["/".to_string()]);
-    let (history_index, set_history_index) = signal(0usize);
-    let (view_mode, set_view_mode) = signal(ViewMode::Icons);
+    let (path_history, set_path_history) = signal(
+        saved
+            .as_ref()
+            .map_or_else(|| vec!["/".to_string()], |s| s.history.clone()),
+    );
+    let (history_index, set_history_index) = signal(saved.as_ref().map_or(0, |s| {
+        s.history_index.min(s.history.len().saturating_sub(1))
+    }));
+    let preferences = expect_context::<Preferences>();
+    let restored_view = saved.as_ref().map(|s| s.view_mode);
+    let (view_mode, set_view_mode) = signal(restored_view.unwrap_or_default());
     let (search_query, set_search_query) = signal(String::new());
 
+    // Open in the preferred view, and follow it when it changes; a restored
+    // window keeps its own view until then
+    Effect::new(move |previous_run: Option<()>| {
+        let preferred = preferences.get_string(FINDER_DOMAIN, "FXPreferredViewStyle");
+        if previous_run.is_none() && restored_view.is_some() {
+            return;
+        }
+        if let Some(mode) = preferred.as_deref().and_then(ViewMode::from_style_code) {
+            set_view_mode.set(mode);
+        }
+    });
+    let choose_view = move |mode: ViewMode| {
+        set_view_mode.set(mode);
+        preferences.set(
+            FINDER_DOMAIN,
+            "FXPreferredViewStyle",
+            Value::String(mode.style_code().to_string()),
+        );
+    };
+
     // Context menu state
     let (context_menu_state, set_context_menu_state) = signal(ContextMenuState::default());
 
@@ -203,6 +281,30 @@ pub fn Finder() -> impl IntoView {
         },
     ];
 
+    if let Some(saved) = &saved {
+        let restored = sidebar_favorites
+            .iter()
+            .chain(&sidebar_icloud)
+            .chain(&sidebar_locations)
+            .map(|item| item.name)
+            .find(|name| *name == saved.sidebar);
+        set_selected_sidebar.set(restored.unwrap_or(""));
+    }
+
+    // Keep the folder, view and history with the window
+    Effect::new(move |_| {
+        let state = FinderWindowState {
+            path: current_path.get(),
+            view_mode: view_mode.get(),
+            history: path_history.get(),
+            history_index: history_index.get(),
+            sidebar: selected_sidebar.get().to_string(),
+        };
+        if let Some(window_state) = window_state {
+            window_state.save(&state);
+        }
+    });
+
     // Sidebar tags (visual only - no functionality)
     let sidebar_tags: Vec<(&str, &str)> = vec![
         ("Red", "#ff3b30"),
@@ -227,6 +329,23 @@ pub fn Finder() -> impl IntoView {
         set_selected_items.set(Vec::new());
     };
 
+    // Open a file in its default app
+    let open_file = move |path: &str| {
+        if let Some((app, document)) = file_types::launch_target(path, false) {
+            system_state.request_open(&app.to_string(), document.as_deref());
+        }
+    };
+
+    // Show a folder opened in this window from elsewhere (e.g. `open .` in
+    // Terminal)
+    let window_id = use_window_app_state().map(|state| state.window_id());
+    Effect::new(move |_| {
+        if let Some(path) = window_id.and_then(|id| system_state.take_open_document(id)) {
+            set_selected_sidebar.set("");
+            navigate_to(path);
+        }
+    });
+
     // Go back in history
     let go_back = move |_| {
         let idx = history_index.get();
@@ -320,15 +439,42 @@ pub fn Finder() -> impl IntoView {
                     }
                     "Move to Trash" => {
                         let items = selected_items.get();
-                        for item_name in items {
-                            let item_path = if path == "/" {
-                                format!("/{}", item_name)
-                            } else {
-                                format!("{}/{}", path, item_name)
-                            };
-                            fs.delete(&item_path);
+                        let paths: Vec<String> = items
+                            .iter()
+                            .map(|item_name| join_path(&path, item_name))
+                            .collect();
+                        let fs = fs.clone();
+                        let delete = move || {
+                            for item_path in &paths {
+                                fs.delete(item_path);
+                            }
+                            set_selected_items.set(Vec::new());
+                        };
+                        // Deleting can't be undone, so ask first
+                        match window_sheet.filter(|_| !items.is_empty()) {
+                            Some(sheet) => {
+                                let title = match items.as_slice() {
+                                    [name] => {
+                                        format!("Are you sure you want to delete “{}”?", name)
+                                    }
+                                    _ => format!(
+                                        "Are you sure you want to delete the {} selected items?",
+                                        items.len()
+                                    ),
+                                };
+                                wasm_bindgen_futures::spawn_local(async move {
+                                    let buttons = vec![
+                                        SheetButton::new("Cancel", ButtonRole::Cancel),
+                                        SheetButton::new("Delete", ButtonRole::Default),
+                                    ];
+                                    let message = "This item will be deleted immediately. You can’t undo this action.";
+                                    if sheet.alert(&title, message, buttons).await == Some(1) {
+                                        delete();
+                                    }
+                                });
+                            }
+                            None => delete(),
                         }
-                        set_selected_items.set(Vec::new());
                     }
                     "Rename" => {
                         let items = selected_items.get();
@@ -336,6 +482,23 @@ pub fn Finder() -> impl IntoView {
                             set_renaming_item.set(Some(first.clone()));
                         }
                     }
+                    "Copy" => {
+                        let paths = selected_items
+                            .get()
+                            .iter()
+                            .map(|item_name| join_path(&path, item_name))
+                            .collect();
+                        clipboard.copy_files(paths);
+                    }
+                    "Paste Item" => {
+                        for source in clipboard.files() {
+                            let Some(entry) = fs.get(&source) else {
+                                continue;
+                            };
+                            let name = copy_name(&fs, &path, &entry.metadata.name);
+                            fs.copy(&source, &join_path(&path, &name));
+                        }
+                    }
                     _ => {}
                 }
                 set_pending_action.set(None);
@@ -405,28 +568,28 @@ pub fn Finder() -> impl IntoView {
                         <button
                             class=move || if view_mode.get() == ViewMode::Icons { "finder-view-btn active" } else { "finder-view-btn" }
                             title="Icons"
-                            on:click=move |_| set_view_mode.set(ViewMode::Icons)
+                            on:click=move |_| choose_view(ViewMode::Icons)
                         >
                             <span>"⊞"</span>
                         </button>
                         <button
                             class=move || if view_mode.get() == ViewMode::List { "finder-view-btn active" } else { "finder-view-btn" }
                             title="List"
-                            on:click=move |_| set_view_mode.set(ViewMode::List)
+                            on:click=move |_| choose_view(ViewMode::List)
                         >
                             <span>"☰"</span>
                         </button>
                         <button
                             class=move || if view_mode.get() == ViewMode::Column { "finder-view-btn active" } else { "finder-view-btn" }
                             title="Columns"
-                            on:click=move |_| set_view_mode.set(ViewMode::Column)
+                            on:click=move |_| choose_view(ViewMode::Column)
                         >
                             <span>"❘❘❘"</span>
                         </button>
                         <button
                             class=move || if view_mode.get() == ViewMode::Gallery { "finder-view-btn active" } else { "finder-view-btn" }
                             title="Gallery"
-                            on:click=move |_| set_view_mode.set(ViewMode::Gallery)
+                            on:click=move |_| choose_view(ViewMode::Gallery)
                         >
                             <span>"▭"</span>
                         </button>
@@ -694,7 +857,7 @@ pub fn Finder() -> impl IntoView {
                                                 } else {
                                                     format_size(size)
                                                 };
-                                                let kind = get_file_kind(&name_for_kind, is_folder);
+                                                let kind = file_types::kind(&name_for_kind, is_folder);
                                                 let date_display = format_date(modified);
 
                                                 view! {
@@ -752,6 +915,8 @@ pub fn Finder() -> impl IntoView {
                                                         on:dblclick=move |_| {
                                                             if is_folder {
                                                                 navigate_to(path_for_dblclick.clone());
+                                                            } else {
+                                                                open_file(&path_for_dblclick);
                                                             }
                                                         }
                                                         on:contextmenu=move |ev: web_sys::MouseEvent| {
@@ -967,6 +1132,8 @@ pub fn Finder() -> impl IntoView {
                                                     on:dblclick=move |_| {
                                                         if is_folder {
                                                             navigate_to(path_for_dblclick.clone());
+                                                        } else {
+                                                            open_file(&path_for_dblclick);
                                                         }
                                                     }
                                                     on:contextmenu=move |ev: web_sys::MouseEvent| {
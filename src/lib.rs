@@ -1,28 +1,41 @@
 use leptos::prelude::*;
 use wasm_bindgen::prelude::wasm_bindgen;
 
+mod ansi;
 mod app_switcher;
 mod calculator;
+mod clipboard;
 mod context_menu;
 mod desktop;
 mod dock;
 mod drag_drop;
+mod encoding;
 pub mod file_system;
+mod file_types;
 mod finder;
+mod manual;
 mod menu_bar;
 mod modals;
 mod notes;
 mod notification;
+mod pattern;
+mod preferences;
+mod process;
+mod sheet;
+mod shell;
 mod spotlight;
 mod system_settings;
 mod system_state;
 mod terminal;
+mod terminal_editor;
+mod terminal_pager;
 mod textedit;
 pub mod theme;
 mod wallpaper;
 mod window_manager;
 
 use app_switcher::AppSwitcher;
+use clipboard::Clipboard;
 use context_menu::{ContextMenu, ContextMenuState};
 use desktop::Desktop;
 use dock::Dock;
@@ -31,8 +44,11 @@ use file_system::FileSystemProvider;
 use menu_bar::MenuBar;
 use modals::{LockScreen, ModalOverlay, PowerOverlay};
 use notification::{NotificationContainer, NotificationState};
+use preferences::Preferences;
+use process::ProcessTable;
 use spotlight::Spotlight;
 use system_state::SystemState;
+use terminal::provide_terminal_context;
 use theme::ThemeProvider;
 use wallpaper::provide_wallpaper_context;
 use window_manager::WindowManager;
@@ -50,6 +66,20 @@ fn App() -> impl IntoView {
     let notification_state = NotificationState::new();
     provide_context(notification_state);
 
+    // Provide process table context
+    provide_context(ProcessTable::new());
+
+    // Provide the shared clipboard, kept in step with native copies
+    let clipboard = Clipboard::new();
+    clipboard::listen_for_system_copies(clipboard);
+    provide_context(clipboard);
+
+    // Provide the preferences store, which theme and wallpaper follow
+    provide_context(Preferences::new());
+
+    // Provide the command history and definitions Terminal windows share
+    provide_terminal_context();
+
     // Provide wallpaper context
     provide_wallpaper_context();
 
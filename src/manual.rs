@@ -0,0 +1,812 @@
+//! Reference documentation for Terminal commands, shared by `help`,
+//! `<command> --help` and `man`.
+
+use crate::ansi;
+
+/// Width of a formatted manual page
+const PAGE_WIDTH: usize = 78;
+/// Indent of body text in a manual page
+const INDENT: usize = 5;
+/// Column where option descriptions start, relative to the body indent
+const OPTION_COLUMN: usize = 8;
+
+/// How a command treats arguments that start with `-`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Options {
+    /// Clusters of these single-letter options come before the operands,
+    /// and anything else is rejected before the command runs
+    Flags(&'static str),
+    /// The command parses its own options
+    Custom,
+    /// Every argument is an operand, even `--help` (`echo --help`)
+    Literal,
+}
+
+pub struct Manual {
+    pub name: &'static str,
+    /// Other names that run the same command
+    pub aliases: &'static [&'static str],
+    /// One-line description, for `help` and the NAME section
+    pub summary: &'static str,
+    /// Usage lines, without the leading `usage: `
+    pub synopsis: &'static [&'static str],
+    pub options: Options,
+    /// Paragraphs separated by blank lines
+    pub description: &'static str,
+    /// Option and its description, in the order they are documented
+    pub flags: &'static [(&'static str, &'static str)],
+    pub see_also: &'static [&'static str],
+}
+
+const MANUALS: &[Manual] = &[
+    Manual {
+        name: "alias",
+        aliases: &[],
+        summary: "define or list command aliases",
+        synopsis: &["alias [name[=value] ...]"],
+        options: Options::Flags(""),
+        description: "Without arguments, alias prints every alias as name=value. For each name=value argument it defines an alias, so that a command line starting with name runs value instead. For each bare name it prints that alias, and fails if there is none.\n\nAliases are saved with the Terminal and shared by all tabs.",
+        flags: &[],
+        see_also: &["unalias", "functions"],
+    },
+    Manual {
+        name: "base64",
+        aliases: &[],
+        summary: "encode and decode using Base64",
+        synopsis: &["base64 [-d] [-b count] [-i input_file] [-o output_file] [input_file]"],
+        options: Options::Custom,
+        description: "base64 encodes a file, or standard input, as Base64 text, or with -d decodes it. File contents in VirtualMac are text, so decoded bytes that are not valid UTF-8 are replaced.",
+        flags: &[
+            ("-b count", "Break encoded lines every count characters."),
+            ("-d", "Decode instead of encode. -D is the same."),
+            ("-i input_file", "Read from input_file; - is standard input."),
+            ("-o output_file", "Write to output_file; - is standard output."),
+        ],
+        see_also: &["md5", "shasum", "xxd"],
+    },
+    Manual {
+        name: "break",
+        aliases: &["continue"],
+        summary: "leave or restart an enclosing loop",
+        synopsis: &["break [n]", "continue [n]"],
+        options: Options::Flags(""),
+        description: "break exits the innermost for, while or until loop; continue skips to its next iteration. With n, the command applies to the n-th enclosing loop.",
+        flags: &[],
+        see_also: &["sh"],
+    },
+    Manual {
+        name: "cat",
+        aliases: &[],
+        summary: "concatenate and print files",
+        synopsis: &["cat [file ...]"],
+        options: Options::Flags(""),
+        description: "cat writes the contents of each file to standard output, one after the other. With no files it copies standard input, so it can end a pipeline.",
+        flags: &[],
+        see_also: &["grep", "less", "nano"],
+    },
+    Manual {
+        name: "cd",
+        aliases: &[],
+        summary: "change the working directory",
+        synopsis: &["cd [directory | -]"],
+        options: Options::Flags(""),
+        description: "cd makes directory the current working directory. Without an argument it changes to $HOME; with - it returns to the previous directory ($OLDPWD) and prints its name.",
+        flags: &[],
+        see_also: &["pwd", "ls"],
+    },
+    Manual {
+        name: "clear",
+        aliases: &[],
+        summary: "clear the terminal screen",
+        synopsis: &["clear"],
+        options: Options::Flags(""),
+        description: "clear erases the scrollback of the current Terminal tab.",
+        flags: &[],
+        see_also: &[],
+    },
+    Manual {
+        name: "date",
+        aliases: &[],
+        summary: "display the date and time",
+        synopsis: &["date"],
+        options: Options::Flags(""),
+        description: "date prints the current date and time.",
+        flags: &[],
+        see_also: &[],
+    },
+    Manual {
+        name: "defaults",
+        aliases: &[],
+        summary: "access app preferences",
+        synopsis: &[
+            "defaults read [domain [key]]",
+            "defaults read-type domain key",
+            "defaults write domain key [-type] value",
+            "defaults delete domain [key]",
+            "defaults domains",
+        ],
+        options: Options::Custom,
+        description: "defaults reads, writes and deletes the preferences apps keep in domains. Apps pick up a change as soon as it is written. -g names NSGlobalDomain, which every app shares.\n\nKnown keys include AppleInterfaceStyle (Dark, or deleted for light mode) in NSGlobalDomain, persistent-apps and magnification in com.apple.dock, Background (a wallpaper id such as ventura) in com.apple.desktop, and FXPreferredViewStyle (icnv, Nlsv, clmv or glyv) in com.apple.finder.",
+        flags: &[
+            ("-string value", "Write value as a string; the default when no type is given."),
+            ("-int value", "Write an integer. -integer is the same."),
+            ("-float value", "Write a floating point number."),
+            ("-bool value", "Write true, false, yes or no. -boolean is the same."),
+            ("-array item ...", "Write the remaining arguments as an array of strings."),
+            ("-array-add item", "Append the remaining arguments to an existing array."),
+        ],
+        see_also: &["open"],
+    },
+    Manual {
+        name: "echo",
+        aliases: &[],
+        summary: "write arguments to standard output",
+        synopsis: &["echo [-neE] [string ...]"],
+        options: Options::Literal,
+        description: "echo writes its arguments separated by single spaces, followed by a newline.",
+        flags: &[
+            ("-n", "Do not print the trailing newline."),
+            ("-e", "Interpret backslash escapes such as \\n, \\t and \\e."),
+            ("-E", "Do not interpret backslash escapes (the default)."),
+        ],
+        see_also: &["printf"],
+    },
+    Manual {
+        name: "env",
+        aliases: &["printenv"],
+        summary: "print the environment",
+        synopsis: &["env", "printenv [name ...]"],
+        options: Options::Flags(""),
+        description: "env and printenv print every environment variable as name=value. printenv with names prints just the value of each variable that is set.",
+        flags: &[],
+        see_also: &["export", "unset"],
+    },
+    Manual {
+        name: "exit",
+        aliases: &[],
+        summary: "exit the shell",
+        synopsis: &["exit [n]"],
+        options: Options::Custom,
+        description: "exit ends the shell with status n, or the status of the last command. Exiting the shell closes its Terminal tab.",
+        flags: &[],
+        see_also: &["return"],
+    },
+    Manual {
+        name: "export",
+        aliases: &[],
+        summary: "set environment variables",
+        synopsis: &["export [name[=value] ...]"],
+        options: Options::Flags(""),
+        description: "export sets each name to value in the environment. Without arguments it prints every variable as name=value.",
+        flags: &[],
+        see_also: &["env", "unset"],
+    },
+    Manual {
+        name: "false",
+        aliases: &[],
+        summary: "return a failing exit status",
+        synopsis: &["false"],
+        options: Options::Literal,
+        description: "false does nothing and exits with status 1.",
+        flags: &[],
+        see_also: &["true"],
+    },
+    Manual {
+        name: "file",
+        aliases: &[],
+        summary: "determine file type",
+        synopsis: &["file [-b] file ..."],
+        options: Options::Flags("b"),
+        description: "file prints the type of each file: the kind Finder shows for its extension, with the text encoding for text formats. Files of unknown types are described by their content, and scripts by the interpreter their #! line names.",
+        flags: &[("-b", "Print only the type, without the file name.")],
+        see_also: &["ls", "open"],
+    },
+    Manual {
+        name: "functions",
+        aliases: &[],
+        summary: "print shell function definitions",
+        synopsis: &["functions [name ...]"],
+        options: Options::Flags(""),
+        description: "functions prints the source of each named shell function, or of every function when no names are given.",
+        flags: &[],
+        see_also: &["unfunction", "alias"],
+    },
+    Manual {
+        name: "grep",
+        aliases: &[],
+        summary: "print lines matching a pattern",
+        synopsis: &["grep [-cilnqvEF] [-e pattern] [--color=when] [pattern] [file ...]"],
+        options: Options::Custom,
+        description: "grep searches each file, or standard input when none is given, for lines matching pattern and prints them. Patterns are regular expressions supporting ., [...], *, +, ?, ^, $ and |.\n\ngrep exits with status 0 when a line matched, 1 when none did and 2 on an error.",
+        flags: &[
+            ("-c", "Print only a count of matching lines."),
+            ("-e pattern", "Use pattern, even if it starts with -."),
+            ("-E", "Use extended regular expressions (the default)."),
+            ("-F", "Match the pattern as a fixed string."),
+            ("-i", "Ignore case when matching."),
+            ("-l", "Print only the names of files with a match."),
+            ("-n", "Precede each line with its line number."),
+            ("-q", "Print nothing; report a match through the exit status."),
+            ("-v", "Select lines that do not match."),
+            ("--color[=when]", "Highlight matches: always, auto (when writing to the terminal) or never. $GREP_COLOR sets the color."),
+        ],
+        see_also: &["cat"],
+    },
+    Manual {
+        name: "help",
+        aliases: &[],
+        summary: "list the available commands",
+        synopsis: &["help [command]"],
+        options: Options::Flags(""),
+        description: "help lists every command with a one-line summary. With a command name it prints that command's usage, like command --help.",
+        flags: &[],
+        see_also: &["man"],
+    },
+    Manual {
+        name: "hexdump",
+        aliases: &[],
+        summary: "display file contents in hexadecimal",
+        synopsis: &["hexdump [-C] [-n length] [-s skip] [file ...]"],
+        options: Options::Custom,
+        description: "hexdump shows the files, or standard input, as one stream of bytes: by default as 16-bit little-endian words, with the offset of each line and the total length at the end. Runs of identical lines are shown as a single *.",
+        flags: &[
+            ("-C", "Canonical display: each byte in hex, followed by the text between bars."),
+            ("-n length", "Show only length bytes of input."),
+            ("-s skip", "Skip skip bytes from the start of the input."),
+        ],
+        see_also: &["xxd"],
+    },
+    Manual {
+        name: "history",
+        aliases: &[],
+        summary: "list previously entered commands",
+        synopsis: &["history [first]"],
+        options: Options::Custom,
+        description: "history lists the most recent commands with their event numbers. first selects where the list starts: an event number, a negative count back from the newest event, or the most recent command starting with a prefix.\n\nEvents can be reused with !! (the last command), !n, !-n, !prefix, !?text? and !$ (the last word of the previous command). Ctrl+R searches the history interactively.",
+        flags: &[],
+        see_also: &[],
+    },
+    Manual {
+        name: "hostname",
+        aliases: &[],
+        summary: "print the name of this host",
+        synopsis: &["hostname"],
+        options: Options::Flags(""),
+        description: "hostname prints the name of this computer.",
+        flags: &[],
+        see_also: &["whoami"],
+    },
+    Manual {
+        name: "kill",
+        aliases: &[],
+        summary: "send a signal to a process",
+        synopsis: &["kill [-s signal | -signal] pid ...", "kill -l [signal]"],
+        options: Options::Custom,
+        description: "kill sends a signal, TERM by default, to each process. Signals that terminate a process close its window or Terminal tab. Processes owned by other users cannot be signalled.",
+        flags: &[
+            ("-s signal", "Send signal, given by name (with or without SIG) or number."),
+            ("-signal", "The same as -s signal."),
+            ("-l", "List signal names, or translate between a signal's name and number."),
+        ],
+        see_also: &["killall", "ps"],
+    },
+    Manual {
+        name: "killall",
+        aliases: &[],
+        summary: "kill processes by name",
+        synopsis: &["killall [-s signal | -signal] procname ..."],
+        options: Options::Custom,
+        description: "killall sends a signal, TERM by default, to every process you own whose name is one of the given names.",
+        flags: &[("-s signal", "Send signal instead of TERM.")],
+        see_also: &["kill", "ps"],
+    },
+    Manual {
+        name: "less",
+        aliases: &["more"],
+        summary: "page through text",
+        synopsis: &["less [-iN] [file ...]"],
+        options: Options::Flags("iN"),
+        description: "less shows files, or standard input at the end of a pipeline, one screen at a time. When the output is not the terminal it copies its input like cat.\n\nSpace, f and Page Down scroll forward a screen, b and Page Up back a screen, and j, k and the arrow keys a line. d and u scroll half a screen; g and G go to the start and end. /pattern searches forward and ?pattern backward for a regular expression, highlighting every match; n repeats the search and N reverses it. q quits.",
+        flags: &[
+            ("-i", "Ignore case when searching."),
+            ("-N", "Number each line."),
+        ],
+        see_also: &["cat", "man"],
+    },
+    Manual {
+        name: "local",
+        aliases: &[],
+        summary: "declare function-local variables",
+        synopsis: &["local name[=value] ..."],
+        options: Options::Flags(""),
+        description: "local sets variables that are restored when the enclosing function returns. It can only be used inside a function.",
+        flags: &[],
+        see_also: &["export"],
+    },
+    Manual {
+        name: "ls",
+        aliases: &[],
+        summary: "list directory contents",
+        synopsis: &["ls [-aG] [file ...]"],
+        options: Options::Flags("aG"),
+        description: "For each operand that names a file, ls displays its name; for each directory it lists the files it contains. With no operands the current directory is listed. Directory names end in /.",
+        flags: &[
+            ("-a", "Include entries whose names begin with a dot (.)."),
+            ("-G", "Color directory names when writing to the terminal. Setting $CLICOLOR does the same, and $CLICOLOR_FORCE colors output that goes elsewhere too."),
+        ],
+        see_also: &["cd", "open"],
+    },
+    Manual {
+        name: "man",
+        aliases: &[],
+        summary: "display manual pages",
+        synopsis: &["man command ...", "man -k keyword"],
+        options: Options::Flags("k"),
+        description: "man shows the manual page for each command in the same pager as less. Use the arrow keys, space and b to scroll, / to search, n and N to repeat the search, and q to quit. When the output is not the terminal, the page is printed instead.",
+        flags: &[("-k", "Search command names and summaries for keyword, like apropos.")],
+        see_also: &["help", "less"],
+    },
+    Manual {
+        name: "md5",
+        aliases: &[],
+        summary: "calculate a message-digest fingerprint",
+        synopsis: &["md5 [-qr] [-s string] [file ...]"],
+        options: Options::Custom,
+        description: "md5 prints the MD5 digest of each file, or of standard input, as MD5 (file) = digest.",
+        flags: &[
+            ("-q", "Print only the digest."),
+            ("-r", "Print the digest followed by the file name."),
+            ("-s string", "Print the digest of string."),
+        ],
+        see_also: &["shasum"],
+    },
+    Manual {
+        name: "mkdir",
+        aliases: &[],
+        summary: "make directories",
+        synopsis: &["mkdir [-p] directory ..."],
+        options: Options::Flags("p"),
+        description: "mkdir creates each directory.",
+        flags: &[("-p", "Create missing parent directories as needed, and don't fail if the directory already exists.")],
+        see_also: &["rm", "touch"],
+    },
+    Manual {
+        name: "nano",
+        aliases: &["pico"],
+        summary: "edit text files",
+        synopsis: &["nano [file]"],
+        options: Options::Flags(""),
+        description: "nano opens file, or a new buffer, in a full-screen editor. The shortcut bar at the bottom lists its commands: ^O writes the file, ^W searches, ^K and ^U cut and paste lines, and ^X exits.",
+        flags: &[],
+        see_also: &["cat"],
+    },
+    Manual {
+        name: "notify",
+        aliases: &[],
+        summary: "post a notification",
+        synopsis: &["notify title [message ...]"],
+        options: Options::Flags(""),
+        description: "notify shows a notification banner with title and the remaining arguments as its message.",
+        flags: &[],
+        see_also: &[],
+    },
+    Manual {
+        name: "open",
+        aliases: &[],
+        summary: "open files and applications",
+        synopsis: &["open [-e] [-a application] [file ...]"],
+        options: Options::Custom,
+        description: "open opens each file in its default application: folders in Finder, text files in TextEdit, and apps in /Applications by launching them. Nothing is opened if any file is missing.",
+        flags: &[
+            ("-a application", "Open the files with application, or just launch it when no files are given."),
+            ("-e", "Open the files with TextEdit."),
+        ],
+        see_also: &["ls"],
+    },
+    Manual {
+        name: "pbcopy",
+        aliases: &[],
+        summary: "copy standard input to the clipboard",
+        synopsis: &["pbcopy < file", "command | pbcopy"],
+        options: Options::Flags(""),
+        description: "pbcopy puts its standard input on the clipboard shared with TextEdit, Notes and Finder, and on the browser clipboard when the browser allows it.",
+        flags: &[],
+        see_also: &["pbpaste"],
+    },
+    Manual {
+        name: "pbpaste",
+        aliases: &[],
+        summary: "print the clipboard contents",
+        synopsis: &["pbpaste"],
+        options: Options::Flags(""),
+        description: "pbpaste writes the clipboard's text to standard output. Files copied in Finder paste as their names, one per line.",
+        flags: &[],
+        see_also: &["pbcopy"],
+    },
+    Manual {
+        name: "printf",
+        aliases: &[],
+        summary: "formatted output",
+        synopsis: &["printf format [argument ...]"],
+        options: Options::Literal,
+        description: "printf writes its arguments according to format, which may contain backslash escapes and conversions such as %s, %d, %x, %f, %c and %b, with optional flags, width and precision. The format is reused until every argument has been consumed.",
+        flags: &[],
+        see_also: &["echo"],
+    },
+    Manual {
+        name: "ps",
+        aliases: &[],
+        summary: "process status",
+        synopsis: &["ps [-AaefuxX] [-p pid[,pid...]]"],
+        options: Options::Custom,
+        description: "ps lists running processes: every open window is an app process and every Terminal tab runs a shell. By default only your processes with a controlling terminal are shown. Options may be given without the dash, as in ps aux.",
+        flags: &[
+            ("-A", "List every process. -e is the same."),
+            ("-a", "Include other users' processes with a terminal."),
+            ("-f", "Show the user id, parent process and start time."),
+            ("-p pids", "List only the given processes."),
+            ("-u", "Show the user, CPU and memory usage and start time."),
+            ("-x", "Include processes without a controlling terminal."),
+            ("-X", "Skip processes without a controlling terminal (the default)."),
+        ],
+        see_also: &["top", "kill"],
+    },
+    Manual {
+        name: "pwd",
+        aliases: &[],
+        summary: "print the working directory",
+        synopsis: &["pwd"],
+        options: Options::Flags("LP"),
+        description: "pwd prints the absolute path of the current working directory.",
+        flags: &[],
+        see_also: &["cd"],
+    },
+    Manual {
+        name: "return",
+        aliases: &[],
+        summary: "return from a shell function",
+        synopsis: &["return [n]"],
+        options: Options::Custom,
+        description: "return ends the current function or sourced script with status n, or the status of the last command.",
+        flags: &[],
+        see_also: &["exit"],
+    },
+    Manual {
+        name: "rm",
+        aliases: &[],
+        summary: "remove files and directories",
+        synopsis: &["rm [-fr] file ..."],
+        options: Options::Flags("frR"),
+        description: "rm removes each file. Directories are only removed with -r.",
+        flags: &[
+            ("-f", "Don't report files that don't exist."),
+            ("-r", "Remove directories and everything in them. -R is the same."),
+        ],
+        see_also: &["mkdir"],
+    },
+    Manual {
+        name: "set",
+        aliases: &[],
+        summary: "set shell options and positional parameters",
+        synopsis: &["set [-ex] [+ex] [--] [argument ...]"],
+        options: Options::Custom,
+        description: "set turns shell options on with - and off with +. Remaining arguments replace the positional parameters $1, $2, and so on. Without arguments it prints every variable.",
+        flags: &[
+            ("-e", "Exit when a command fails."),
+            ("-x", "Print each command before running it."),
+        ],
+        see_also: &["shift"],
+    },
+    Manual {
+        name: "sh",
+        aliases: &["bash", "zsh"],
+        summary: "run a shell script",
+        synopsis: &["sh [script [argument ...]]", "sh -c command [name [argument ...]]"],
+        options: Options::Custom,
+        description: "sh runs script, or the command string given with -c, in the current shell with its own positional parameters.\n\nThe shell supports pipes (|), && and ||, ;, > >> < and 2> redirection, $(...) substitution, $((...)) arithmetic, if/elif/else, for, while and until loops, functions, and ! history expansion.",
+        flags: &[("-c command", "Run command instead of a script.")],
+        see_also: &["source"],
+    },
+    Manual {
+        name: "shasum",
+        aliases: &[],
+        summary: "print or check SHA checksums",
+        synopsis: &["shasum [-a algorithm] [file ...]", "shasum -c [file ...]"],
+        options: Options::Custom,
+        description: "shasum prints the SHA digest of each file, or of standard input (shown as -), followed by its name.\n\nWith -c it reads lines in that format and checks each named file, printing OK or FAILED, and exits with 1 if any failed. The algorithm is told from each digest's length unless -a gives it.",
+        flags: &[
+            ("-a algorithm", "1 (the default), 224 or 256."),
+            ("-c", "Check digests listed in the files."),
+            ("-b", "Read in binary mode; the same as text here. -t is the same."),
+        ],
+        see_also: &["md5"],
+    },
+    Manual {
+        name: "shift",
+        aliases: &[],
+        summary: "shift positional parameters",
+        synopsis: &["shift [n]"],
+        options: Options::Flags(""),
+        description: "shift drops the first n positional parameters (1 by default), renumbering the rest.",
+        flags: &[],
+        see_also: &["set"],
+    },
+    Manual {
+        name: "source",
+        aliases: &["."],
+        summary: "run a script in the current shell",
+        synopsis: &["source file [argument ...]"],
+        options: Options::Flags(""),
+        description: "source runs the commands in file in the current shell, so variables, functions and directory changes it makes remain afterwards.",
+        flags: &[],
+        see_also: &["sh"],
+    },
+    Manual {
+        name: "test",
+        aliases: &["["],
+        summary: "evaluate a condition",
+        synopsis: &["test expression", "[ expression ]"],
+        options: Options::Literal,
+        description: "test exits with status 0 when expression is true and 1 when it is false. Expressions include -e, -f and -d file tests, -z and -n string tests, = and != string comparisons, -eq, -ne, -lt, -le, -gt and -ge integer comparisons, and ! for negation.",
+        flags: &[],
+        see_also: &["sh"],
+    },
+    Manual {
+        name: "top",
+        aliases: &[],
+        summary: "display process resource usage",
+        synopsis: &["top [-o key] [-n nprocs]"],
+        options: Options::Custom,
+        description: "top prints a summary of system load and memory use, followed by each process with its CPU usage, CPU time and memory.",
+        flags: &[
+            ("-n nprocs", "Show at most nprocs processes."),
+            ("-o key", "Sort by key: pid (the default), command, cpu, mem or time."),
+        ],
+        see_also: &["ps", "kill"],
+    },
+    Manual {
+        name: "touch",
+        aliases: &[],
+        summary: "create empty files",
+        synopsis: &["touch file ..."],
+        options: Options::Flags(""),
+        description: "touch creates each file that doesn't exist yet as an empty file.",
+        flags: &[],
+        see_also: &["mkdir", "rm"],
+    },
+    Manual {
+        name: "true",
+        aliases: &[":"],
+        summary: "return a successful exit status",
+        synopsis: &["true"],
+        options: Options::Literal,
+        description: "true does nothing and exits with status 0.",
+        flags: &[],
+        see_also: &["false"],
+    },
+    Manual {
+        name: "unalias",
+        aliases: &[],
+        summary: "remove aliases",
+        synopsis: &["unalias -a", "unalias name ..."],
+        options: Options::Flags("a"),
+        description: "unalias removes each named alias.",
+        flags: &[("-a", "Remove every alias.")],
+        see_also: &["alias"],
+    },
+    Manual {
+        name: "unfunction",
+        aliases: &[],
+        summary: "remove shell functions",
+        synopsis: &["unfunction name ..."],
+        options: Options::Flags(""),
+        description: "unfunction removes each named shell function.",
+        flags: &[],
+        see_also: &["functions"],
+    },
+    Manual {
+        name: "unset",
+        aliases: &[],
+        summary: "remove variables and functions",
+        synopsis: &["unset [-fv] name ..."],
+        options: Options::Flags("fv"),
+        description: "unset removes each named variable, or function.",
+        flags: &[
+            ("-f", "Remove only functions."),
+            ("-v", "Remove only variables."),
+        ],
+        see_also: &["export"],
+    },
+    Manual {
+        name: "whoami",
+        aliases: &[],
+        summary: "print your user name",
+        synopsis: &["whoami"],
+        options: Options::Flags(""),
+        description: "whoami prints the name of the logged-in user.",
+        flags: &[],
+        see_also: &["hostname"],
+    },
+    Manual {
+        name: "xxd",
+        aliases: &[],
+        summary: "make a hex dump or do the reverse",
+        synopsis: &["xxd [-p] [-c cols] [-l len] [-s seek] [file]", "xxd -r [-p] [file]"],
+        options: Options::Custom,
+        description: "xxd shows a file, or standard input, as lines of an offset, hex digits in pairs of bytes and the printable text. With -r it turns a dump back into text.",
+        flags: &[
+            ("-c cols", "Show cols bytes per line, 16 by default."),
+            ("-l len", "Stop after len bytes."),
+            ("-p", "Plain hex dump, 30 bytes per line with no offsets or text."),
+            ("-r", "Reverse: convert a dump (plain with -p) back to text."),
+            ("-s seek", "Start at offset seek."),
+        ],
+        see_also: &["hexdump", "base64"],
+    },
+];
+
+/// Look up the manual for a command by any of its names
+pub fn lookup(name: &str) -> Option<&'static Manual> {
+    MANUALS
+        .iter()
+        .find(|manual| manual.name == name || manual.aliases.contains(&name))
+}
+
+/// Every documented command, in alphabetical order
+pub fn all() -> impl Iterator<Item = &'static Manual> {
+    MANUALS.iter()
+}
+
+/// Break text into lines of at most `width` characters
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+impl Manual {
+    /// Usage lines, as printed after an option error
+    pub fn usage(&self) -> String {
+        let mut usage = String::new();
+        for (i, line) in self.synopsis.iter().enumerate() {
+            let prefix = if i == 0 { "usage: " } else { "       " };
+            usage.push_str(prefix);
+            usage.push_str(line);
+            usage.push('\n');
+        }
+        usage
+    }
+
+    /// Usage summary printed by `command --help`
+    pub fn help(&self) -> String {
+        let mut help = self.usage();
+        help.push('\n');
+        let mut summary = self.summary.to_string();
+        if let Some(first) = summary.get_mut(..1) {
+            first.make_ascii_uppercase();
+        }
+        help.push_str(&summary);
+        help.push_str(".\n");
+        if !self.flags.is_empty() {
+            help.push('\n');
+            for (flag, text) in self.flags {
+                help.push_str(&format!(
+                    "  {:<width$}{}\n",
+                    flag,
+                    text,
+                    width = OPTION_COLUMN + 8
+                ));
+            }
+        }
+        help
+    }
+
+    /// The formatted manual page, with bold headings
+    pub fn page(&self) -> String {
+        let bold = |text: &str| ansi::paint(text, "1");
+        let indent = " ".repeat(INDENT);
+        let body_width = PAGE_WIDTH - INDENT;
+        let mut page = Vec::new();
+
+        let title = format!("{}(1)", self.name.to_uppercase());
+        let center = "General Commands Manual";
+        let gap = PAGE_WIDTH.saturating_sub(title.len() * 2 + center.len());
+        let left = gap / 2;
+        page.push(format!(
+            "{}{}{}{}{}",
+            title,
+            " ".repeat(left),
+            center,
+            " ".repeat(gap - left),
+            title
+        ));
+        page.push(String::new());
+
+        page.push(bold("NAME"));
+        let mut names = vec![self.name];
+        names.extend(self.aliases.iter().copied());
+        page.push(format!(
+            "{}{} – {}",
+            indent,
+            bold(&names.join(", ")),
+            self.summary
+        ));
+        page.push(String::new());
+
+        page.push(bold("SYNOPSIS"));
+        for line in self.synopsis {
+            let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+            page.push(
+                format!("{}{} {}", indent, bold(command), rest)
+                    .trim_end()
+                    .to_string(),
+            );
+        }
+        page.push(String::new());
+
+        page.push(bold("DESCRIPTION"));
+        for paragraph in self.description.split("\n\n") {
+            for line in wrap(paragraph, body_width) {
+                page.push(format!("{}{}", indent, line));
+            }
+            page.push(String::new());
+        }
+        if !self.flags.is_empty() {
+            page.push(format!("{}The following options are available:", indent));
+            page.push(String::new());
+            let hanging = " ".repeat(INDENT + OPTION_COLUMN);
+            for (flag, text) in self.flags {
+                let mut lines = wrap(text, body_width - OPTION_COLUMN).into_iter();
+                let first = lines.next().unwrap_or_default();
+                if flag.chars().count() < OPTION_COLUMN {
+                    let pad = " ".repeat(OPTION_COLUMN - flag.chars().count());
+                    page.push(format!("{}{}{}{}", indent, bold(flag), pad, first));
+                } else {
+                    page.push(format!("{}{}", indent, bold(flag)));
+                    page.push(format!("{}{}", hanging, first));
+                }
+                for line in lines {
+                    page.push(format!("{}{}", hanging, line));
+                }
+                page.push(String::new());
+            }
+        }
+
+        if !self.see_also.is_empty() {
+            page.push(bold("SEE ALSO"));
+            let references: Vec<String> = self
+                .see_also
+                .iter()
+                .map(|name| format!("{}(1)", name))
+                .collect();
+            page.push(format!("{}{}", indent, references.join(", ")));
+            page.push(String::new());
+        }
+
+        let footer = "VirtualMac";
+        let date = "January 16, 2026";
+        let gap = PAGE_WIDTH.saturating_sub(footer.len() * 2 + date.len());
+        let left = gap / 2;
+        page.push(format!(
+            "{}{}{}{}{}",
+            footer,
+            " ".repeat(left),
+            date,
+            " ".repeat(gap - left),
+            footer
+        ));
+        page.join("\n") + "\n"
+    }
+}
@@ -1,7 +1,9 @@
 use leptos::prelude::*;
 
-use crate::system_state::{ModalType, SystemState};
+use crate::preferences::{Preferences, Value, WINDOW_MANAGER_DOMAIN};
+use crate::system_state::{ListedWindow, ModalType, SystemState};
 use crate::theme::use_theme;
+use crate::window_manager::{TileLayout, WindowAction};
 
 #[component]
 pub fn MenuBar() -> impl IntoView {
@@ -76,8 +78,31 @@ pub fn MenuBar() -> impl IntoView {
         system_state.show_modal(ModalType::ResetDesktopConfirm);
     });
 
+    let on_show_all = Callback::new(move |_| {
+        set_active_menu.set(None);
+        system_state.hidden_apps.set(Vec::new());
+    });
+
+    // Window menu actions on the active window
+    let window_action = move |action: WindowAction| {
+        Callback::new(move |_| {
+            set_active_menu.set(None);
+            system_state.request_window_action(action);
+        })
+    };
+    let tile = move |layout: TileLayout| window_action(WindowAction::Tile(layout));
+
     view! {
-        <div class="menu-bar" on:mouseleave=close_menu>
+        <div
+            class=move || {
+                if system_state.full_screen.get() {
+                    "menu-bar autohide"
+                } else {
+                    "menu-bar"
+                }
+            }
+            on:mouseleave=close_menu
+        >
             <div class="menu-bar-left">
                 <MenuItem
                     id="apple"
@@ -116,9 +141,17 @@ pub fn MenuBar() -> impl IntoView {
                     <DropdownSeparator />
                     <DropdownItem label="Settings..." shortcut="⌘," />
                     <DropdownSeparator />
-                    <DropdownItem label="Hide VirtualMac" shortcut="⌘H" />
-                    <DropdownItem label="Hide Others" shortcut="⌥⌘H" />
-                    <DropdownItem label="Show All" />
+                    <DropdownItem
+                        label="Hide VirtualMac"
+                        shortcut="⌘H"
+                        on_click=window_action(WindowAction::HideActive)
+                    />
+                    <DropdownItem
+                        label="Hide Others"
+                        shortcut="⌥⌘H"
+                        on_click=window_action(WindowAction::HideOthers)
+                    />
+                    <DropdownItem label="Show All" on_click=on_show_all />
                     <DropdownSeparator />
                     <DropdownItem label="Quit VirtualMac" shortcut="⌘Q" />
                 </MenuItem>
@@ -129,7 +162,11 @@ pub fn MenuBar() -> impl IntoView {
                     active_menu=active_menu
                     set_active_menu=set_active_menu
                 >
-                    <DropdownItem label="New Window" shortcut="⌘N" />
+                    <DropdownItem
+                        label="New Window"
+                        shortcut="⌘N"
+                        on_click=window_action(WindowAction::NewWindow)
+                    />
                     <DropdownItem label="New Tab" shortcut="⌘T" />
                     <DropdownSeparator />
                     <DropdownItem label="Open..." shortcut="⌘O" />
@@ -174,7 +211,20 @@ pub fn MenuBar() -> impl IntoView {
                     <DropdownItem label="Show All Tabs" />
                     <DropdownSeparator />
                     <DropdownItem label="Show Sidebar" shortcut="⌘S" />
-                    <DropdownItem label="Enter Full Screen" shortcut="⌃⌘F" />
+                    {move || {
+                        let label = if system_state.full_screen.get() {
+                            "Exit Full Screen"
+                        } else {
+                            "Enter Full Screen"
+                        };
+                        view! {
+                            <DropdownItem
+                                label=label
+                                shortcut="⌃⌘F"
+                                on_click=window_action(WindowAction::ToggleFullScreen)
+                            />
+                        }
+                    }}
                 </MenuItem>
 
                 <MenuItem
@@ -183,15 +233,65 @@ pub fn MenuBar() -> impl IntoView {
                     active_menu=active_menu
                     set_active_menu=set_active_menu
                 >
-                    <DropdownItem label="Minimize" shortcut="⌘M" />
-                    <DropdownItem label="Zoom" />
-                    <DropdownItem label="Tile Window to Left of Screen" />
-                    <DropdownItem label="Tile Window to Right of Screen" />
+                    <DropdownItem
+                        label="Minimize"
+                        shortcut="⌘M"
+                        on_click=window_action(WindowAction::MinimizeActive)
+                    />
+                    <DropdownItem
+                        label="Minimize All"
+                        shortcut="⌥⌘M"
+                        on_click=window_action(WindowAction::MinimizeAll)
+                    />
+                    <DropdownItem label="Zoom" on_click=window_action(WindowAction::ZoomActive) />
+                    <DropdownItem label="Fill" shortcut="⌃⌥↩" on_click=tile(TileLayout::Fill) />
+                    <DropdownSubmenu label="Move & Resize">
+                        <DropdownItem label="Left" shortcut="⌃⌥←" on_click=tile(TileLayout::Left) />
+                        <DropdownItem label="Right" shortcut="⌃⌥→" on_click=tile(TileLayout::Right) />
+                        <DropdownItem label="Top" shortcut="⌃⌥↑" on_click=tile(TileLayout::Top) />
+                        <DropdownItem label="Bottom" shortcut="⌃⌥↓" on_click=tile(TileLayout::Bottom) />
+                        <DropdownSeparator />
+                        <DropdownItem label="Top Left" shortcut="⌃⌥U" on_click=tile(TileLayout::TopLeft) />
+                        <DropdownItem label="Top Right" shortcut="⌃⌥I" on_click=tile(TileLayout::TopRight) />
+                        <DropdownItem label="Bottom Left" shortcut="⌃⌥J" on_click=tile(TileLayout::BottomLeft) />
+                        <DropdownItem label="Bottom Right" shortcut="⌃⌥K" on_click=tile(TileLayout::BottomRight) />
+                        <DropdownSeparator />
+                        <DropdownItem
+                            label="Return to Previous Size"
+                            shortcut="⌃⌥⌫"
+                            on_click=window_action(WindowAction::RestorePreviousSize)
+                        />
+                    </DropdownSubmenu>
                     <DropdownSeparator />
-                    <DropdownItem label="Move Window to Left Side of Screen" />
-                    <DropdownItem label="Move Window to Right Side of Screen" />
+                    <DropdownItem
+                        label="Cycle Through Windows"
+                        shortcut="⌘`"
+                        on_click=window_action(WindowAction::CycleWindows(1))
+                    />
                     <DropdownSeparator />
-                    <DropdownItem label="Bring All to Front" />
+                    <DropdownItem
+                        label="Bring All to Front"
+                        on_click=window_action(WindowAction::BringAllToFront)
+                    />
+                    {move || {
+                        let app = system_state.active_app.get();
+                        let listed: Vec<ListedWindow> = system_state
+                            .window_list
+                            .get()
+                            .into_iter()
+                            .filter(|w| w.app == app)
+                            .collect();
+                        (!listed.is_empty())
+                            .then(|| {
+                                view! {
+                                    <DropdownSeparator />
+                                    {listed
+                                        .into_iter()
+                                        .map(|w| view! { <WindowListItem window=w on_focus=window_action /> })
+                                        .collect_view()}
+                                }
+                            })
+                    }}
                 </MenuItem>
 
                 <MenuItem
@@ -286,6 +386,8 @@ fn DropdownItem(
 
     let handler = move |e: leptos::ev::MouseEvent| {
         if let Some(callback) = on_click {
+            // Keep the click from reaching the menu title, which would reopen it
+            e.stop_propagation();
             callback.run(e);
         }
     };
@@ -302,6 +404,50 @@ fn DropdownItem(
     }
 }
 
+/// A Window menu row that brings one of the active app's windows forward
+#[component]
+fn WindowListItem(
+    window: ListedWindow,
+    on_focus: impl Fn(WindowAction) -> Callback<leptos::ev::MouseEvent> + 'static,
+) -> impl IntoView {
+    let mark = if window.is_active {
+        "✓"
+    } else if window.is_minimized {
+        "◆"
+    } else {
+        ""
+    };
+    let on_click = on_focus(WindowAction::FocusWindow(window.id));
+
+    view! {
+        <div
+            class="dropdown-item window-list-item"
+            on:click=move |e: leptos::ev::MouseEvent| {
+                e.stop_propagation();
+                on_click.run(e);
+            }
+        >
+            <span class="dropdown-check">{mark}</span>
+            <span class="window-list-title">{window.title}</span>
+        </div>
+    }
+}
+
+/// A dropdown row that opens a nested menu on hover
+#[component]
+fn DropdownSubmenu(label: &'static str, children: Children) -> impl IntoView {
+    view! {
+        <div
+            class="dropdown-item dropdown-submenu"
+            on:click=move |e: leptos::ev::MouseEvent| e.stop_propagation()
+        >
+            <span>{label}</span>
+            <span class="dropdown-submenu-arrow">"▸"</span>
+            <div class="submenu-dropdown">{children()}</div>
+        </div>
+    }
+}
+
 #[component]
 fn DropdownSeparator() -> impl IntoView {
     view! { <div class="dropdown-separator"></div> }
@@ -377,6 +523,21 @@ fn ControlCenter(is_open: ReadSignal<bool>) -> impl IntoView {
     let toggle_airdrop = move |_| set_airdrop_on.update(|v| *v = !*v);
     let toggle_dnd = move |_| set_dnd_on.update(|v| *v = !*v);
 
+    // Kept with the window manager's preferences, as `defaults` sees it
+    let preferences = expect_context::<Preferences>();
+    let stage_manager_on = move || {
+        preferences
+            .get_bool(WINDOW_MANAGER_DOMAIN, "GloballyEnabled")
+            .unwrap_or(false)
+    };
+    let toggle_stage_manager = move |_| {
+        preferences.set(
+            WINDOW_MANAGER_DOMAIN,
+            "GloballyEnabled",
+            Value::Bool(!stage_manager_on()),
+        );
+    };
+
     let on_brightness_change = move |e: leptos::ev::Event| {
         let value = event_target_value(&e).parse().unwrap_or(75);
         set_brightness.set(value);
@@ -441,6 +602,27 @@ fn ControlCenter(is_open: ReadSignal<bool>) -> impl IntoView {
                 </div>
             </div>
 
+            <div class="cc-section cc-focus">
+                <div
+                    class=move || {
+                        if stage_manager_on() {
+                            "cc-focus-toggle cc-stage-manager active"
+                        } else {
+                            "cc-focus-toggle cc-stage-manager"
+                        }
+                    }
+                    on:click=toggle_stage_manager
+                >
+                    <div class="cc-focus-icon">"▤"</div>
+                    <div class="cc-focus-info">
+                        <div class="cc-focus-label">"Stage Manager"</div>
+                        <div class="cc-focus-status">
+                            {move || if stage_manager_on() { "On" } else { "Off" }}
+                        </div>
+                    </div>
+                </div>
+            </div>
+
             <div class="cc-section cc-slider-section">
                 <div class="cc-slider">
                     <span class="cc-slider-icon">"🔆"</span>
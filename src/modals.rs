@@ -5,7 +5,9 @@ use wasm_bindgen::closure::Closure;
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::JsCast;
 
+use crate::process::ProcessTable;
 use crate::system_state::{ModalType, PowerState, SystemState};
+use crate::window_manager::AppType;
 
 /// Menu bar height in pixels (matches CSS --menubar-height)
 #[cfg(target_arch = "wasm32")]
@@ -339,30 +341,60 @@ fn LogOutModal() -> impl IntoView {
 #[component]
 fn ForceQuitModal() -> impl IntoView {
     let system_state = expect_context::<SystemState>();
+    let processes = expect_context::<ProcessTable>();
+    let (selected, set_selected) = signal::<Option<u32>>(None);
+
+    // Running apps, one entry per window process
+    let apps = move || {
+        processes
+            .list()
+            .into_iter()
+            .filter(|p| p.window_id.is_some())
+            .collect::<Vec<_>>()
+    };
+
+    let on_force_quit = move |_| {
+        if let Some(pid) = selected.get() {
+            let _ = processes.kill(pid, 9);
+            set_selected.set(None);
+        }
+    };
 
     view! {
         <div class="modal force-quit-modal">
             <div class="force-quit-header">"Force Quit Applications"</div>
             <div class="force-quit-message">"If an app doesn't respond for a while, select its name and click Force Quit."</div>
             <div class="force-quit-list">
-                <div class="force-quit-item">
-                    <span class="app-icon finder-icon"></span>
-                    <span class="app-name">"Finder"</span>
-                </div>
-                <div class="force-quit-item">
-                    <span class="app-icon calculator-icon"></span>
-                    <span class="app-name">"Calculator"</span>
-                </div>
-                <div class="force-quit-item">
-                    <span class="app-icon notes-icon"></span>
-                    <span class="app-name">"Notes"</span>
-                </div>
+                <For
+                    each=apps
+                    key=|p| p.pid
+                    children=move |p| {
+                        let pid = p.pid;
+                        let icon_class = AppType::from_name(&p.name)
+                            .map(|app| format!("app-icon {}-icon", app.icon_class()))
+                            .unwrap_or_else(|| "app-icon".to_string());
+                        view! {
+                            <div
+                                class=move || if selected.get() == Some(pid) { "force-quit-item selected" } else { "force-quit-item" }
+                                on:click=move |_| set_selected.set(Some(pid))
+                            >
+                                <span class=icon_class></span>
+                                <span class="app-name">{p.name.clone()}</span>
+                            </div>
+                        }
+                    }
+                />
             </div>
             <div class="force-quit-footer">
                 <button class="modal-button secondary" on:click=move |_| system_state.close_modal()>
                     "Cancel"
                 </button>
-                <button class="modal-button primary disabled">"Force Quit"</button>
+                <button
+                    class=move || if selected.get().is_some() { "modal-button primary" } else { "modal-button primary disabled" }
+                    on:click=on_force_quit
+                >
+                    "Force Quit"
+                </button>
             </div>
         </div>
     }
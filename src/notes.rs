@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use wasm_bindgen::prelude::*;
 
+use crate::clipboard::Clipboard;
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = document)]
@@ -811,6 +813,7 @@ fn extract_title(content: &str) -> String {
 
 #[component]
 fn NoteEditor(state: ReadSignal<NotesState>, set_state: WriteSignal<NotesState>) -> impl IntoView {
+    let clipboard = expect_context::<Clipboard>();
     let editor_ref: NodeRef<html::Div> = NodeRef::new();
 
     let selected_note = Memo::new(move |_| {
@@ -1076,6 +1079,7 @@ fn NoteEditor(state: ReadSignal<NotesState>, set_state: WriteSignal<NotesState>)
                     on:blur=on_blur
                     on:input=on_input
                     on:keydown=on_keydown
+                    on:paste=move |e| clipboard.handle_paste(&e)
                 />
             </Show>
         </div>
@@ -0,0 +1,272 @@
+//! Minimal regular expressions for Terminal commands like `grep`.
+//!
+//! Supports literals, `.`, bracket expressions (`[a-z]`, `[^0-9]`), the
+//! `*`, `+` and `?` repetitions, `^`/`$` anchors, backslash escapes and
+//! top-level `|` alternation. Matching is backtracking and leftmost-longest
+//! across alternatives.
+
+#[derive(Clone, Debug)]
+enum Atom {
+    Char(char),
+    Any,
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+}
+
+impl Atom {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Atom::Char(expected) => *expected == c,
+            Atom::Any => true,
+            Atom::Class { ranges, negated } => {
+                ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != *negated
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Repeat {
+    One,
+    ZeroOrOne,
+    ZeroOrMore,
+    OneOrMore,
+}
+
+#[derive(Clone, Debug)]
+struct Piece {
+    atom: Atom,
+    repeat: Repeat,
+}
+
+#[derive(Clone, Debug)]
+struct Branch {
+    anchor_start: bool,
+    anchor_end: bool,
+    pieces: Vec<Piece>,
+}
+
+impl Branch {
+    fn parse(source: &[char], ignore_case: bool) -> Result<Branch, String> {
+        let fold = |c: char| if ignore_case { lowercase(c) } else { c };
+        let mut branch = Branch {
+            anchor_start: false,
+            anchor_end: false,
+            pieces: Vec::new(),
+        };
+        let mut i = 0;
+        if source.first() == Some(&'^') {
+            branch.anchor_start = true;
+            i = 1;
+        }
+        while i < source.len() {
+            let c = source[i];
+            let atom = match c {
+                '$' if i + 1 == source.len() => {
+                    branch.anchor_end = true;
+                    i += 1;
+                    continue;
+                }
+                '.' => Atom::Any,
+                '\\' => {
+                    i += 1;
+                    match source.get(i) {
+                        Some(&'t') => Atom::Char('\t'),
+                        Some(&escaped) => Atom::Char(fold(escaped)),
+                        None => return Err("trailing backslash (\\)".to_string()),
+                    }
+                }
+                '[' => {
+                    let (atom, end) = parse_class(source, i, ignore_case)?;
+                    i = end;
+                    atom
+                }
+                '*' | '+' | '?' if branch.pieces.is_empty() => {
+                    return Err("repetition-operator operand invalid".to_string())
+                }
+                '*' | '+' | '?' => {
+                    let last = branch.pieces.last_mut().expect("checked non-empty");
+                    if last.repeat != Repeat::One {
+                        return Err("repetition-operator operand invalid".to_string());
+                    }
+                    last.repeat = match c {
+                        '*' => Repeat::ZeroOrMore,
+                        '+' => Repeat::OneOrMore,
+                        _ => Repeat::ZeroOrOne,
+                    };
+                    i += 1;
+                    continue;
+                }
+                other => Atom::Char(fold(other)),
+            };
+            branch.pieces.push(Piece {
+                atom,
+                repeat: Repeat::One,
+            });
+            i += 1;
+        }
+        Ok(branch)
+    }
+
+    /// Length of the longest match starting at `start`, if any
+    fn match_at(&self, text: &[char], start: usize) -> Option<usize> {
+        let end = match_pieces(&self.pieces, text, start, self.anchor_end)?;
+        Some(end - start)
+    }
+}
+
+/// Parse a bracket expression starting at `source[start] == '['`; returns the
+/// atom and the index of the closing `]`
+fn parse_class(source: &[char], start: usize, ignore_case: bool) -> Result<(Atom, usize), String> {
+    let mut i = start + 1;
+    let negated = matches!(source.get(i), Some('^') | Some('!'));
+    if negated {
+        i += 1;
+    }
+    let mut ranges = Vec::new();
+    let mut first = true;
+    loop {
+        let c = *source
+            .get(i)
+            .ok_or_else(|| "brackets ([ ]) not balanced".to_string())?;
+        if c == ']' && !first {
+            break;
+        }
+        first = false;
+        if source.get(i + 1) == Some(&'-') && source.get(i + 2).is_some_and(|&c| c != ']') {
+            ranges.push((c, source[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((c, c));
+            i += 1;
+        }
+    }
+    if ignore_case {
+        let folded: Vec<(char, char)> = ranges
+            .iter()
+            .filter(|(lo, hi)| lo.is_ascii_uppercase() && hi.is_ascii_uppercase())
+            .map(|&(lo, hi)| (lowercase(lo), lowercase(hi)))
+            .collect();
+        ranges.extend(folded);
+    }
+    Ok((Atom::Class { ranges, negated }, i))
+}
+
+fn match_pieces(pieces: &[Piece], text: &[char], pos: usize, anchor_end: bool) -> Option<usize> {
+    let Some((piece, rest)) = pieces.split_first() else {
+        return (!anchor_end || pos == text.len()).then_some(pos);
+    };
+    let (min, max) = match piece.repeat {
+        Repeat::One => (1, 1),
+        Repeat::ZeroOrOne => (0, 1),
+        Repeat::ZeroOrMore => (0, usize::MAX),
+        Repeat::OneOrMore => (1, usize::MAX),
+    };
+    let mut count = 0;
+    while count < max && pos + count < text.len() && piece.atom.matches(text[pos + count]) {
+        count += 1;
+    }
+    // Greedy: try the longest run first and back off
+    (min..=count)
+        .rev()
+        .find_map(|n| match_pieces(rest, text, pos + n, anchor_end))
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[derive(Clone, Debug)]
+pub struct Regex {
+    branches: Vec<Branch>,
+    ignore_case: bool,
+}
+
+impl Regex {
+    pub fn new(pattern: &str, ignore_case: bool) -> Result<Regex, String> {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut branches = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+        while i <= chars.len() {
+            match chars.get(i) {
+                Some('\\') => i += 1,
+                Some('[') => {
+                    // Skip over the class so a `|` inside it stays literal
+                    let (_, end) = parse_class(&chars, i, false)?;
+                    i = end;
+                }
+                Some('|') | None => {
+                    branches.push(Branch::parse(&chars[start..i], ignore_case)?);
+                    start = i + 1;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        Ok(Regex {
+            branches,
+            ignore_case,
+        })
+    }
+
+    /// A regex matching `text` literally
+    pub fn literal(text: &str, ignore_case: bool) -> Regex {
+        let fold = |c: char| if ignore_case { lowercase(c) } else { c };
+        let pieces = text
+            .chars()
+            .map(|c| Piece {
+                atom: Atom::Char(fold(c)),
+                repeat: Repeat::One,
+            })
+            .collect();
+        Regex {
+            branches: vec![Branch {
+                anchor_start: false,
+                anchor_end: false,
+                pieces,
+            }],
+            ignore_case,
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find_iter(text).next().is_some()
+    }
+
+    /// Byte ranges of successive non-overlapping matches
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let chars: Vec<char> = text
+            .chars()
+            .map(|c| if self.ignore_case { lowercase(c) } else { c })
+            .collect();
+        let offsets: Vec<usize> = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(text.len()))
+            .collect();
+        let mut pos = 0;
+        std::iter::from_fn(move || {
+            while pos <= chars.len() {
+                let start = pos;
+                let longest = self
+                    .branches
+                    .iter()
+                    .filter(|b| !b.anchor_start || start == 0)
+                    .filter_map(|b| b.match_at(&chars, start))
+                    .max();
+                match longest {
+                    Some(len) => {
+                        // Always advance so empty matches can't loop forever
+                        pos = start + len.max(1);
+                        return Some((offsets[start], offsets[start + len]));
+                    }
+                    None => pos += 1,
+                }
+            }
+            None
+        })
+    }
+}
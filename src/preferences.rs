@@ -0,0 +1,320 @@
+//! Per-app preferences, grouped into domains like macOS `defaults`. Apps read
+//! their settings from here (tracked, so they react as soon as a value is
+//! written from System Settings or Terminal) instead of keeping their own
+//! localStorage keys.
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[allow(dead_code)]
+const STORAGE_KEY: &str = "virtualmac_defaults";
+#[allow(dead_code)]
+const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// The domain shared by every app (`defaults -g`)
+pub const GLOBAL_DOMAIN: &str = "NSGlobalDomain";
+pub const DOCK_DOMAIN: &str = "com.apple.dock";
+pub const DESKTOP_DOMAIN: &str = "com.apple.desktop";
+pub const FINDER_DOMAIN: &str = "com.apple.finder";
+pub const WINDOW_MANAGER_DOMAIN: &str = "com.apple.WindowManager";
+
+/// A stored preference value
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Value {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    /// The type name `defaults read-type` reports
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Bool(_) => "boolean",
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::Array(_) => "array",
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Booleans, and numbers and strings that read as one, as macOS does
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            Value::Integer(n) => Some(*n != 0),
+            Value::String(s) => parse_bool(s),
+            _ => None,
+        }
+    }
+
+    /// An array of strings
+    pub fn as_strings(&self) -> Option<Vec<String>> {
+        match self {
+            Value::Array(items) => items
+                .iter()
+                .map(|item| item.as_str().map(str::to_string))
+                .collect(),
+            _ => None,
+        }
+    }
+
+    /// Format in the property list style `defaults read` prints
+    pub fn describe(&self, indent: usize) -> String {
+        match self {
+            Value::Bool(b) => (if *b { "1" } else { "0" }).to_string(),
+            Value::Integer(n) => n.to_string(),
+            Value::Float(f) => f.to_string(),
+            Value::String(s) => quote(s),
+            Value::Array(items) => {
+                let pad = " ".repeat(indent + 4);
+                let mut text = String::from("(\n");
+                for (index, item) in items.iter().enumerate() {
+                    let comma = if index + 1 < items.len() { "," } else { "" };
+                    text.push_str(&format!("{}{}{}\n", pad, item.describe(indent + 4), comma));
+                }
+                text.push_str(&" ".repeat(indent));
+                text.push(')');
+                text
+            }
+        }
+    }
+}
+
+/// Parse a `-bool` argument
+pub fn parse_bool(text: &str) -> Option<bool> {
+    match text.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "1" => Some(true),
+        "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Quote a string unless it is a plain word, as property lists do
+fn quote(text: &str) -> String {
+    let plain = !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_$./:".contains(c));
+    if plain {
+        text.to_string()
+    } else {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Format a domain's keys as a property list dictionary
+pub fn describe_domain(entries: &BTreeMap<String, Value>, indent: usize) -> String {
+    let pad = " ".repeat(indent + 4);
+    let mut text = String::from("{\n");
+    for (key, value) in entries {
+        text.push_str(&format!(
+            "{}{} = {};\n",
+            pad,
+            quote(key),
+            value.describe(indent + 4)
+        ));
+    }
+    text.push_str(&" ".repeat(indent));
+    text.push('}');
+    text
+}
+
+type Domains = BTreeMap<String, BTreeMap<String, Value>>;
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct PreferencesState {
+    schema_version: u32,
+    domains: Domains,
+}
+
+#[derive(Clone, Copy)]
+pub struct Preferences {
+    domains: RwSignal<Domains>,
+}
+
+impl Preferences {
+    pub fn new() -> Self {
+        Self {
+            domains: RwSignal::new(load_from_storage()),
+        }
+    }
+
+    /// Read a value (tracked)
+    pub fn get(&self, domain: &str, key: &str) -> Option<Value> {
+        self.domains
+            .with(|domains| domains.get(domain).and_then(|keys| keys.get(key)).cloned())
+    }
+
+    pub fn get_string(&self, domain: &str, key: &str) -> Option<String> {
+        self.get(domain, key)
+            .and_then(|value| value.as_str().map(str::to_string))
+    }
+
+    pub fn get_bool(&self, domain: &str, key: &str) -> Option<bool> {
+        self.get(domain, key).and_then(|value| value.as_bool())
+    }
+
+    pub fn get_strings(&self, domain: &str, key: &str) -> Option<Vec<String>> {
+        self.get(domain, key).and_then(|value| value.as_strings())
+    }
+
+    /// Every key in a domain, if it has any
+    pub fn domain(&self, domain: &str) -> Option<BTreeMap<String, Value>> {
+        self.domains
+            .with_untracked(|domains| domains.get(domain).cloned())
+    }
+
+    /// Names of every domain with stored keys
+    pub fn domains(&self) -> Vec<String> {
+        self.domains
+            .with_untracked(|domains| domains.keys().cloned().collect())
+    }
+
+    pub fn set(&self, domain: &str, key: &str, value: Value) {
+        if self.get_untracked(domain, key).as_ref() == Some(&value) {
+            return;
+        }
+        self.domains.update(|domains| {
+            domains
+                .entry(domain.to_string())
+                .or_default()
+                .insert(key.to_string(), value);
+        });
+        self.save();
+    }
+
+    /// Remove a key; returns false if it wasn't set
+    pub fn delete(&self, domain: &str, key: &str) -> bool {
+        if self.get_untracked(domain, key).is_none() {
+            return false;
+        }
+        self.domains.update(|domains| {
+            if let Some(keys) = domains.get_mut(domain) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    domains.remove(domain);
+                }
+            }
+        });
+        self.save();
+        true
+    }
+
+    /// Remove a whole domain; returns false if it had no keys
+    pub fn delete_domain(&self, domain: &str) -> bool {
+        if self.domain(domain).is_none() {
+            return false;
+        }
+        self.domains.update(|domains| {
+            domains.remove(domain);
+        });
+        self.save();
+        true
+    }
+
+    fn get_untracked(&self, domain: &str, key: &str) -> Option<Value> {
+        self.domains
+            .with_untracked(|domains| domains.get(domain).and_then(|keys| keys.get(key)).cloned())
+    }
+
+    fn save(&self) {
+        self.domains.with_untracked(save_to_storage);
+    }
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn save_to_storage(domains: &Domains) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        if let Some(window) = web_sys::window() {
+            if let Ok(Some(storage)) = window.local_storage() {
+                let state = PreferencesState {
+                    schema_version: CURRENT_SCHEMA_VERSION,
+                    domains: domains.clone(),
+                };
+                if let Ok(json) = serde_json::to_string(&state) {
+                    let _ = storage.set_item(STORAGE_KEY, &json);
+                }
+            }
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = domains;
+    }
+}
+
+fn load_from_storage() -> Domains {
+    #[cfg(target_arch = "wasm32")]
+    {
+        if let Some(window) = web_sys::window() {
+            if let Ok(Some(storage)) = window.local_storage() {
+                if let Ok(Some(json)) = storage.get_item(STORAGE_KEY) {
+                    if let Ok(state) = serde_json::from_str::<PreferencesState>(&json) {
+                        if state.schema_version == CURRENT_SCHEMA_VERSION {
+                            return state.domains;
+                        }
+                    }
+                }
+                return migrate_legacy_keys(&storage);
+            }
+        }
+    }
+    Domains::new()
+}
+
+/// Carry over the theme and Dock settings saved before preferences were
+/// kept in domains
+#[cfg(target_arch = "wasm32")]
+fn migrate_legacy_keys(storage: &web_sys::Storage) -> Domains {
+    let mut domains = Domains::new();
+    if let Ok(Some(theme)) = storage.get_item("virtualmac-theme") {
+        if theme == "dark" {
+            domains
+                .entry(GLOBAL_DOMAIN.to_string())
+                .or_default()
+                .insert(
+                    "AppleInterfaceStyle".to_string(),
+                    Value::String("Dark".to_string()),
+                );
+        }
+        let _ = storage.remove_item("virtualmac-theme");
+    }
+    if let Ok(Some(json)) = storage.get_item("virtualmac_dock") {
+        let pinned = serde_json::from_str::<serde_json::Value>(&json)
+            .ok()
+            .and_then(|dock| {
+                serde_json::from_value::<Vec<String>>(dock["pinned_apps"].clone()).ok()
+            })
+            .filter(|apps| !apps.is_empty());
+        if let Some(apps) = pinned {
+            domains.entry(DOCK_DOMAIN.to_string()).or_default().insert(
+                "persistent-apps".to_string(),
+                Value::Array(apps.into_iter().map(Value::String).collect()),
+            );
+        }
+        let _ = storage.remove_item("virtualmac_dock");
+    }
+    if !domains.is_empty() {
+        save_to_storage(&domains);
+    }
+    domains
+}
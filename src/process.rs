@@ -0,0 +1,328 @@
+use leptos::prelude::*;
+
+use crate::window_manager::{AppType, WindowId};
+
+/// The logged-in user, who owns every app and shell
+pub const USER: &str = "guest";
+
+/// Signal names by number, as listed by `kill -l`
+pub const SIGNALS: [&str; 31] = [
+    "HUP", "INT", "QUIT", "ILL", "TRAP", "ABRT", "EMT", "FPE", "KILL", "BUS", "SEGV", "SYS",
+    "PIPE", "ALRM", "TERM", "URG", "STOP", "TSTP", "CONT", "CHLD", "TTIN", "TTOU", "IO", "XCPU",
+    "XFSZ", "VTALRM", "PROF", "WINCH", "INFO", "USR1", "USR2",
+];
+
+/// Signals whose default action doesn't terminate the process
+const NON_TERMINATING: [&str; 10] = [
+    "URG", "STOP", "TSTP", "CONT", "CHLD", "TTIN", "TTOU", "IO", "WINCH", "INFO",
+];
+
+/// Installed memory, for `%MEM` and `top`'s summary
+pub const PHYSICAL_MEMORY_KB: u64 = 16 * 1024 * 1024;
+
+/// Look up a signal by number or name, with or without the `SIG` prefix
+pub fn signal_number(spec: &str) -> Option<usize> {
+    if let Ok(number) = spec.parse::<usize>() {
+        return (number <= SIGNALS.len()).then_some(number);
+    }
+    let upper = spec.to_ascii_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    SIGNALS
+        .iter()
+        .position(|signal| *signal == name)
+        .map(|index| index + 1)
+}
+
+/// Whether delivering a signal ends the process
+pub fn terminates(signal: usize) -> bool {
+    signal > 0 && !NON_TERMINATING.contains(&SIGNALS[signal - 1])
+}
+
+/// First PID handed out to apps and shells
+const FIRST_USER_PID: u32 = 412;
+
+/// A running process: an app window, a Terminal shell, or a system daemon
+#[derive(Clone, Debug, PartialEq)]
+pub struct Process {
+    pub pid: u32,
+    pub ppid: u32,
+    pub user: String,
+    /// Short name, as shown by `top` and Force Quit
+    pub name: String,
+    /// Full command line, as shown by `ps`
+    pub command: String,
+    /// Controlling terminal (e.g. `ttys000`), if any
+    pub tty: Option<String>,
+    /// Start time in milliseconds since the epoch
+    pub started: f64,
+    /// Window this process draws, if it's an app
+    pub window_id: Option<WindowId>,
+    /// Resident memory in KB, before jitter
+    base_memory: u64,
+    /// Typical CPU usage in percent
+    base_cpu: f64,
+}
+
+impl Process {
+    /// Synthetic CPU usage in percent, varying every couple of seconds
+    pub fn cpu_percent(&self, now: f64) -> f64 {
+        let tick = (now / 2000.0).max(0.0) as u64;
+        let noise = jitter(self.pid as u64, tick);
+        // Between a tenth and twice the typical load
+        (self.base_cpu * (0.1 + 1.9 * noise) * 10.0).round() / 10.0
+    }
+
+    /// Synthetic resident memory in KB
+    pub fn memory_kb(&self, now: f64) -> u64 {
+        let tick = (now / 10_000.0).max(0.0) as u64;
+        let noise = jitter(self.pid as u64 ^ 0x5bd1, tick);
+        // Within 10% of the base figure
+        (self.base_memory as f64 * (0.95 + 0.1 * noise)) as u64
+    }
+
+    /// Virtual memory size in KB
+    pub fn virtual_kb(&self) -> u64 {
+        // Every macOS process maps a large shared region
+        34_000_000 + self.base_memory * 4
+    }
+
+    /// Accumulated CPU time in seconds
+    pub fn cpu_seconds(&self, now: f64) -> f64 {
+        let elapsed = ((now - self.started) / 1000.0).max(0.0);
+        elapsed * self.base_cpu / 100.0
+    }
+
+    /// Whether `kill` can only be sent by root
+    pub fn is_protected(&self) -> bool {
+        self.user != USER
+    }
+
+    /// Numeric user id, as shown by `ps -f`
+    pub fn uid(&self) -> u32 {
+        match self.user.as_str() {
+            "root" => 0,
+            "_windowserver" => 88,
+            _ => 501,
+        }
+    }
+}
+
+/// Deterministic pseudo-random value in [0, 1) for a seed and time tick
+fn jitter(seed: u64, tick: u64) -> f64 {
+    let mut x = seed
+        .wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        .wrapping_add(tick.wrapping_mul(0xbf58_476d_1ce4_e5b9));
+    x ^= x >> 31;
+    x = x.wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 29;
+    (x % 10_000) as f64 / 10_000.0
+}
+
+/// Current time in milliseconds since the epoch
+pub fn now() -> f64 {
+    #[cfg(target_arch = "wasm32")]
+    {
+        js_sys::Date::now()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        0.0
+    }
+}
+
+/// Why a signal couldn't be delivered
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KillError {
+    NoSuchProcess,
+    NotPermitted,
+}
+
+/// Global process table
+#[derive(Clone, Copy)]
+pub struct ProcessTable {
+    processes: RwSignal<Vec<Process>>,
+    next_pid: RwSignal<u32>,
+    /// Windows whose process was killed, for the window manager to close
+    pub terminated_windows: RwSignal<Vec<WindowId>>,
+}
+
+impl ProcessTable {
+    pub fn new() -> Self {
+        let boot = now() - 3_600_000.0;
+        let system =
+            |pid: u32, user: &str, name: &str, command: &str, memory: u64, cpu: f64| Process {
+                pid,
+                ppid: if pid == 1 { 0 } else { 1 },
+                user: user.to_string(),
+                name: name.to_string(),
+                command: command.to_string(),
+                tty: None,
+                started: boot,
+                window_id: None,
+                base_memory: memory,
+                base_cpu: cpu,
+            };
+        Self {
+            processes: RwSignal::new(vec![
+                system(1, "root", "launchd", "/sbin/launchd", 12_800, 0.4),
+                system(
+                    96,
+                    "root",
+                    "kernel_task",
+                    "kernel_task",
+                    1_540_000,
+                    3.2,
+                ),
+                system(
+                    148,
+                    "_windowserver",
+                    "WindowServer",
+                    "/System/Library/PrivateFrameworks/SkyLight.framework/Resources/WindowServer -daemon",
+                    210_000,
+                    6.5,
+                ),
+            ]),
+            next_pid: RwSignal::new(FIRST_USER_PID),
+            terminated_windows: RwSignal::new(Vec::new()),
+        }
+    }
+
+    /// All processes, ordered by PID
+    pub fn list(&self) -> Vec<Process> {
+        self.processes.get()
+    }
+
+    pub fn get(&self, pid: u32) -> Option<Process> {
+        self.processes
+            .with_untracked(|processes| processes.iter().find(|p| p.pid == pid).cloned())
+    }
+
+    /// Whether a process is still running (tracked, so effects can react to
+    /// it being killed)
+    pub fn is_running(&self, pid: u32) -> bool {
+        self.processes
+            .with(|processes| processes.iter().any(|p| p.pid == pid))
+    }
+
+    fn spawn(&self, mut process: Process) -> u32 {
+        let pid = self.next_pid.get_untracked();
+        // Leave gaps like a real system, where other daemons take PIDs too
+        self.next_pid.set(pid + 1 + pid % 5);
+        process.pid = pid;
+        self.processes.update(|processes| processes.push(process));
+        pid
+    }
+
+    /// Start the process behind an app window
+    pub fn spawn_app(&self, app_type: &AppType, window_id: WindowId) -> u32 {
+        let name = app_type.to_string();
+        let (memory, cpu) = match app_type {
+            AppType::Finder => (92_000, 0.8),
+            AppType::Calculator => (38_000, 0.2),
+            AppType::Terminal => (54_000, 1.1),
+            AppType::TextEdit => (46_000, 0.5),
+            AppType::Notes => (71_000, 0.7),
+            AppType::SystemSettings => (83_000, 0.9),
+        };
+        let command = match app_type {
+            AppType::Finder => {
+                "/System/Library/CoreServices/Finder.app/Contents/MacOS/Finder".to_string()
+            }
+            _ => format!("/Applications/{0}.app/Contents/MacOS/{0}", name),
+        };
+        self.spawn(Process {
+            pid: 0,
+            ppid: 1,
+            user: USER.to_string(),
+            name,
+            command,
+            tty: None,
+            started: now(),
+            window_id: Some(window_id),
+            base_memory: memory,
+            base_cpu: cpu,
+        })
+    }
+
+    /// Start a login shell for a Terminal tab, on the lowest tty no other
+    /// shell holds, returning its PID and tty
+    pub fn spawn_shell(&self) -> (u32, String) {
+        let tty = self.processes.with_untracked(|processes| {
+            (0..)
+                .map(|n| format!("ttys{:03}", n))
+                .find(|tty| !processes.iter().any(|p| p.tty.as_ref() == Some(tty)))
+                .unwrap_or_default()
+        });
+        let pid = self.spawn(Process {
+            pid: 0,
+            ppid: 1,
+            user: USER.to_string(),
+            name: "zsh".to_string(),
+            command: "-zsh".to_string(),
+            tty: Some(tty.clone()),
+            started: now(),
+            window_id: None,
+            base_memory: 2_600,
+            base_cpu: 0.1,
+        });
+        (pid, tty)
+    }
+
+    /// Remove a process that ended on its own (its window or tab closed)
+    pub fn exit(&self, pid: u32) {
+        if self.get(pid).is_some() {
+            self.processes
+                .update(|processes| processes.retain(|p| p.pid != pid));
+        }
+    }
+
+    /// Keep app processes in step with the open windows: start one for each
+    /// new window and end those whose window has closed
+    pub fn sync_windows(&self, windows: &[(WindowId, AppType)]) {
+        let stale: Vec<u32> = self.processes.with_untracked(|processes| {
+            processes
+                .iter()
+                .filter(|p| {
+                    p.window_id
+                        .is_some_and(|id| !windows.iter().any(|(window, _)| *window == id))
+                })
+                .map(|p| p.pid)
+                .collect()
+        });
+        for pid in stale {
+            self.exit(pid);
+        }
+        for (window_id, app_type) in windows {
+            let running = self.processes.with_untracked(|processes| {
+                processes.iter().any(|p| p.window_id == Some(*window_id))
+            });
+            if !running {
+                self.spawn_app(app_type, *window_id);
+            }
+        }
+    }
+
+    /// Send a signal on behalf of the user; terminating signals end the
+    /// process and close its window
+    pub fn kill(&self, pid: u32, signal: usize) -> Result<(), KillError> {
+        let process = self.get(pid).ok_or(KillError::NoSuchProcess)?;
+        if process.is_protected() {
+            return Err(KillError::NotPermitted);
+        }
+        if !terminates(signal) {
+            return Ok(());
+        }
+        self.exit(pid);
+        if let Some(window_id) = process.window_id {
+            self.terminated_windows
+                .update(|windows| windows.push(window_id));
+        }
+        Ok(())
+    }
+}
+
+impl Default for ProcessTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
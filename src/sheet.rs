@@ -0,0 +1,366 @@
+//! Sheets: dialogs that drop down from a window's title bar and block only
+//! that window, like Save panels and "Do you want to save?" prompts. An app
+//! presents one through its window's [`WindowSheet`] and awaits the answer,
+//! while the rest of the desktop stays usable.
+
+use futures_channel::oneshot;
+use leptos::ev::KeyboardEvent;
+use leptos::html::Div;
+use leptos::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::window_manager::{WindowId, WindowState};
+
+/// Folders a Save panel offers
+const SAVE_LOCATIONS: &[&str] = &["/Desktop", "/Documents", "/Downloads"];
+
+/// How a sheet button behaves
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ButtonRole {
+    /// Highlighted, and pressed by Return
+    Default,
+    /// Pressed by Escape
+    Cancel,
+    Normal,
+}
+
+/// A button on an alert sheet
+#[derive(Clone, Debug, PartialEq)]
+pub struct SheetButton {
+    pub label: String,
+    pub role: ButtonRole,
+}
+
+impl SheetButton {
+    pub fn new(label: &str, role: ButtonRole) -> Self {
+        Self {
+            label: label.to_string(),
+            role,
+        }
+    }
+}
+
+/// What a sheet asks
+#[derive(Clone, Debug, PartialEq)]
+pub enum Sheet {
+    /// A message with a row of buttons
+    Alert {
+        title: String,
+        message: String,
+        buttons: Vec<SheetButton>,
+    },
+    /// A file name and the folder to save it in
+    Save { name: String, folder: String },
+}
+
+/// How a sheet was answered
+#[derive(Clone, Debug, PartialEq)]
+pub enum SheetResponse {
+    /// Index of the alert button pressed
+    Button(usize),
+    /// Path chosen in a Save panel
+    Save(String),
+    /// Dismissed without an answer
+    Cancel,
+}
+
+/// The sheet slot of one window, handed to the app inside it
+#[derive(Clone, Copy)]
+pub struct WindowSheet {
+    window_id: WindowId,
+    set_windows: WriteSignal<Vec<WindowState>>,
+    current: RwSignal<Option<Sheet>>,
+    reply: StoredValue<Option<oneshot::Sender<SheetResponse>>>,
+    close_guard: StoredValue<Option<Callback<(), bool>>>,
+}
+
+impl WindowSheet {
+    pub(crate) fn new(window_id: WindowId, set_windows: WriteSignal<Vec<WindowState>>) -> Self {
+        Self {
+            window_id,
+            set_windows,
+            current: RwSignal::new(None),
+            reply: StoredValue::new(None),
+            close_guard: StoredValue::new(None),
+        }
+    }
+
+    /// Show a sheet over the window and wait for it to be answered. A sheet
+    /// already up is cancelled in favour of the new one.
+    pub async fn present(&self, sheet: Sheet) -> SheetResponse {
+        let (sender, receiver) = oneshot::channel();
+        if let Some(previous) = self
+            .reply
+            .try_update_value(|reply| reply.replace(sender))
+            .flatten()
+        {
+            let _ = previous.send(SheetResponse::Cancel);
+        }
+        self.current.set(Some(sheet));
+        // The sender is dropped with the window, if it closes first
+        receiver.await.unwrap_or(SheetResponse::Cancel)
+    }
+
+    /// Ask a question, returning the index of the button pressed
+    pub async fn alert(
+        &self,
+        title: &str,
+        message: &str,
+        buttons: Vec<SheetButton>,
+    ) -> Option<usize> {
+        let sheet = Sheet::Alert {
+            title: title.to_string(),
+            message: message.to_string(),
+            buttons,
+        };
+        match self.present(sheet).await {
+            SheetResponse::Button(index) => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Ask where to save a file, returning its path
+    pub async fn save_panel(&self, name: &str, folder: &str) -> Option<String> {
+        let sheet = Sheet::Save {
+            name: name.to_string(),
+            folder: folder.to_string(),
+        };
+        match self.present(sheet).await {
+            SheetResponse::Save(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Let the app object to its window closing, e.g. to ask about unsaved
+    /// changes. The guard returns true to keep the window open, and can
+    /// [`close`](Self::close) it later.
+    pub fn guard_close(&self, guard: impl Fn() -> bool + Send + Sync + 'static) {
+        self.close_guard
+            .set_value(Some(Callback::new(move |()| guard())));
+    }
+
+    /// Close the window without consulting the guard
+    pub fn close(&self) {
+        let window_id = self.window_id;
+        self.set_windows.update(|windows| {
+            windows.retain(|w| w.id != window_id);
+        });
+    }
+
+    /// Whether a sheet is up
+    pub fn is_presented(&self) -> bool {
+        self.current.with(Option::is_some)
+    }
+
+    /// Whether the window should stay open when asked to close: while a sheet
+    /// is up, or when the app's guard says so
+    pub(crate) fn keeps_open(&self) -> bool {
+        self.current.with_untracked(Option::is_some)
+            || self
+                .close_guard
+                .try_get_value()
+                .flatten()
+                .is_some_and(|guard| guard.run(()))
+    }
+
+    fn respond(&self, response: SheetResponse) {
+        self.current.set(None);
+        if let Some(reply) = self.reply.try_update_value(Option::take).flatten() {
+            let _ = reply.send(response);
+        }
+    }
+}
+
+/// The sheet handle of the window the calling app is shown in
+pub fn use_window_sheet() -> Option<WindowSheet> {
+    use_context::<WindowSheet>()
+}
+
+/// Where a Save panel puts `name` in `folder`
+fn save_path(folder: &str, name: &str) -> String {
+    format!("{}/{}", folder.trim_end_matches('/'), name)
+}
+
+/// The sheet of a window, if one is up, covering the content below the
+/// title bar
+#[component]
+pub fn SheetView(sheet: WindowSheet) -> impl IntoView {
+    move || {
+        sheet.current.get().map(|current| {
+            let panel_ref = NodeRef::<Div>::new();
+            let body = match current {
+                Sheet::Alert {
+                    title,
+                    message,
+                    buttons,
+                } => alert_view(sheet, panel_ref, title, message, buttons),
+                Sheet::Save { name, folder } => save_view(sheet, panel_ref, name, folder),
+            };
+
+            // Take focus, so Return and Escape answer the sheet
+            Effect::new(move |_| {
+                let Some(panel) = panel_ref.get() else {
+                    return;
+                };
+                let input = panel
+                    .query_selector("input")
+                    .ok()
+                    .flatten()
+                    .and_then(|input| input.dyn_into::<web_sys::HtmlInputElement>().ok());
+                if let Some(input) = input {
+                    let _ = input.focus();
+                    // Select the name, not its extension
+                    let value = input.value();
+                    let end = value
+                        .rfind('.')
+                        .filter(|&dot| dot > 0)
+                        .unwrap_or(value.len());
+                    let _ =
+                        input.set_selection_range(0, value[..end].encode_utf16().count() as u32);
+                } else {
+                    let _ = panel.focus();
+                }
+            });
+
+            view! {
+                <div class="sheet-backdrop">
+                    {body}
+                </div>
+            }
+        })
+    }
+}
+
+fn alert_view(
+    sheet: WindowSheet,
+    panel_ref: NodeRef<Div>,
+    title: String,
+    message: String,
+    buttons: Vec<SheetButton>,
+) -> AnyView {
+    let find = |role: ButtonRole| buttons.iter().position(|button| button.role == role);
+    let default_button = find(ButtonRole::Default);
+    let cancel_button = find(ButtonRole::Cancel);
+
+    let on_keydown = move |e: KeyboardEvent| {
+        let button = match e.key().as_str() {
+            "Enter" => default_button,
+            "Escape" => cancel_button,
+            _ => return,
+        };
+        e.prevent_default();
+        e.stop_propagation();
+        if let Some(index) = button {
+            sheet.respond(SheetResponse::Button(index));
+        }
+    };
+
+    let buttons = buttons
+        .into_iter()
+        .enumerate()
+        .map(|(index, button)| {
+            let class = if button.role == ButtonRole::Default {
+                "modal-button primary sheet-button"
+            } else {
+                "modal-button secondary sheet-button"
+            };
+            view! {
+                <button class=class on:click=move |_| sheet.respond(SheetResponse::Button(index))>
+                    {button.label}
+                </button>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div class="sheet" tabindex="-1" node_ref=panel_ref on:keydown=on_keydown>
+            <div class="sheet-title">{title}</div>
+            <div class="sheet-message">{message}</div>
+            <div class="sheet-buttons">{buttons}</div>
+        </div>
+    }
+    .into_any()
+}
+
+fn save_view(sheet: WindowSheet, panel_ref: NodeRef<Div>, name: String, folder: String) -> AnyView {
+    let mut locations: Vec<String> = SAVE_LOCATIONS.iter().map(|l| l.to_string()).collect();
+    if !locations.contains(&folder) {
+        locations.insert(0, folder.clone());
+    }
+    let name = RwSignal::new(name);
+    let folder = RwSignal::new(folder);
+
+    let valid = move || name.with(|name| !name.trim().is_empty() && !name.contains('/'));
+    let save = move || {
+        if valid() {
+            let path = save_path(&folder.get_untracked(), name.get_untracked().trim());
+            sheet.respond(SheetResponse::Save(path));
+        }
+    };
+
+    let on_keydown = move |e: KeyboardEvent| {
+        match e.key().as_str() {
+            "Enter" => save(),
+            "Escape" => sheet.respond(SheetResponse::Cancel),
+            _ => return,
+        }
+        e.prevent_default();
+        e.stop_propagation();
+    };
+
+    let options = locations
+        .into_iter()
+        .map(|location| {
+            let label = location.rsplit('/').next().unwrap_or_default().to_string();
+            let label = if label.is_empty() {
+                "/".to_string()
+            } else {
+                label
+            };
+            let selected = folder.get_untracked() == location;
+            view! { <option value=location selected=selected>{label}</option> }
+        })
+        .collect_view();
+
+    view! {
+        <div class="sheet save-sheet" tabindex="-1" node_ref=panel_ref on:keydown=on_keydown>
+            <label class="sheet-field">
+                <span class="sheet-label">"Save As:"</span>
+                <input
+                    class="sheet-input"
+                    type="text"
+                    prop:value=move || name.get()
+                    on:input=move |e| name.set(event_target_value(&e))
+                />
+            </label>
+            <label class="sheet-field">
+                <span class="sheet-label">"Where:"</span>
+                <select
+                    class="sheet-select"
+                    on:change=move |e| folder.set(event_target_value(&e))
+                >
+                    {options}
+                </select>
+            </label>
+            <div class="sheet-buttons">
+                <button
+                    class="modal-button secondary sheet-button"
+                    on:click=move |_| sheet.respond(SheetResponse::Cancel)
+                >
+                    "Cancel"
+                </button>
+                <button
+                    class=move || if valid() {
+                        "modal-button primary sheet-button"
+                    } else {
+                        "modal-button primary sheet-button disabled"
+                    }
+                    on:click=move |_| save()
+                >
+                    "Save"
+                </button>
+            </div>
+        </div>
+    }
+    .into_any()
+}
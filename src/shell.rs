@@ -0,0 +1,4813 @@
+//! Shell interpreter behind the Terminal app.
+//!
+//! Parses zsh/sh-style command lines and scripts (pipelines, `&&`/`||`,
+//! `if`/`for`/`while`, functions, redirections) and runs them against the
+//! virtual file system. The Terminal component owns one `Shell` per session
+//! and renders whatever it prints.
+
+use crate::ansi;
+use crate::clipboard::Clipboard;
+use crate::encoding;
+use crate::file_system::{EntryType, VirtualFileSystem};
+use crate::file_types;
+use crate::manual::{self, Manual, Options};
+use crate::notification::NotificationState;
+use crate::pattern::Regex;
+use crate::preferences::{self, Preferences};
+use crate::process::{self, KillError, ProcessTable};
+use crate::window_manager::AppType;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Maximum nesting of function calls and scripts
+const MAX_CALL_DEPTH: usize = 100;
+/// Iterations after which a `while`/`until` loop is treated as runaway
+const MAX_LOOP_ITERATIONS: usize = 10_000;
+/// Process ID reported by `$$`
+const SHELL_PID: u32 = 4242;
+/// Events `history` lists when not given a range
+const HISTORY_LIST_LENGTH: usize = 16;
+/// SGR colors used by `ls -G` and `grep --color` (BSD defaults)
+const DIRECTORY_COLOR: &str = "34";
+const DEFAULT_MATCH_COLOR: &str = "01;31";
+
+/// Environment a fresh login shell starts with
+pub fn default_environment() -> BTreeMap<String, String> {
+    [
+        ("HOME", "/"),
+        ("USER", "guest"),
+        ("LOGNAME", "guest"),
+        ("HOST", "virtualmac"),
+        ("SHELL", "/bin/zsh"),
+        ("PATH", "/usr/local/bin:/usr/bin:/bin:/usr/sbin:/sbin"),
+        ("PWD", "/"),
+        ("PS1", "%n@%m %1~ %# "),
+        ("TERM", "xterm-256color"),
+        ("LANG", "en_US.UTF-8"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v.to_string()))
+    .collect()
+}
+
+/// Check whether a string is a valid shell variable name
+fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Split `NAME=value` into its parts if the word is a variable assignment
+fn parse_assignment(word: &str) -> Option<(&str, &str)> {
+    let (name, value) = word.split_once('=')?;
+    is_valid_identifier(name).then_some((name, value))
+}
+
+/// Replace the home directory prefix of a path with `~`
+pub fn abbreviate_home(path: &str, home: &str) -> String {
+    if path == home {
+        "~".to_string()
+    } else if home == "/" {
+        format!("~{}", path)
+    } else if let Some(rest) = path.strip_prefix(&format!("{}/", home)) {
+        format!("~/{}", rest)
+    } else {
+        path.to_string()
+    }
+}
+
+/// Expand zsh prompt escapes in PS1 (%n, %m, %~, %1~, %c, %d, %#, %%)
+pub fn expand_prompt(ps1: &str, env: &BTreeMap<String, String>, cwd: &str) -> String {
+    let home = env.get("HOME").map(String::as_str).unwrap_or("/");
+    let user = env.get("USER").map(String::as_str).unwrap_or("guest");
+    let host = env.get("HOST").map(String::as_str).unwrap_or("virtualmac");
+    let abbreviated = abbreviate_home(cwd, home);
+
+    // Last `n` path components of the home-abbreviated cwd
+    let trailing = |n: usize| -> String {
+        let parts: Vec<&str> = abbreviated.split('/').filter(|p| !p.is_empty()).collect();
+        if parts.is_empty() {
+            return "/".to_string();
+        }
+        if n == 0 || n >= parts.len() {
+            return abbreviated.clone();
+        }
+        parts[parts.len() - n..].join("/")
+    };
+
+    let mut result = String::new();
+    let mut chars = ps1.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        let mut digits = String::new();
+        while let Some(&d) = chars.peek() {
+            if d.is_ascii_digit() {
+                digits.push(d);
+                chars.next();
+            } else {
+                break;
+            }
+        }
+        let count = digits.parse::<usize>().unwrap_or(0);
+        match chars.next() {
+            Some('n') => result.push_str(user),
+            Some('m') => result.push_str(host.split('.').next().unwrap_or(host)),
+            Some('M') => result.push_str(host),
+            Some('~') => result.push_str(&trailing(count)),
+            Some('c') | Some('.') => result.push_str(&trailing(count.max(1))),
+            Some('d') | Some('/') => result.push_str(cwd),
+            Some('#') => result.push('%'),
+            Some('%') => result.push('%'),
+            Some(other) => {
+                result.push('%');
+                result.push_str(&digits);
+                result.push(other);
+            }
+            None => result.push('%'),
+        }
+    }
+    result
+}
+
+/// Canonicalize `target` against `cwd`: expands a leading `~`, drops `.`
+/// segments and resolves `..` (never climbing above `/`)
+pub fn resolve_path(cwd: &str, home: &str, target: &str) -> String {
+    let joined = if target == "~" {
+        home.to_string()
+    } else if let Some(rest) = target.strip_prefix("~/") {
+        format!("{}/{}", home, rest)
+    } else if target.starts_with('/') {
+        target.to_string()
+    } else {
+        format!("{}/{}", cwd, target)
+    };
+
+    let mut parts: Vec<&str> = Vec::new();
+    for segment in joined.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            name => parts.push(name),
+        }
+    }
+    format!("/{}", parts.join("/"))
+}
+
+/// Quote a word for display so it reads back as the same word
+fn quote_word(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c));
+    if plain {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// Expand history references in a line typed at the prompt: `!!`, `!n`,
+/// `!-n`, `!prefix`, `!?text?` and `!$`. `history` is oldest first and
+/// numbered from 1. Returns `Ok(None)` when the line has no references.
+pub fn expand_history(line: &str, history: &[String]) -> Result<Option<String>, String> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::new();
+    let mut expanded = false;
+    let mut in_single_quotes = false;
+    let mut in_double_quotes = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        // Single quotes stop expansion, but not inside double quotes
+        if c == '\'' && !in_double_quotes {
+            in_single_quotes = !in_single_quotes;
+        } else if c == '"' && !in_single_quotes {
+            in_double_quotes = !in_double_quotes;
+        }
+        if c == '\\' && !in_single_quotes {
+            // Keep the escape for the parser; an escaped `!` is not an event
+            out.push(c);
+            out.extend(chars.get(i + 1));
+            i += 2;
+            continue;
+        }
+        let designator = chars.get(i + 1).copied();
+        if c != '!'
+            || in_single_quotes
+            || designator.is_none_or(|d| d.is_whitespace() || "=(\"".contains(d))
+        {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+
+        let start = i + 1;
+        let (event, end) = match designator {
+            Some('!') => (history.last(), start + 1),
+            Some('$') => {
+                let word = history
+                    .last()
+                    .and_then(|event| event.split_whitespace().last())
+                    .ok_or_else(|| "no such word in event".to_string())?;
+                out.push_str(word);
+                expanded = true;
+                i = start + 1;
+                continue;
+            }
+            Some('?') => {
+                let close = chars[start + 1..]
+                    .iter()
+                    .position(|&c| c == '?')
+                    .map(|p| start + 1 + p);
+                let text: String = chars[start + 1..close.unwrap_or(chars.len())]
+                    .iter()
+                    .collect();
+                let event = history.iter().rev().find(|event| event.contains(&text));
+                if event.is_none() {
+                    return Err(format!("no such event: {}", text));
+                }
+                (event, close.map_or(chars.len(), |p| p + 1))
+            }
+            Some(d) if d.is_ascii_digit() || d == '-' => {
+                let digits_start = if d == '-' { start + 1 } else { start };
+                let digits_end = digits_start
+                    + chars[digits_start..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_digit())
+                        .count();
+                let number: String = chars[start..digits_end].iter().collect();
+                let event = match number.parse::<i64>() {
+                    Ok(n) if n > 0 => history.get(n as usize - 1),
+                    Ok(n) if n < 0 => history
+                        .len()
+                        .checked_sub(n.unsigned_abs() as usize)
+                        .and_then(|index| history.get(index)),
+                    _ => None,
+                };
+                if event.is_none() {
+                    return Err(format!("no such event: {}", number));
+                }
+                (event, digits_end)
+            }
+            _ => {
+                let end = start
+                    + chars[start..]
+                        .iter()
+                        .take_while(|c| !c.is_whitespace() && !";|&<>()'\"".contains(**c))
+                        .count();
+                let prefix: String = chars[start..end].iter().collect();
+                let event = history
+                    .iter()
+                    .rev()
+                    .find(|event| event.starts_with(&prefix));
+                if event.is_none() {
+                    return Err(format!("event not found: {}", prefix));
+                }
+                (event, end)
+            }
+        };
+        let event = event.ok_or_else(|| "no such event: 0".to_string())?;
+        out.push_str(event);
+        expanded = true;
+        i = end;
+    }
+    Ok(expanded.then_some(out))
+}
+
+/// Whether a pattern contains unescaped `*`, `?` or `[`
+fn has_glob_chars(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '*' | '?' => return true,
+            // Only a bracket expression with a closing `]` is a pattern, so
+            // the `[` command stays a plain word
+            '[' => {
+                let rest = chars.as_str();
+                let rest = rest.strip_prefix(['!', '^']).unwrap_or(rest);
+                if rest.chars().skip(1).any(|c| c == ']') {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Remove the escaping backslashes from a pattern
+fn unescape_pattern(pattern: &str) -> String {
+    let mut result = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.extend(chars.next()),
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Match a file name against a glob pattern supporting `*`, `?`, `[abc]`,
+/// `[a-z]` and `[!x]`; backslash escapes the next character
+fn glob_match(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        None => name.is_empty(),
+        Some('*') => (0..=name.len()).any(|skip| glob_match(&pattern[1..], &name[skip..])),
+        Some('?') => !name.is_empty() && glob_match(&pattern[1..], &name[1..]),
+        Some('[') => {
+            let Some(&c) = name.first() else {
+                return false;
+            };
+            let mut i = 1;
+            let negated = matches!(pattern.get(i), Some('!') | Some('^'));
+            if negated {
+                i += 1;
+            }
+            let mut matched = false;
+            let mut first = true;
+            while i < pattern.len() && (pattern[i] != ']' || first) {
+                first = false;
+                let low = pattern[i];
+                if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&h| h != ']')
+                {
+                    matched |= low <= c && c <= pattern[i + 2];
+                    i += 3;
+                } else {
+                    matched |= low == c;
+                    i += 1;
+                }
+            }
+            if i >= pattern.len() {
+                // No closing bracket: treat `[` literally
+                return c == '[' && glob_match(&pattern[1..], &name[1..]);
+            }
+            matched != negated && glob_match(&pattern[i + 1..], &name[1..])
+        }
+        Some('\\') if pattern.len() > 1 => {
+            name.first() == Some(&pattern[1]) && glob_match(&pattern[2..], &name[1..])
+        }
+        Some(&p) => name.first() == Some(&p) && glob_match(&pattern[1..], &name[1..]),
+    }
+}
+
+pub fn get_current_date() -> String {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let date = js_sys::Date::new_0();
+        let days = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+        let months = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ];
+
+        format!(
+            "{} {} {:2} {:02}:{:02}:{:02} UTC {}",
+            days[date.get_day() as usize],
+            months[date.get_month() as usize],
+            date.get_date(),
+            date.get_hours(),
+            date.get_minutes(),
+            date.get_seconds(),
+            date.get_full_year()
+        )
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        "Thu Jan 16 12:00:00 UTC 2026".to_string()
+    }
+}
+
+/// Format accumulated CPU time as `ps` does (`m:ss.cc`)
+fn format_cpu_time(seconds: f64) -> String {
+    let centis = (seconds * 100.0) as u64;
+    format!(
+        "{}:{:02}.{:02}",
+        centis / 6000,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+/// Format a start time as `ps` does (`9:41AM`)
+fn format_start_time(timestamp: f64) -> String {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let date = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(timestamp));
+        let hours = date.get_hours();
+        let suffix = if hours < 12 { "AM" } else { "PM" };
+        let hours = match hours % 12 {
+            0 => 12,
+            h => h,
+        };
+        format!("{}:{:02}{}", hours, date.get_minutes(), suffix)
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let _ = timestamp;
+        "12:00PM".to_string()
+    }
+}
+
+/// Format a size in KB the way `top` does (`92M`, `2604K`)
+fn format_memory(kb: u64) -> String {
+    if kb >= 10 * 1024 {
+        format!("{}M", kb / 1024)
+    } else {
+        format!("{}K", kb)
+    }
+}
+
+/// Split leading single-letter option clusters (already checked against
+/// the command's manual) from its operands
+fn split_options(args: &[String]) -> (String, &[String]) {
+    let mut flags = String::new();
+    for (index, arg) in args.iter().enumerate() {
+        if arg == "--" {
+            return (flags, &args[index + 1..]);
+        }
+        if arg == "-" || !arg.starts_with('-') {
+            return (flags, &args[index..]);
+        }
+        flags.push_str(&arg[1..]);
+    }
+    (flags, &[])
+}
+
+/// A getopt-style option a command couldn't accept
+enum OptionError {
+    Illegal(char),
+    MissingValue(char),
+}
+
+/// Options with their values (empty for flags), and the operands after them
+type ParsedOptions<'a> = (Vec<(char, String)>, &'a [String]);
+
+/// Split arguments getopt-style into options and operands. `flags` take no
+/// value; options in `with_value` take the rest of the word or the next
+/// argument.
+fn parse_options<'a>(
+    args: &'a [String],
+    flags: &str,
+    with_value: &str,
+) -> Result<ParsedOptions<'a>, OptionError> {
+    let mut options = Vec::new();
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        if arg == "--" {
+            index += 1;
+            break;
+        }
+        if arg == "-" || !arg.starts_with('-') {
+            break;
+        }
+        index += 1;
+        for (position, option) in arg.char_indices().skip(1) {
+            if flags.contains(option) {
+                options.push((option, String::new()));
+            } else if with_value.contains(option) {
+                let rest = &arg[position + option.len_utf8()..];
+                let value = if rest.is_empty() {
+                    let value = args.get(index).ok_or(OptionError::MissingValue(option))?;
+                    index += 1;
+                    value.clone()
+                } else {
+                    rest.to_string()
+                };
+                options.push((option, value));
+                break;
+            } else {
+                return Err(OptionError::Illegal(option));
+            }
+        }
+    }
+    Ok((options, &args[index..]))
+}
+
+/// Parse the value arguments of `defaults write`, which start with an
+/// optional type. On failure, returns the argument that couldn't be used.
+fn parse_default_value(
+    args: &[String],
+    existing: Option<preferences::Value>,
+) -> Result<preferences::Value, String> {
+    use preferences::Value;
+    let strings = |values: &[String]| {
+        values
+            .iter()
+            .map(|value| Value::String(value.clone()))
+            .collect::<Vec<_>>()
+    };
+    let (kind, values) = match args.split_first() {
+        Some((kind, values)) if kind.starts_with('-') => (kind.as_str(), values),
+        _ => ("-string", args),
+    };
+    match (kind, values) {
+        ("-array", values) => Ok(Value::Array(strings(values))),
+        ("-array-add", values) => match existing {
+            Some(Value::Array(mut items)) => {
+                items.extend(strings(values));
+                Ok(Value::Array(items))
+            }
+            _ => Ok(Value::Array(strings(values))),
+        },
+        ("-string", [value]) => Ok(Value::String(value.clone())),
+        ("-int" | "-integer", [value]) => {
+            value.parse().map(Value::Integer).map_err(|_| value.clone())
+        }
+        ("-float", [value]) => value.parse().map(Value::Float).map_err(|_| value.clone()),
+        ("-bool" | "-boolean", [value]) => preferences::parse_bool(value)
+            .map(Value::Bool)
+            .ok_or_else(|| value.clone()),
+        ("-string" | "-int" | "-integer" | "-float" | "-bool" | "-boolean", [_, extra, ..]) => {
+            Err(extra.clone())
+        }
+        (kind, _) => Err(kind.to_string()),
+    }
+}
+
+/// Interpret backslash escapes as `echo -e` and `printf` do. `\0NNN` octal
+/// is used by echo, bare `\NNN` by printf formats. Returns the text and
+/// whether a `\c` asked to stop all further output.
+fn interpret_escapes(text: &str, bare_octal: bool) -> (String, bool) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '\\' || i + 1 == chars.len() {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        i += 1;
+        let simple = match chars[i] {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            'a' => Some('\x07'),
+            'b' => Some('\x08'),
+            'f' => Some('\x0c'),
+            'v' => Some('\x0b'),
+            'e' | 'E' => Some('\x1b'),
+            '\\' => Some('\\'),
+            _ => None,
+        };
+        if let Some(c) = simple {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+        // Read up to `max` digits in `radix` starting at `from`
+        let digits = |from: usize, max: usize, radix: u32| {
+            let len = chars[from..]
+                .iter()
+                .take(max)
+                .take_while(|c| c.is_digit(radix))
+                .count();
+            let value: String = chars[from..from + len].iter().collect();
+            (u32::from_str_radix(&value, radix).ok(), len)
+        };
+        match chars[i] {
+            'c' => return (out, true),
+            'x' => match digits(i + 1, 2, 16) {
+                (Some(value), len) => {
+                    out.extend(char::from_u32(value));
+                    i += 1 + len;
+                }
+                _ => {
+                    out.push_str("\\x");
+                    i += 1;
+                }
+            },
+            '0' if !bare_octal => {
+                let (value, len) = digits(i + 1, 3, 8);
+                out.extend(char::from_u32(value.unwrap_or(0)));
+                i += 1 + len;
+            }
+            '0'..='7' if bare_octal => {
+                let (value, len) = digits(i, 3, 8);
+                out.extend(value.and_then(char::from_u32));
+                i += len;
+            }
+            other => {
+                out.push('\\');
+                out.push(other);
+                i += 1;
+            }
+        }
+    }
+    (out, false)
+}
+
+/// Pad `text` to `width` columns, on the right if `left` is set
+fn pad(text: String, width: usize, left: bool, fill: char) -> String {
+    let len = text.chars().count();
+    if len >= width {
+        return text;
+    }
+    let padding: String = std::iter::repeat_n(fill, width - len).collect();
+    if left {
+        text + &padding
+    } else if fill == '0' && (text.starts_with('-') || text.starts_with('+')) {
+        // Zero padding goes between the sign and the digits
+        format!("{}{}{}", &text[..1], padding, &text[1..])
+    } else {
+        padding + &text
+    }
+}
+
+/// Expand a `printf` format against its arguments, reusing the format while
+/// arguments remain. Errors are reported for each unusable argument.
+fn format_printf(format: &str, args: &[String]) -> (String, Vec<String>) {
+    let chars: Vec<char> = format.chars().collect();
+    let mut out = String::new();
+    let mut errors = Vec::new();
+    let mut next = 0;
+
+    loop {
+        let mut i = 0;
+        let mut literal = String::new();
+        while i < chars.len() {
+            if chars[i] != '%' {
+                literal.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            let (text, stop) = interpret_escapes(&std::mem::take(&mut literal), true);
+            out.push_str(&text);
+            if stop {
+                return (out, errors);
+            }
+            i += 1;
+            if chars.get(i) == Some(&'%') {
+                out.push('%');
+                i += 1;
+                continue;
+            }
+            let mut flags = String::new();
+            while let Some(&c) = chars.get(i).filter(|c| "-+ 0#".contains(**c)) {
+                flags.push(c);
+                i += 1;
+            }
+            let mut take_number = |i: &mut usize| -> Option<usize> {
+                if chars.get(*i) == Some(&'*') {
+                    *i += 1;
+                    let arg = args.get(next).cloned().unwrap_or_default();
+                    next += 1;
+                    return arg.parse().ok();
+                }
+                let start = *i;
+                while chars.get(*i).is_some_and(char::is_ascii_digit) {
+                    *i += 1;
+                }
+                chars[start..*i].iter().collect::<String>().parse().ok()
+            };
+            let width = take_number(&mut i).unwrap_or(0);
+            let precision = if chars.get(i) == Some(&'.') {
+                i += 1;
+                Some(take_number(&mut i).unwrap_or(0))
+            } else {
+                None
+            };
+            let Some(&conversion) = chars.get(i) else {
+                out.push('%');
+                break;
+            };
+            i += 1;
+
+            let arg = args.get(next).cloned();
+            if arg.is_some() {
+                next += 1;
+            }
+            let arg = arg.unwrap_or_default();
+            let left = flags.contains('-');
+            let zero = flags.contains('0') && !left;
+            let mut integer = |arg: &str| -> i64 {
+                if arg.is_empty() {
+                    return 0;
+                }
+                if let Some(c) = arg.strip_prefix(['\'', '"']) {
+                    return c.chars().next().map_or(0, |c| c as i64);
+                }
+                let parsed = if let Some(hex) = arg.strip_prefix("0x") {
+                    i64::from_str_radix(hex, 16).ok()
+                } else {
+                    arg.parse().ok()
+                };
+                parsed.unwrap_or_else(|| {
+                    errors.push(format!("printf: {}: expected numeric value", arg));
+                    0
+                })
+            };
+            let text = match conversion {
+                'd' | 'i' => {
+                    let value = integer(&arg);
+                    if flags.contains('+') && value >= 0 {
+                        format!("+{}", value)
+                    } else {
+                        value.to_string()
+                    }
+                }
+                'u' => (integer(&arg) as u64).to_string(),
+                'x' => format!("{:x}", integer(&arg)),
+                'X' => format!("{:X}", integer(&arg)),
+                'o' => format!("{:o}", integer(&arg)),
+                'c' => arg.chars().next().map(String::from).unwrap_or_default(),
+                's' => match precision {
+                    Some(p) => arg.chars().take(p).collect(),
+                    None => arg,
+                },
+                'b' => {
+                    let (text, stop) = interpret_escapes(&arg, false);
+                    if stop {
+                        out.push_str(&text);
+                        return (out, errors);
+                    }
+                    text
+                }
+                'f' | 'F' | 'e' | 'E' | 'g' | 'G' => {
+                    let value: f64 = if arg.is_empty() {
+                        0.0
+                    } else {
+                        arg.parse().unwrap_or_else(|_| {
+                            errors.push(format!("printf: {}: expected numeric value", arg));
+                            0.0
+                        })
+                    };
+                    let precision = precision.unwrap_or(6);
+                    match conversion {
+                        'f' | 'F' => format!("{:.*}", precision, value),
+                        'e' => format!("{:.*e}", precision, value),
+                        'E' => format!("{:.*E}", precision, value),
+                        _ => value.to_string(),
+                    }
+                }
+                other => {
+                    errors.push(format!("printf: %{}: invalid directive", other));
+                    return (out, errors);
+                }
+            };
+            let fill = if zero && !matches!(conversion, 's' | 'c' | 'b') {
+                '0'
+            } else {
+                ' '
+            };
+            out.push_str(&pad(text, width, left, fill));
+        }
+        let (text, stop) = interpret_escapes(&literal, true);
+        out.push_str(&text);
+        // Reuse the format only if it consumed arguments and some are left
+        if stop || next == 0 || next >= args.len() {
+            break;
+        }
+    }
+    (out, errors)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// A word with its quoting intact; quotes are removed during expansion
+    Word(String),
+    Op(&'static str),
+    Newline,
+}
+
+/// Operators recognized by the tokenizer, longest first
+const OPERATORS: &[&str] = &[
+    "2>&1", "2>>", ">&2", "&&", "||", ">>", "2>", ";", "|", "&", ">", "<", "(", ")",
+];
+
+fn is_redirect(op: &str) -> bool {
+    matches!(op, ">" | ">>" | "<" | "2>" | "2>>" | "2>&1" | ">&2")
+}
+
+fn flush_word(tokens: &mut Vec<Token>, word: &mut String) {
+    if !word.is_empty() {
+        tokens.push(Token::Word(std::mem::take(word)));
+    }
+}
+
+/// Find the end (exclusive) of a quoted string starting at `start`
+fn scan_quoted(chars: &[char], start: usize) -> Result<usize, String> {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            c if c == quote => return Ok(i + 1),
+            '\\' if quote != '\'' => i += 2,
+            '$' if quote == '"' && matches!(chars.get(i + 1), Some('(') | Some('{')) => {
+                i = scan_group(chars, i + 1)?;
+            }
+            _ => i += 1,
+        }
+    }
+    Err(format!("unmatched {}", quote))
+}
+
+/// Find the end (exclusive) of a `(...)` or `{...}` group starting at `open`
+fn scan_group(chars: &[char], open: usize) -> Result<usize, String> {
+    let (open_char, close_char) = if chars[open] == '(' {
+        ('(', ')')
+    } else {
+        ('{', '}')
+    };
+    let mut depth = 0;
+    let mut i = open;
+    while i < chars.len() {
+        match chars[i] {
+            c if c == open_char => {
+                depth += 1;
+                i += 1;
+            }
+            c if c == close_char => {
+                depth -= 1;
+                i += 1;
+                if depth == 0 {
+                    return Ok(i);
+                }
+            }
+            '\'' | '"' | '`' => i = scan_quoted(chars, i)?,
+            '\\' => i += 2,
+            _ => i += 1,
+        }
+    }
+    Err(format!("unmatched {}", open_char))
+}
+
+/// Split source text into words and operators
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\r' => {
+                flush_word(&mut tokens, &mut word);
+                i += 1;
+            }
+            '\n' => {
+                flush_word(&mut tokens, &mut word);
+                tokens.push(Token::Newline);
+                i += 1;
+            }
+            '#' if word.is_empty() => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '\\' => {
+                // Backslash-newline continues the line
+                if chars.get(i + 1) != Some(&'\n') {
+                    word.extend(&chars[i..(i + 2).min(chars.len())]);
+                }
+                i += 2;
+            }
+            '\'' | '"' | '`' => {
+                let end = scan_quoted(&chars, i)?;
+                word.extend(&chars[i..end]);
+                i = end;
+            }
+            '$' if matches!(chars.get(i + 1), Some('(') | Some('{')) => {
+                let end = scan_group(&chars, i + 1)?;
+                word.extend(&chars[i..end]);
+                i = end;
+            }
+            _ => {
+                let rest: String = chars[i..(i + 4).min(chars.len())].iter().collect();
+                // `2>` only redirects stderr at the start of a word
+                let op = OPERATORS
+                    .iter()
+                    .find(|op| rest.starts_with(**op) && (word.is_empty() || !op.starts_with('2')));
+                match op {
+                    Some(op) => {
+                        flush_word(&mut tokens, &mut word);
+                        tokens.push(Token::Op(op));
+                        i += op.len();
+                    }
+                    None => {
+                        word.push(c);
+                        i += 1;
+                    }
+                }
+            }
+        }
+    }
+    flush_word(&mut tokens, &mut word);
+    Ok(tokens)
+}
+
+#[derive(Clone, Debug)]
+enum Redirect {
+    Output {
+        target: String,
+        append: bool,
+    },
+    Error {
+        target: String,
+        append: bool,
+    },
+    Input(String),
+    /// `2>&1`
+    ErrorToOutput,
+    /// `>&2`
+    OutputToError,
+}
+
+#[derive(Clone, Debug, Default)]
+struct SimpleCommand {
+    assignments: Vec<(String, String)>,
+    words: Vec<String>,
+    redirects: Vec<Redirect>,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Simple(SimpleCommand),
+    Pipeline {
+        stages: Vec<Node>,
+        negated: bool,
+    },
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    List(Vec<Node>),
+    If {
+        branches: Vec<(Node, Node)>,
+        otherwise: Option<Box<Node>>,
+    },
+    For {
+        var: String,
+        /// `None` iterates over the positional parameters
+        items: Option<Vec<String>>,
+        body: Box<Node>,
+    },
+    While {
+        condition: Box<Node>,
+        body: Box<Node>,
+        until: bool,
+    },
+    Group(Box<Node>),
+    Subshell(Box<Node>),
+    Function {
+        name: String,
+        body: Box<Node>,
+    },
+}
+
+impl SimpleCommand {
+    fn to_source(&self) -> String {
+        let mut parts: Vec<String> = self
+            .assignments
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        parts.extend(self.words.iter().cloned());
+        for redirect in &self.redirects {
+            parts.push(match redirect {
+                Redirect::Output { target, append } => {
+                    format!("{} {}", if *append { ">>" } else { ">" }, target)
+                }
+                Redirect::Error { target, append } => {
+                    format!("{} {}", if *append { "2>>" } else { "2>" }, target)
+                }
+                Redirect::Input(target) => format!("< {}", target),
+                Redirect::ErrorToOutput => "2>&1".to_string(),
+                Redirect::OutputToError => ">&2".to_string(),
+            });
+        }
+        parts.join(" ")
+    }
+}
+
+impl Node {
+    /// Source text for this node, formatted as `functions` prints it. The
+    /// first line is unindented; later lines are indented `depth` tabs.
+    fn to_source(&self, depth: usize) -> String {
+        let pad = "\t".repeat(depth);
+        let block = |node: &Node| format!("{}\t{}", pad, node.to_source(depth + 1));
+        match self {
+            Node::Simple(command) => command.to_source(),
+            Node::Pipeline { stages, negated } => {
+                let stages: Vec<String> = stages.iter().map(|s| s.to_source(depth)).collect();
+                format!("{}{}", if *negated { "! " } else { "" }, stages.join(" | "))
+            }
+            Node::And(left, right) => {
+                format!("{} && {}", left.to_source(depth), right.to_source(depth))
+            }
+            Node::Or(left, right) => {
+                format!("{} || {}", left.to_source(depth), right.to_source(depth))
+            }
+            Node::List(items) => items
+                .iter()
+                .map(|item| item.to_source(depth))
+                .collect::<Vec<_>>()
+                .join(&format!("\n{}", pad)),
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                let mut source = String::new();
+                for (index, (condition, body)) in branches.iter().enumerate() {
+                    if index > 0 {
+                        source.push_str(&format!("\n{}el", pad));
+                    }
+                    source.push_str(&format!(
+                        "if {}\n{}then\n{}",
+                        condition.to_source(depth),
+                        pad,
+                        block(body)
+                    ));
+                }
+                if let Some(otherwise) = otherwise {
+                    source.push_str(&format!("\n{}else\n{}", pad, block(otherwise)));
+                }
+                source.push_str(&format!("\n{}fi", pad));
+                source
+            }
+            Node::For { var, items, body } => {
+                let items = match items {
+                    Some(items) => format!(" in {}", items.join(" ")),
+                    None => String::new(),
+                };
+                format!(
+                    "for {}{}\n{}do\n{}\n{}done",
+                    var,
+                    items,
+                    pad,
+                    block(body),
+                    pad
+                )
+            }
+            Node::While {
+                condition,
+                body,
+                until,
+            } => format!(
+                "{} {}\n{}do\n{}\n{}done",
+                if *until { "until" } else { "while" },
+                condition.to_source(depth),
+                pad,
+                block(body),
+                pad
+            ),
+            Node::Group(body) => format!("{{\n{}\n{}}}", block(body), pad),
+            Node::Subshell(body) => format!("(\n{}\n{})", block(body), pad),
+            Node::Function { name, body } => {
+                format!("{} () {}", name, body.to_source(depth))
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    aliases: &'a BTreeMap<String, String>,
+}
+
+/// Parse source text into a command list, expanding `aliases` in command
+/// position
+fn parse(source: &str, aliases: &BTreeMap<String, String>) -> Result<Node, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        aliases,
+    };
+    let program = parser.parse_list(&[])?;
+    match parser.peek() {
+        None => Ok(program),
+        Some(_) => Err(parser.unexpected()),
+    }
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(w)) if w == keyword)
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.peek() == Some(&Token::Newline) {
+            self.pos += 1;
+        }
+    }
+
+    fn unexpected(&self) -> String {
+        match self.peek() {
+            Some(Token::Word(w)) => format!("parse error near `{}'", w),
+            Some(Token::Op(op)) => format!("parse error near `{}'", op),
+            Some(Token::Newline) => "parse error near `\\n'".to_string(),
+            None => "parse error: unexpected end of file".to_string(),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.is_keyword(keyword) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn at_terminator(&self, terminators: &[&str]) -> bool {
+        match self.peek() {
+            None => true,
+            Some(Token::Word(w)) => terminators.contains(&w.as_str()),
+            Some(Token::Op(")")) => terminators.contains(&")"),
+            _ => false,
+        }
+    }
+
+    /// Parse commands separated by `;`, `&` or newlines until a terminator
+    fn parse_list(&mut self, terminators: &[&str]) -> Result<Node, String> {
+        let mut items = Vec::new();
+        loop {
+            while matches!(self.peek(), Some(Token::Newline) | Some(Token::Op(";"))) {
+                self.pos += 1;
+            }
+            if self.at_terminator(terminators) {
+                break;
+            }
+            items.push(self.parse_and_or()?);
+            match self.peek() {
+                Some(Token::Newline) | Some(Token::Op(";")) | Some(Token::Op("&")) => {
+                    self.pos += 1;
+                }
+                _ if self.at_terminator(terminators) => {}
+                _ => return Err(self.unexpected()),
+            }
+        }
+        Ok(Node::List(items))
+    }
+
+    fn parse_and_or(&mut self) -> Result<Node, String> {
+        let mut left = self.parse_pipeline()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op(op @ ("&&" | "||"))) => *op,
+                _ => return Ok(left),
+            };
+            self.pos += 1;
+            self.skip_newlines();
+            let right = Box::new(self.parse_pipeline()?);
+            left = if op == "&&" {
+                Node::And(Box::new(left), right)
+            } else {
+                Node::Or(Box::new(left), right)
+            };
+        }
+    }
+
+    fn parse_pipeline(&mut self) -> Result<Node, String> {
+        let negated = self.is_keyword("!");
+        if negated {
+            self.pos += 1;
+        }
+        let mut stages = vec![self.parse_command()?];
+        while self.peek() == Some(&Token::Op("|")) {
+            self.pos += 1;
+            self.skip_newlines();
+            stages.push(self.parse_command()?);
+        }
+        if stages.len() == 1 && !negated {
+            return Ok(stages.remove(0));
+        }
+        Ok(Node::Pipeline { stages, negated })
+    }
+
+    /// Replace an alias in command position with its tokens. An alias is not
+    /// expanded again within its own expansion (`alias ls='ls -G'`).
+    fn expand_aliases(&mut self) -> Result<(), String> {
+        let mut expanded: Vec<String> = Vec::new();
+        while let Some(Token::Word(word)) = self.peek() {
+            let Some(value) = self.aliases.get(word) else {
+                break;
+            };
+            if expanded.contains(word) {
+                break;
+            }
+            expanded.push(word.clone());
+            let replacement = tokenize(value)?;
+            self.tokens.splice(self.pos..self.pos + 1, replacement);
+        }
+        Ok(())
+    }
+
+    fn parse_command(&mut self) -> Result<Node, String> {
+        self.expand_aliases()?;
+        match self.peek() {
+            Some(Token::Word(w)) => match w.as_str() {
+                "if" => self.parse_if(),
+                "for" => self.parse_for(),
+                "while" | "until" => self.parse_while(),
+                "{" => {
+                    self.pos += 1;
+                    let body = self.parse_list(&["}"])?;
+                    self.expect_keyword("}")?;
+                    Ok(Node::Group(Box::new(body)))
+                }
+                "function" => {
+                    self.pos += 1;
+                    let name = match self.next() {
+                        Some(Token::Word(name)) => name,
+                        _ => return Err(self.unexpected()),
+                    };
+                    if self.peek() == Some(&Token::Op("(")) {
+                        self.pos += 1;
+                        if self.next() != Some(Token::Op(")")) {
+                            return Err(self.unexpected());
+                        }
+                    }
+                    self.parse_function_body(name)
+                }
+                _ if self.tokens.get(self.pos + 1) == Some(&Token::Op("("))
+                    && self.tokens.get(self.pos + 2) == Some(&Token::Op(")")) =>
+                {
+                    let name = w.clone();
+                    self.pos += 3;
+                    self.parse_function_body(name)
+                }
+                _ => self.parse_simple(),
+            },
+            Some(Token::Op("(")) => {
+                self.pos += 1;
+                let body = self.parse_list(&[")"])?;
+                if self.next() != Some(Token::Op(")")) {
+                    return Err(self.unexpected());
+                }
+                Ok(Node::Subshell(Box::new(body)))
+            }
+            _ => self.parse_simple(),
+        }
+    }
+
+    fn parse_function_body(&mut self, name: String) -> Result<Node, String> {
+        if name.contains(['$', '"', '\'', '=']) {
+            return Err(format!("parse error near `{}'", name));
+        }
+        self.skip_newlines();
+        let body = Box::new(self.parse_command()?);
+        Ok(Node::Function { name, body })
+    }
+
+    fn parse_if(&mut self) -> Result<Node, String> {
+        self.expect_keyword("if")?;
+        let mut branches = Vec::new();
+        let mut otherwise = None;
+        loop {
+            let condition = self.parse_list(&["then"])?;
+            self.expect_keyword("then")?;
+            let body = self.parse_list(&["elif", "else", "fi"])?;
+            branches.push((condition, body));
+            if self.is_keyword("elif") {
+                self.pos += 1;
+                continue;
+            }
+            if self.is_keyword("else") {
+                self.pos += 1;
+                otherwise = Some(Box::new(self.parse_list(&["fi"])?));
+            }
+            self.expect_keyword("fi")?;
+            return Ok(Node::If {
+                branches,
+                otherwise,
+            });
+        }
+    }
+
+    fn parse_for(&mut self) -> Result<Node, String> {
+        self.expect_keyword("for")?;
+        let var = match self.next() {
+            Some(Token::Word(name)) if is_valid_identifier(&name) => name,
+            _ => {
+                self.pos -= 1;
+                return Err(self.unexpected());
+            }
+        };
+        self.skip_newlines();
+        let items = if self.is_keyword("in") {
+            self.pos += 1;
+            let mut items = Vec::new();
+            while let Some(Token::Word(word)) = self.peek() {
+                items.push(word.clone());
+                self.pos += 1;
+            }
+            Some(items)
+        } else {
+            None
+        };
+        while matches!(self.peek(), Some(Token::Newline) | Some(Token::Op(";"))) {
+            self.pos += 1;
+        }
+        self.expect_keyword("do")?;
+        let body = Box::new(self.parse_list(&["done"])?);
+        self.expect_keyword("done")?;
+        Ok(Node::For { var, items, body })
+    }
+
+    fn parse_while(&mut self) -> Result<Node, String> {
+        let until = self.is_keyword("until");
+        self.pos += 1;
+        let condition = Box::new(self.parse_list(&["do"])?);
+        self.expect_keyword("do")?;
+        let body = Box::new(self.parse_list(&["done"])?);
+        self.expect_keyword("done")?;
+        Ok(Node::While {
+            condition,
+            body,
+            until,
+        })
+    }
+
+    fn parse_simple(&mut self) -> Result<Node, String> {
+        let mut command = SimpleCommand::default();
+        loop {
+            match self.peek() {
+                Some(Token::Word(word)) => {
+                    let word = word.clone();
+                    self.pos += 1;
+                    match parse_assignment(&word) {
+                        Some((name, value)) if command.words.is_empty() => command
+                            .assignments
+                            .push((name.to_string(), value.to_string())),
+                        _ => command.words.push(word),
+                    }
+                }
+                Some(Token::Op(op)) if is_redirect(op) => {
+                    let op = *op;
+                    self.pos += 1;
+                    let redirect = match op {
+                        "2>&1" => Redirect::ErrorToOutput,
+                        ">&2" => Redirect::OutputToError,
+                        _ => {
+                            let target = match self.peek() {
+                                Some(Token::Word(target)) => target.clone(),
+                                _ => return Err(self.unexpected()),
+                            };
+                            self.pos += 1;
+                            match op {
+                                "<" => Redirect::Input(target),
+                                "2>" | "2>>" => Redirect::Error {
+                                    target,
+                                    append: op == "2>>",
+                                },
+                                _ => Redirect::Output {
+                                    target,
+                                    append: op == ">>",
+                                },
+                            }
+                        }
+                    };
+                    command.redirects.push(redirect);
+                }
+                _ => break,
+            }
+        }
+        if command.words.is_empty()
+            && command.assignments.is_empty()
+            && command.redirects.is_empty()
+        {
+            return Err(self.unexpected());
+        }
+        Ok(Node::Simple(command))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ArithToken {
+    Num(i64),
+    Op(&'static str),
+}
+
+/// Arithmetic operators, longest first
+const ARITH_OPERATORS: &[&str] = &[
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "!", "(", ")",
+];
+
+fn arith_precedence(op: &str) -> Option<u8> {
+    match op {
+        "||" => Some(1),
+        "&&" => Some(2),
+        "==" | "!=" => Some(3),
+        "<" | "<=" | ">" | ">=" => Some(4),
+        "+" | "-" => Some(5),
+        "*" | "/" | "%" => Some(6),
+        _ => None,
+    }
+}
+
+/// Evaluate a `$(( ... ))` expression with integer semantics
+fn eval_arithmetic(expr: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<i64, String> {
+    let bad = || format!("bad math expression: {}", expr.trim());
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            let digits: String = chars[start..i].iter().collect();
+            tokens.push(ArithToken::Num(digits.parse().map_err(|_| bad())?));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            let value = lookup(&name).unwrap_or_default();
+            let value = value.trim();
+            tokens.push(ArithToken::Num(if value.is_empty() {
+                0
+            } else {
+                value.parse().map_err(|_| bad())?
+            }));
+        } else {
+            let rest: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+            let op = ARITH_OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(bad)?;
+            tokens.push(ArithToken::Op(op));
+            i += op.len();
+        }
+    }
+
+    let mut pos = 0;
+    let value = parse_arith_binary(&tokens, &mut pos, 0).ok_or_else(bad)??;
+    if pos != tokens.len() {
+        return Err(bad());
+    }
+    Ok(value)
+}
+
+/// Precedence-climbing binary expression parser. Returns `None` on a syntax
+/// error and `Some(Err)` on an evaluation error (division by zero).
+fn parse_arith_binary(
+    tokens: &[ArithToken],
+    pos: &mut usize,
+    min_precedence: u8,
+) -> Option<Result<i64, String>> {
+    let mut lhs = match parse_arith_unary(tokens, pos)? {
+        Ok(value) => value,
+        Err(e) => return Some(Err(e)),
+    };
+    while let Some(ArithToken::Op(op)) = tokens.get(*pos) {
+        let precedence = match arith_precedence(op) {
+            Some(p) if p >= min_precedence => p,
+            _ => break,
+        };
+        *pos += 1;
+        let rhs = match parse_arith_binary(tokens, pos, precedence + 1)? {
+            Ok(value) => value,
+            Err(e) => return Some(Err(e)),
+        };
+        lhs = match *op {
+            "||" => ((lhs != 0) || (rhs != 0)) as i64,
+            "&&" => ((lhs != 0) && (rhs != 0)) as i64,
+            "==" => (lhs == rhs) as i64,
+            "!=" => (lhs != rhs) as i64,
+            "<" => (lhs < rhs) as i64,
+            "<=" => (lhs <= rhs) as i64,
+            ">" => (lhs > rhs) as i64,
+            ">=" => (lhs >= rhs) as i64,
+            "+" => lhs.wrapping_add(rhs),
+            "-" => lhs.wrapping_sub(rhs),
+            "*" => lhs.wrapping_mul(rhs),
+            "/" | "%" if rhs == 0 => return Some(Err("division by zero".to_string())),
+            "/" => lhs.wrapping_div(rhs),
+            _ => lhs.wrapping_rem(rhs),
+        };
+    }
+    Some(Ok(lhs))
+}
+
+fn parse_arith_unary(tokens: &[ArithToken], pos: &mut usize) -> Option<Result<i64, String>> {
+    let token = tokens.get(*pos)?.clone();
+    *pos += 1;
+    match token {
+        ArithToken::Num(n) => Some(Ok(n)),
+        ArithToken::Op("-") => Some(parse_arith_unary(tokens, pos)?.map(i64::wrapping_neg)),
+        ArithToken::Op("+") => parse_arith_unary(tokens, pos),
+        ArithToken::Op("!") => Some(parse_arith_unary(tokens, pos)?.map(|v| (v == 0) as i64)),
+        ArithToken::Op("(") => {
+            let value = parse_arith_binary(tokens, pos, 0)?;
+            if tokens.get(*pos) != Some(&ArithToken::Op(")")) {
+                return None;
+            }
+            *pos += 1;
+            Some(value)
+        }
+        _ => None,
+    }
+}
+
+/// Side effects a command asks the Terminal UI to perform
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShellEffect {
+    /// Clear the scrollback (`clear`)
+    ClearScreen,
+    /// Open the full-screen editor (`nano`) on a file, if one was named
+    Edit {
+        name: Option<String>,
+        content: Option<String>,
+    },
+    /// The shell itself exited (`exit`, or a failure under `set -e`)
+    Exit,
+    /// Launch an app, optionally on a document or folder (`open`)
+    Open { app: AppType, path: Option<String> },
+    /// Show text in the full-screen pager (`man`, `less`)
+    Page {
+        text: String,
+        prompt: PagerPrompt,
+        /// Whether searches ignore case (`less -i`)
+        ignore_case: bool,
+    },
+}
+
+/// What the pager's status line describes
+#[derive(Clone, Debug, PartialEq)]
+pub enum PagerPrompt {
+    /// A manual page (e.g. "Manual page ls(1)"), shown with the current line
+    Manual(String),
+    /// The file being paged, shown on the first screen; `None` for standard
+    /// input
+    File(Option<String>),
+}
+
+/// Aliases and functions defined in a session, as source text so they can
+/// be persisted and shared between Terminal tabs
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShellDefinitions {
+    pub aliases: BTreeMap<String, String>,
+    /// Function name to its definition (`name () { ... }`)
+    pub functions: BTreeMap<String, String>,
+}
+
+/// Everything a command line produced, for the Terminal to render
+pub struct ShellOutput {
+    pub text: String,
+    pub effects: Vec<ShellEffect>,
+}
+
+/// Non-local control flow unwinding through the interpreter
+enum Flow {
+    Break(usize),
+    Continue(usize),
+    Return(i32),
+    Exit(i32),
+    /// Abandon the command line without exiting the shell
+    Abort(i32),
+}
+
+type ExecResult = Result<i32, Flow>;
+
+/// A field being built during word expansion. `pattern` mirrors `text` with
+/// quoted characters backslash-escaped, so only unquoted `*?[` glob.
+#[derive(Default)]
+struct Field {
+    text: String,
+    pattern: String,
+    glob: bool,
+    /// Whether the field exists even if empty (quotes or literal text)
+    has_word: bool,
+}
+
+impl Field {
+    fn literal(text: &str) -> Self {
+        let mut field = Self::default();
+        field.push_quoted(text);
+        field
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.has_word && self.text.is_empty()
+    }
+
+    fn push_quoted_char(&mut self, c: char) {
+        self.has_word = true;
+        self.text.push(c);
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            self.pattern.push('\\');
+        }
+        self.pattern.push(c);
+    }
+
+    fn push_quoted(&mut self, text: &str) {
+        self.has_word = true;
+        for c in text.chars() {
+            self.push_quoted_char(c);
+        }
+    }
+
+    fn push_unquoted(&mut self, c: char) {
+        self.has_word = true;
+        self.text.push(c);
+        self.pattern.push(c);
+        self.glob |= matches!(c, '*' | '?' | '[');
+    }
+}
+
+/// Where stderr currently goes
+enum ErrorSink {
+    Terminal,
+    /// Merged into whatever stdout currently is (`2>&1`)
+    Stdout,
+    Capture(String),
+}
+
+/// Where a redirected stdout/stderr ends up once the command finishes
+enum OutputTarget {
+    File { path: String, append: bool },
+    Discard,
+    Stderr,
+}
+
+/// Shell state saved and restored around a subshell
+struct SubshellState {
+    env: BTreeMap<String, String>,
+    cwd: String,
+    functions: HashMap<String, Node>,
+    positional: Vec<String>,
+    script_name: String,
+    errexit: bool,
+    xtrace: bool,
+}
+
+/// Interpreter state for a single terminal session
+pub struct Shell {
+    fs: VirtualFileSystem,
+    notifications: NotificationState,
+    processes: ProcessTable,
+    clipboard: Clipboard,
+    preferences: Preferences,
+    env: BTreeMap<String, String>,
+    cwd: String,
+    functions: HashMap<String, Node>,
+    /// `$1`, `$2`, ...
+    positional: Vec<String>,
+    /// `$0`
+    script_name: String,
+    last_status: i32,
+    /// Exit status of the most recent command substitution
+    substitution_status: Option<i32>,
+    /// `set -e`
+    errexit: bool,
+    /// `set -x`
+    xtrace: bool,
+    /// Nesting of `if`/`while` conditions and `&&`/`||` operands, where
+    /// `set -e` does not apply
+    condition_depth: usize,
+    call_depth: usize,
+    loop_depth: usize,
+    /// Saved values for `local` variables, one frame per function call
+    local_frames: Vec<Vec<(String, Option<String>)>>,
+    stdin: Option<String>,
+    /// Capture stack for stdout; the bottom buffer is the terminal itself
+    stdout: Vec<String>,
+    stderr: Vec<ErrorSink>,
+    effects: Vec<ShellEffect>,
+    /// Lines typed at the prompt, oldest first, for the `history` builtin
+    history: Vec<String>,
+    aliases: BTreeMap<String, String>,
+}
+
+impl Shell {
+    pub fn new(
+        fs: VirtualFileSystem,
+        notifications: NotificationState,
+        processes: ProcessTable,
+        clipboard: Clipboard,
+        preferences: Preferences,
+        env: BTreeMap<String, String>,
+        cwd: String,
+    ) -> Self {
+        Self {
+            fs,
+            notifications,
+            processes,
+            clipboard,
+            preferences,
+            env,
+            cwd,
+            functions: HashMap::new(),
+            positional: Vec::new(),
+            script_name: "zsh".to_string(),
+            last_status: 0,
+            substitution_status: None,
+            errexit: false,
+            xtrace: false,
+            condition_depth: 0,
+            call_depth: 0,
+            loop_depth: 0,
+            local_frames: Vec::new(),
+            stdin: None,
+            stdout: vec![String::new()],
+            stderr: vec![ErrorSink::Terminal],
+            effects: Vec::new(),
+            history: Vec::new(),
+            aliases: BTreeMap::new(),
+        }
+    }
+
+    pub fn env(&self) -> &BTreeMap<String, String> {
+        &self.env
+    }
+
+    pub fn cwd(&self) -> &str {
+        &self.cwd
+    }
+
+    /// Replace the command history shown by `history`
+    pub fn set_history(&mut self, history: Vec<String>) {
+        self.history = history;
+    }
+
+    pub fn definitions(&self) -> ShellDefinitions {
+        ShellDefinitions {
+            aliases: self.aliases.clone(),
+            functions: self
+                .functions
+                .keys()
+                .filter_map(|name| Some((name.clone(), self.function_source(name)?)))
+                .collect(),
+        }
+    }
+
+    /// Replace all aliases and functions, e.g. with those defined in another tab
+    pub fn set_definitions(&mut self, definitions: &ShellDefinitions) {
+        self.aliases = definitions.aliases.clone();
+        self.functions.clear();
+        for source in definitions.functions.values() {
+            // Aliases were already expanded when the function was defined
+            if let Ok(Node::List(items)) = parse(source, &BTreeMap::new()) {
+                for item in items {
+                    if let Node::Function { name, body } = item {
+                        self.functions.insert(name, *body);
+                    }
+                }
+            }
+        }
+    }
+
+    fn function_source(&self, name: &str) -> Option<String> {
+        let body = self.functions.get(name)?;
+        Some(
+            Node::Function {
+                name: name.to_string(),
+                body: Box::new(body.clone()),
+            }
+            .to_source(0),
+        )
+    }
+
+    /// Run a line typed at the prompt
+    pub fn execute(&mut self, source: &str) -> ShellOutput {
+        match parse(source, &self.aliases) {
+            Ok(program) => match self.exec(&program) {
+                Ok(_) | Err(Flow::Break(_)) | Err(Flow::Continue(_)) => {}
+                Err(Flow::Return(status)) | Err(Flow::Abort(status)) => self.last_status = status,
+                Err(Flow::Exit(status)) => {
+                    self.last_status = status;
+                    self.effects.push(ShellEffect::Exit);
+                }
+            },
+            Err(message) => {
+                self.eprintln(&format!("zsh: {}", message));
+                self.last_status = 1;
+            }
+        }
+        self.take_output()
+    }
+
+    /// Source `~/.zshrc` if it exists, as a login shell does at startup
+    pub fn source_startup_files(&mut self) -> ShellOutput {
+        let rc_path = self.resolve_path(&format!(
+            "{}/.zshrc",
+            self.var("HOME").unwrap_or_default().trim_end_matches('/')
+        ));
+        if self.fs.get(&rc_path).is_some_and(|e| e.is_file()) {
+            let _ = self.source_file(&rc_path, &[]);
+        }
+        self.take_output()
+    }
+
+    fn take_output(&mut self) -> ShellOutput {
+        self.stdout.truncate(1);
+        self.stderr.truncate(1);
+        ShellOutput {
+            text: std::mem::take(&mut self.stdout[0]),
+            effects: std::mem::take(&mut self.effects),
+        }
+    }
+
+    fn print(&mut self, text: &str) {
+        if let Some(buffer) = self.stdout.last_mut() {
+            buffer.push_str(text);
+        }
+    }
+
+    /// Whether output currently goes straight to the Terminal rather than a
+    /// pipe, file or command substitution
+    fn stdout_is_terminal(&self) -> bool {
+        self.stdout.len() == 1
+    }
+
+    fn println(&mut self, text: &str) {
+        self.print(text);
+        self.print("\n");
+    }
+
+    fn eprint(&mut self, text: &str) {
+        match self.stderr.last_mut() {
+            Some(ErrorSink::Capture(buffer)) => buffer.push_str(text),
+            Some(ErrorSink::Stdout) => {
+                if let Some(buffer) = self.stdout.last_mut() {
+                    buffer.push_str(text);
+                }
+            }
+            _ => self.stdout[0].push_str(text),
+        }
+    }
+
+    fn eprintln(&mut self, text: &str) {
+        self.eprint(text);
+        self.eprint("\n");
+    }
+
+    fn var(&self, name: &str) -> Option<String> {
+        self.env.get(name).cloned()
+    }
+
+    fn set_var(&mut self, name: &str, value: String) {
+        self.env.insert(name.to_string(), value);
+    }
+
+    /// Look up a parameter, including the special ones (`$?`, `$#`, `$1`, ...)
+    fn parameter(&self, name: &str) -> Option<String> {
+        match name {
+            "?" => Some(self.last_status.to_string()),
+            "#" => Some(self.positional.len().to_string()),
+            "@" | "*" => Some(self.positional.join(" ")),
+            "$" => Some(SHELL_PID.to_string()),
+            "0" => Some(self.script_name.clone()),
+            _ if name.chars().all(|c| c.is_ascii_digit()) => name
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| self.positional.get(n.checked_sub(1)?).cloned()),
+            _ => self.var(name),
+        }
+    }
+
+    /// Resolve a path relative to the working directory
+    pub fn resolve_path(&self, target: &str) -> String {
+        let home = self.var("HOME").unwrap_or_else(|| "/".to_string());
+        resolve_path(&self.cwd, &home, target)
+    }
+
+    /// Change directory, tracking PWD/OLDPWD
+    fn change_directory(&mut self, new_path: String) {
+        let old_path = std::mem::replace(&mut self.cwd, new_path.clone());
+        self.set_var("OLDPWD", old_path);
+        self.set_var("PWD", new_path);
+    }
+
+    /// Expand a word into fields: tilde, parameters, command substitution,
+    /// arithmetic and quote removal, with sh-style splitting of unquoted
+    /// expansion results, then glob expansion against the file system.
+    fn expand_word(&mut self, word: &str) -> Result<Vec<String>, String> {
+        let mut words = Vec::new();
+        for field in self.expand(word, true)? {
+            if !field.glob || !has_glob_chars(&field.pattern) {
+                words.push(field.text);
+                continue;
+            }
+            let matches = self.glob(&field.pattern);
+            if matches.is_empty() {
+                return Err(format!("no matches found: {}", field.text));
+            }
+            words.extend(matches);
+        }
+        Ok(words)
+    }
+
+    /// Expand a word without field splitting or globbing (assignments,
+    /// redirect targets)
+    fn expand_single(&mut self, word: &str) -> Result<String, String> {
+        let fields: Vec<String> = self
+            .expand(word, false)?
+            .into_iter()
+            .map(|field| field.text)
+            .collect();
+        Ok(fields.join(" "))
+    }
+
+    fn expand(&mut self, word: &str, split: bool) -> Result<Vec<Field>, String> {
+        if word == "\"$@\"" {
+            return Ok(self.positional.iter().map(|p| Field::literal(p)).collect());
+        }
+
+        let chars: Vec<char> = word.chars().collect();
+        let mut fields = Vec::new();
+        let mut current = Field::default();
+        let mut i = 0;
+
+        // Leading unquoted `~` expands to $HOME
+        if chars.first() == Some(&'~') && matches!(chars.get(1), None | Some('/')) {
+            let home = self.var("HOME").unwrap_or_else(|| "/".to_string());
+            let home = if chars.len() > 1 {
+                home.trim_end_matches('/')
+            } else {
+                &home
+            };
+            current.push_quoted(home);
+            i = 1;
+        }
+
+        while i < chars.len() {
+            match chars[i] {
+                '\'' => {
+                    current.has_word = true;
+                    i += 1;
+                    while i < chars.len() && chars[i] != '\'' {
+                        current.push_quoted_char(chars[i]);
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                '"' => {
+                    current.has_word = true;
+                    i += 1;
+                    while i < chars.len() && chars[i] != '"' {
+                        match chars[i] {
+                            '\\' => match chars.get(i + 1) {
+                                Some(&next) if matches!(next, '"' | '\\' | '$' | '`') => {
+                                    current.push_quoted_char(next);
+                                    i += 2;
+                                }
+                                _ => {
+                                    current.push_quoted_char('\\');
+                                    i += 1;
+                                }
+                            },
+                            '$' => {
+                                let (value, next) = self.expand_dollar(&chars, i)?;
+                                current.push_quoted(&value);
+                                i = next;
+                            }
+                            '`' => {
+                                let (value, next) = self.expand_backtick(&chars, i)?;
+                                current.push_quoted(&value);
+                                i = next;
+                            }
+                            c => {
+                                current.push_quoted_char(c);
+                                i += 1;
+                            }
+                        }
+                    }
+                    i += 1;
+                }
+                '\\' => {
+                    current.has_word = true;
+                    if let Some(&next) = chars.get(i + 1) {
+                        current.push_quoted_char(next);
+                    }
+                    i += 2;
+                }
+                '$' | '`' => {
+                    let (value, next) = if chars[i] == '$' {
+                        self.expand_dollar(&chars, i)?
+                    } else {
+                        self.expand_backtick(&chars, i)?
+                    };
+                    i = next;
+                    if !split {
+                        current.push_quoted(&value);
+                        continue;
+                    }
+                    // Split unquoted results on whitespace
+                    if value.starts_with(char::is_whitespace) && !current.is_empty() {
+                        fields.push(std::mem::take(&mut current));
+                    }
+                    let pieces: Vec<&str> = value.split_whitespace().collect();
+                    for (n, piece) in pieces.iter().enumerate() {
+                        if n > 0 {
+                            fields.push(std::mem::take(&mut current));
+                        }
+                        current.push_quoted(piece);
+                    }
+                    if !pieces.is_empty() && value.ends_with(char::is_whitespace) {
+                        fields.push(std::mem::take(&mut current));
+                    }
+                }
+                c => {
+                    current.push_unquoted(c);
+                    i += 1;
+                }
+            }
+        }
+        if !current.is_empty() {
+            fields.push(current);
+        }
+        Ok(fields)
+    }
+
+    /// Expand a glob pattern (with `\\`-escaped literals) to the sorted paths
+    /// it matches. Relative patterns yield relative paths.
+    fn glob(&self, pattern: &str) -> Vec<String> {
+        let dirs_only = pattern.ends_with('/');
+        let segments: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+        // (path as displayed, absolute path)
+        let mut candidates = vec![if pattern.starts_with('/') {
+            ("/".to_string(), "/".to_string())
+        } else {
+            (String::new(), self.cwd.clone())
+        }];
+
+        for segment in segments {
+            let mut next = Vec::new();
+            for (display, dir) in &candidates {
+                let join = |name: &str| {
+                    if display.is_empty() || display.ends_with('/') {
+                        format!("{}{}", display, name)
+                    } else {
+                        format!("{}/{}", display, name)
+                    }
+                };
+                if !has_glob_chars(segment) {
+                    let name = unescape_pattern(segment);
+                    let path = resolve_path(dir, "/", &name);
+                    if self.fs.exists(&path) {
+                        next.push((join(&name), path));
+                    }
+                    continue;
+                }
+                let pattern: Vec<char> = segment.chars().collect();
+                let mut names: Vec<String> = self
+                    .fs
+                    .list_dir(dir)
+                    .into_iter()
+                    .map(|entry| entry.metadata.name)
+                    // Hidden files only match a pattern that starts with `.`
+                    .filter(|name| !name.starts_with('.') || segment.starts_with('.'))
+                    .filter(|name| glob_match(&pattern, &name.chars().collect::<Vec<_>>()))
+                    .collect();
+                names.sort();
+                for name in names {
+                    let path = resolve_path(dir, "/", &name);
+                    next.push((join(&name), path));
+                }
+            }
+            candidates = next;
+        }
+
+        candidates
+            .into_iter()
+            .filter(|(_, path)| !dirs_only || self.fs.get(path).is_some_and(|e| e.is_directory()))
+            .map(|(display, _)| {
+                if dirs_only {
+                    format!("{}/", display)
+                } else {
+                    display
+                }
+            })
+            .collect()
+    }
+
+    /// Expand the `$...` construct at `chars[start]`, returning its value and
+    /// the index just past it
+    fn expand_dollar(&mut self, chars: &[char], start: usize) -> Result<(String, usize), String> {
+        let collect = |from: usize, to: usize| chars[from..to].iter().collect::<String>();
+        match chars.get(start + 1) {
+            Some('(') if chars.get(start + 2) == Some(&'(') => {
+                let end = scan_group(chars, start + 1)?;
+                let expr = self.expand_single(&collect(start + 3, end.saturating_sub(2)))?;
+                let value = eval_arithmetic(&expr, &|name| self.parameter(name))?;
+                Ok((value.to_string(), end))
+            }
+            Some('(') => {
+                let end = scan_group(chars, start + 1)?;
+                Ok((self.command_substitution(&collect(start + 2, end - 1)), end))
+            }
+            Some('{') => {
+                let end = scan_group(chars, start + 1)?;
+                Ok((self.expand_braced(&collect(start + 2, end - 1))?, end))
+            }
+            Some(c) if c.is_ascii_alphabetic() || *c == '_' => {
+                let mut end = start + 1;
+                while end < chars.len() && (chars[end].is_ascii_alphanumeric() || chars[end] == '_')
+                {
+                    end += 1;
+                }
+                let name = collect(start + 1, end);
+                Ok((self.parameter(&name).unwrap_or_default(), end))
+            }
+            Some(&c) if c.is_ascii_digit() || matches!(c, '?' | '#' | '@' | '*' | '$') => Ok((
+                self.parameter(&c.to_string()).unwrap_or_default(),
+                start + 2,
+            )),
+            // A lone `$` is literal
+            _ => Ok(("$".to_string(), start + 1)),
+        }
+    }
+
+    fn expand_backtick(&mut self, chars: &[char], start: usize) -> Result<(String, usize), String> {
+        let end = scan_quoted(chars, start)?;
+        let source: String = chars[start + 1..end - 1].iter().collect();
+        Ok((self.command_substitution(&source), end))
+    }
+
+    /// Expand `${...}`: `${NAME}`, `${#NAME}`, `${NAME:-word}`, `${NAME:=word}`
+    /// and `${NAME:+word}` (the colon also treats empty as unset)
+    fn expand_braced(&mut self, inner: &str) -> Result<String, String> {
+        if let Some(name) = inner.strip_prefix('#') {
+            if !name.is_empty() {
+                let value = self.parameter(name).unwrap_or_default();
+                return Ok(value.chars().count().to_string());
+            }
+        }
+        let name_len = if inner.starts_with(|c: char| c.is_ascii_digit()) {
+            inner.chars().take_while(char::is_ascii_digit).count()
+        } else if inner.starts_with(['?', '#', '@', '*', '$']) {
+            1
+        } else {
+            inner
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .count()
+        };
+        let (name, rest) = inner.split_at(name_len);
+        if name.is_empty() {
+            return Err(format!("bad substitution: ${{{}}}", inner));
+        }
+        let value = self.parameter(name);
+        let (check_empty, rest) = match rest.strip_prefix(':') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        let is_set = match &value {
+            Some(v) => !(check_empty && v.is_empty()),
+            None => false,
+        };
+        let mut operator = rest.chars();
+        match operator.next() {
+            None if !check_empty => Ok(value.unwrap_or_default()),
+            Some('-') if is_set => Ok(value.unwrap_or_default()),
+            Some('-') => self.expand_single(operator.as_str()),
+            Some('=') if is_set => Ok(value.unwrap_or_default()),
+            Some('=') => {
+                let default = self.expand_single(operator.as_str())?;
+                self.set_var(name, default.clone());
+                Ok(default)
+            }
+            Some('+') if is_set => self.expand_single(operator.as_str()),
+            Some('+') => Ok(String::new()),
+            _ => Err(format!("bad substitution: ${{{}}}", inner)),
+        }
+    }
+
+    /// Run `source` in a subshell and return its output, minus trailing newlines
+    fn command_substitution(&mut self, source: &str) -> String {
+        self.stdout.push(String::new());
+        let saved_stdin = self.stdin.take();
+        let status = match parse(source, &self.aliases) {
+            Ok(program) => self.subshell(|sh| sh.exec(&program)),
+            Err(message) => {
+                self.eprintln(&format!("zsh: {}", message));
+                1
+            }
+        };
+        self.stdin = saved_stdin;
+        self.last_status = status;
+        self.substitution_status = Some(status);
+        let output = self.stdout.pop().unwrap_or_default();
+        output.trim_end_matches('\n').to_string()
+    }
+
+    /// Record a command's exit status, aborting under `set -e` on failure
+    fn finish(&mut self, status: i32) -> ExecResult {
+        self.last_status = status;
+        if status != 0 && self.errexit && self.condition_depth == 0 {
+            return Err(Flow::Exit(status));
+        }
+        Ok(status)
+    }
+
+    /// Run `f` in a context where `set -e` is suspended
+    fn condition(&mut self, f: impl FnOnce(&mut Self) -> ExecResult) -> ExecResult {
+        self.condition_depth += 1;
+        let result = f(self);
+        self.condition_depth -= 1;
+        result
+    }
+
+    /// Run `f` with a copy of the shell state, discarding any changes
+    fn subshell(&mut self, f: impl FnOnce(&mut Self) -> ExecResult) -> i32 {
+        let saved = SubshellState {
+            env: self.env.clone(),
+            cwd: self.cwd.clone(),
+            functions: self.functions.clone(),
+            positional: self.positional.clone(),
+            script_name: self.script_name.clone(),
+            errexit: self.errexit,
+            xtrace: self.xtrace,
+        };
+        let saved_depths = (self.condition_depth, self.loop_depth);
+        self.condition_depth = 0;
+        self.loop_depth = 0;
+        let result = f(self);
+        (self.condition_depth, self.loop_depth) = saved_depths;
+        self.env = saved.env;
+        self.cwd = saved.cwd;
+        self.functions = saved.functions;
+        self.positional = saved.positional;
+        self.script_name = saved.script_name;
+        self.errexit = saved.errexit;
+        self.xtrace = saved.xtrace;
+        match result {
+            Ok(status)
+            | Err(Flow::Return(status))
+            | Err(Flow::Exit(status))
+            | Err(Flow::Abort(status)) => status,
+            Err(Flow::Break(_)) | Err(Flow::Continue(_)) => self.last_status,
+        }
+    }
+
+    fn exec(&mut self, node: &Node) -> ExecResult {
+        match node {
+            Node::Simple(command) => self.exec_simple(command),
+            Node::Pipeline { stages, negated } => {
+                let status = self.condition(|sh| sh.exec_pipeline(stages))?;
+                if *negated {
+                    let status = (status == 0) as i32;
+                    self.last_status = status;
+                    Ok(status)
+                } else {
+                    self.finish(status)
+                }
+            }
+            Node::And(left, right) => {
+                let status = self.condition(|sh| sh.exec(left))?;
+                if status == 0 {
+                    self.exec(right)
+                } else {
+                    Ok(status)
+                }
+            }
+            Node::Or(left, right) => {
+                let status = self.condition(|sh| sh.exec(left))?;
+                if status != 0 {
+                    self.exec(right)
+                } else {
+                    Ok(status)
+                }
+            }
+            Node::List(items) => {
+                let mut status = 0;
+                for item in items {
+                    status = self.exec(item)?;
+                }
+                Ok(status)
+            }
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                for (condition, body) in branches {
+                    if self.condition(|sh| sh.exec(condition))? == 0 {
+                        return self.exec(body);
+                    }
+                }
+                match otherwise {
+                    Some(body) => self.exec(body),
+                    None => {
+                        self.last_status = 0;
+                        Ok(0)
+                    }
+                }
+            }
+            Node::For { var, items, body } => {
+                let values = match items {
+                    Some(words) => {
+                        let mut values = Vec::new();
+                        for word in words {
+                            match self.expand_word(word) {
+                                Ok(fields) => values.extend(fields),
+                                Err(message) => {
+                                    self.eprintln(&format!("zsh: {}", message));
+                                    return self.finish(1);
+                                }
+                            }
+                        }
+                        values
+                    }
+                    None => self.positional.clone(),
+                };
+                self.loop_depth += 1;
+                let result = self.exec_for(var, values, body);
+                self.loop_depth -= 1;
+                result
+            }
+            Node::While {
+                condition,
+                body,
+                until,
+            } => {
+                self.loop_depth += 1;
+                let result = self.exec_while(condition, body, *until);
+                self.loop_depth -= 1;
+                result
+            }
+            Node::Group(body) => self.exec(body),
+            Node::Subshell(body) => {
+                let status = self.subshell(|sh| sh.exec(body));
+                self.finish(status)
+            }
+            Node::Function { name, body } => {
+                self.functions.insert(name.clone(), (**body).clone());
+                self.last_status = 0;
+                Ok(0)
+            }
+        }
+    }
+
+    /// Run one loop iteration, returning whether the loop should continue
+    fn exec_loop_body(&mut self, body: &Node, status: &mut i32) -> Result<bool, Flow> {
+        match self.exec(body) {
+            Ok(s) => {
+                *status = s;
+                Ok(true)
+            }
+            Err(Flow::Break(n)) if n > 1 => Err(Flow::Break(n - 1)),
+            Err(Flow::Break(_)) => Ok(false),
+            Err(Flow::Continue(n)) if n > 1 => Err(Flow::Continue(n - 1)),
+            Err(Flow::Continue(_)) => Ok(true),
+            Err(flow) => Err(flow),
+        }
+    }
+
+    fn exec_for(&mut self, var: &str, values: Vec<String>, body: &Node) -> ExecResult {
+        let mut status = 0;
+        for value in values {
+            self.set_var(var, value);
+            if !self.exec_loop_body(body, &mut status)? {
+                break;
+            }
+        }
+        self.last_status = status;
+        Ok(status)
+    }
+
+    fn exec_while(&mut self, condition: &Node, body: &Node, until: bool) -> ExecResult {
+        let mut status = 0;
+        let mut iterations = 0;
+        loop {
+            let passed = self.condition(|sh| sh.exec(condition))? == 0;
+            if passed == until {
+                break;
+            }
+            iterations += 1;
+            if iterations > MAX_LOOP_ITERATIONS {
+                self.eprintln("zsh: loop iteration limit exceeded");
+                return Err(Flow::Abort(1));
+            }
+            if !self.exec_loop_body(body, &mut status)? {
+                break;
+            }
+        }
+        self.last_status = status;
+        Ok(status)
+    }
+
+    fn exec_pipeline(&mut self, stages: &[Node]) -> ExecResult {
+        let saved_stdin = self.stdin.take();
+        let mut input = saved_stdin.clone();
+        let mut status = 0;
+        for (index, stage) in stages.iter().enumerate() {
+            self.stdin = input.take();
+            // Every stage but the last runs in a subshell with captured output
+            if index + 1 < stages.len() {
+                self.stdout.push(String::new());
+                status = self.subshell(|sh| sh.exec(stage));
+                input = self.stdout.pop();
+            } else {
+                match self.exec(stage) {
+                    Ok(s) => status = s,
+                    Err(flow) => {
+                        self.stdin = saved_stdin;
+                        return Err(flow);
+                    }
+                }
+            }
+        }
+        self.stdin = saved_stdin;
+        Ok(status)
+    }
+
+    /// Evaluate a redirect target into where the output should go
+    fn output_target(&mut self, target: &str, append: bool) -> Result<OutputTarget, String> {
+        let expanded = self.expand_single(target)?;
+        if expanded == "/dev/null" {
+            return Ok(OutputTarget::Discard);
+        }
+        let path = self.resolve_path(&expanded);
+        match self.fs.get(&path) {
+            Some(entry) if entry.is_directory() => Err(format!("is a directory: {}", expanded)),
+            Some(_) => Ok(OutputTarget::File { path, append }),
+            None => {
+                let parent = match path.rfind('/') {
+                    Some(0) | None => "/".to_string(),
+                    Some(idx) => path[..idx].to_string(),
+                };
+                if self.fs.get(&parent).is_some_and(|e| e.is_directory()) {
+                    Ok(OutputTarget::File { path, append })
+                } else {
+                    Err(format!("no such file or directory: {}", expanded))
+                }
+            }
+        }
+    }
+
+    fn write_output(&mut self, target: OutputTarget, text: String) {
+        match target {
+            OutputTarget::File { path, append } => {
+                let content = if append {
+                    self.fs.read_file(&path).unwrap_or_default() + &text
+                } else {
+                    text
+                };
+                self.fs.write_file(&path, &content, "📄");
+            }
+            OutputTarget::Discard => {}
+            OutputTarget::Stderr => self.eprint(&text),
+        }
+    }
+
+    fn exec_simple(&mut self, command: &SimpleCommand) -> ExecResult {
+        self.substitution_status = None;
+        let mut words = Vec::new();
+        for word in &command.words {
+            match self.expand_word(word) {
+                Ok(fields) => words.extend(fields),
+                Err(message) => {
+                    self.eprintln(&format!("zsh: {}", message));
+                    return self.finish(1);
+                }
+            }
+        }
+        let mut assignments = Vec::new();
+        for (name, value) in &command.assignments {
+            match self.expand_single(value) {
+                Ok(value) => assignments.push((name.clone(), value)),
+                Err(message) => {
+                    self.eprintln(&format!("zsh: {}", message));
+                    return self.finish(1);
+                }
+            }
+        }
+
+        // Resolve redirections before running anything
+        let mut stdout_target = None;
+        let mut stderr_target = None;
+        let mut stderr_to_stdout = false;
+        let mut stdin_override = None;
+        for redirect in &command.redirects {
+            let result = match redirect {
+                Redirect::Output { target, append } => self
+                    .output_target(target, *append)
+                    .map(|t| stdout_target = Some(t)),
+                Redirect::Error { target, append } => self
+                    .output_target(target, *append)
+                    .map(|t| stderr_target = Some(t)),
+                Redirect::ErrorToOutput => {
+                    stderr_to_stdout = true;
+                    Ok(())
+                }
+                Redirect::OutputToError => {
+                    stdout_target = Some(OutputTarget::Stderr);
+                    Ok(())
+                }
+                Redirect::Input(target) => self.expand_single(target).and_then(|name| {
+                    match self.fs.get(&self.resolve_path(&name)) {
+                        Some(entry) if entry.is_file() => {
+                            stdin_override = Some(entry.content.unwrap_or_default());
+                            Ok(())
+                        }
+                        Some(_) => Err(format!("is a directory: {}", name)),
+                        None => Err(format!("no such file or directory: {}", name)),
+                    }
+                }),
+            };
+            if let Err(message) = result {
+                self.eprintln(&format!("zsh: {}", message));
+                return self.finish(1);
+            }
+        }
+
+        if words.is_empty() {
+            for (name, value) in assignments {
+                self.set_var(&name, value);
+            }
+            // Redirections alone still create/truncate their files
+            if let Some(target) = stdout_target {
+                self.write_output(target, String::new());
+            }
+            let status = self.substitution_status.unwrap_or(0);
+            return self.finish(status);
+        }
+
+        if self.xtrace {
+            self.eprintln(&format!("+ {}", words.join(" ")));
+        }
+
+        // `NAME=value command` sets variables for that command only
+        let saved_vars: Vec<(String, Option<String>)> = assignments
+            .into_iter()
+            .map(|(name, value)| {
+                let old = self.env.insert(name.clone(), value);
+                (name, old)
+            })
+            .collect();
+
+        if stdout_target.is_some() {
+            self.stdout.push(String::new());
+        }
+        let redirects_stderr = stderr_target.is_some() || stderr_to_stdout;
+        if stderr_target.is_some() {
+            self.stderr.push(ErrorSink::Capture(String::new()));
+        } else if stderr_to_stdout {
+            self.stderr.push(ErrorSink::Stdout);
+        }
+        let saved_stdin = stdin_override.map(|input| self.stdin.replace(input));
+
+        let result = self.run_command(&words);
+
+        if let Some(stdin) = saved_stdin {
+            self.stdin = stdin;
+        }
+        if redirects_stderr {
+            if let (Some(ErrorSink::Capture(text)), Some(target)) =
+                (self.stderr.pop(), stderr_target)
+            {
+                self.write_output(target, text);
+            }
+        }
+        if let Some(target) = stdout_target {
+            let text = self.stdout.pop().unwrap_or_default();
+            self.write_output(target, text);
+        }
+        for (name, old) in saved_vars {
+            match old {
+                Some(value) => self.env.insert(name, value),
+                None => self.env.remove(&name),
+            };
+        }
+        // Keep each command's terminal output on its own lines
+        if self.stdout.len() == 1 && !self.stdout[0].is_empty() && !self.stdout[0].ends_with('\n') {
+            self.stdout[0].push('\n');
+        }
+
+        let status = result?;
+        self.finish(status)
+    }
+
+    fn run_command(&mut self, words: &[String]) -> ExecResult {
+        let name = words[0].as_str();
+        let args = &words[1..];
+        if let Some(body) = self.functions.get(name).cloned() {
+            return self.call_function(&body, args);
+        }
+        if name.contains('/') {
+            return self.run_script(name, args);
+        }
+        self.run_builtin(name, args)
+    }
+
+    fn call_function(&mut self, body: &Node, args: &[String]) -> ExecResult {
+        if self.call_depth >= MAX_CALL_DEPTH {
+            self.eprintln("zsh: maximum nested function level reached");
+            return Ok(1);
+        }
+        let saved_positional = std::mem::replace(&mut self.positional, args.to_vec());
+        self.call_depth += 1;
+        self.local_frames.push(Vec::new());
+        let result = self.exec(body);
+        for (name, old) in self
+            .local_frames
+            .pop()
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+        {
+            match old {
+                Some(value) => self.env.insert(name, value),
+                None => self.env.remove(&name),
+            };
+        }
+        self.call_depth -= 1;
+        self.positional = saved_positional;
+        match result {
+            Err(Flow::Return(status)) => Ok(status),
+            other => other,
+        }
+    }
+
+    /// Read and parse a script file, reporting errors as a command would
+    fn load_script(&mut self, script: &str) -> Result<Node, i32> {
+        let path = self.resolve_path(script);
+        let source = match self.fs.get(&path) {
+            Some(entry) if entry.is_directory() => {
+                self.eprintln(&format!("zsh: permission denied: {}", script));
+                return Err(126);
+            }
+            Some(entry) => entry.content.unwrap_or_default(),
+            None => {
+                self.eprintln(&format!("zsh: no such file or directory: {}", script));
+                return Err(127);
+            }
+        };
+        parse(&source, &self.aliases).map_err(|message| {
+            self.eprintln(&format!("{}: {}", script, message));
+            2
+        })
+    }
+
+    /// Run a script in a subshell with its own positional parameters
+    fn run_script(&mut self, script: &str, args: &[String]) -> ExecResult {
+        let program = match self.load_script(script) {
+            Ok(program) => program,
+            Err(status) => return Ok(status),
+        };
+        self.run_program(script, &program, args)
+    }
+
+    fn run_program(&mut self, name: &str, program: &Node, args: &[String]) -> ExecResult {
+        if self.call_depth >= MAX_CALL_DEPTH {
+            self.eprintln("zsh: maximum nested function level reached");
+            return Ok(1);
+        }
+        self.call_depth += 1;
+        let status = self.subshell(|sh| {
+            sh.positional = args.to_vec();
+            sh.script_name = name.to_string();
+            sh.errexit = false;
+            sh.exec(program)
+        });
+        self.call_depth -= 1;
+        Ok(status)
+    }
+
+    /// Run a script in the current shell (`source`/`.`)
+    fn source_file(&mut self, script: &str, args: &[String]) -> ExecResult {
+        let program = match self.load_script(script) {
+            Ok(program) => program,
+            Err(status) => return Ok(status),
+        };
+        if self.call_depth >= MAX_CALL_DEPTH {
+            self.eprintln("zsh: maximum nested function level reached");
+            return Ok(1);
+        }
+        let saved_positional = if args.is_empty() {
+            None
+        } else {
+            Some(std::mem::replace(&mut self.positional, args.to_vec()))
+        };
+        self.call_depth += 1;
+        let result = self.exec(&program);
+        self.call_depth -= 1;
+        if let Some(positional) = saved_positional {
+            self.positional = positional;
+        }
+        match result {
+            Err(Flow::Return(status)) => Ok(status),
+            other => other,
+        }
+    }
+
+    fn run_builtin(&mut self, command: &str, args: &[String]) -> ExecResult {
+        if let Some(manual) = manual::lookup(command) {
+            if let Some(status) = self.check_options(command, manual, args) {
+                return Ok(status);
+            }
+        }
+        let status = match command {
+            "clear" => {
+                self.stdout[0].clear();
+                self.effects.push(ShellEffect::ClearScreen);
+                0
+            }
+            "pwd" => {
+                let cwd = self.cwd.clone();
+                self.println(&cwd);
+                0
+            }
+            "echo" => {
+                let mut newline = true;
+                let mut escapes = false;
+                let mut words = args;
+                while let Some(flags) = words.first().and_then(|w| w.strip_prefix('-')) {
+                    if flags.is_empty() || !flags.chars().all(|c| "neE".contains(c)) {
+                        break;
+                    }
+                    for flag in flags.chars() {
+                        match flag {
+                            'n' => newline = false,
+                            'e' => escapes = true,
+                            _ => escapes = false,
+                        }
+                    }
+                    words = &words[1..];
+                }
+                let text = words.join(" ");
+                if escapes {
+                    let (text, stop) = interpret_escapes(&text, false);
+                    self.print(&text);
+                    if stop {
+                        return Ok(0);
+                    }
+                } else {
+                    self.print(&text);
+                }
+                if newline {
+                    self.print("\n");
+                }
+                0
+            }
+            "printf" => {
+                let Some((format, rest)) = args.split_first() else {
+                    self.eprintln("printf: not enough arguments");
+                    return Ok(1);
+                };
+                let (text, errors) = format_printf(format, rest);
+                self.print(&text);
+                for error in &errors {
+                    self.eprintln(error);
+                }
+                i32::from(!errors.is_empty())
+            }
+            "whoami" => {
+                self.println("guest");
+                0
+            }
+            "hostname" => {
+                self.println("virtualmac");
+                0
+            }
+            "date" => {
+                self.println(&get_current_date());
+                0
+            }
+            "nano" | "pico" => {
+                let name = args.first().cloned();
+                let content = match &name {
+                    Some(name) => match self.fs.get(&self.resolve_path(name)) {
+                        Some(entry) if entry.is_directory() => {
+                            self.eprintln(&format!("{}: {}: Is a directory", command, name));
+                            return Ok(1);
+                        }
+                        Some(entry) => Some(entry.content.unwrap_or_default()),
+                        None => None,
+                    },
+                    None => None,
+                };
+                self.effects.push(ShellEffect::Edit { name, content });
+                0
+            }
+            "true" | ":" => 0,
+            "false" => 1,
+            "ls" => self.builtin_ls(args),
+            "grep" => self.builtin_grep(args),
+            "open" => self.builtin_open(args),
+            "ps" => self.builtin_ps(args),
+            "top" => self.builtin_top(args),
+            "kill" => self.builtin_kill(args),
+            "killall" => self.builtin_killall(args),
+            "cd" => self.builtin_cd(args),
+            "cat" => self.builtin_cat(args),
+            "mkdir" => self.builtin_mkdir(args),
+            "rm" => self.builtin_rm(args),
+            "touch" => {
+                let (_, args) = split_options(args);
+                if args.is_empty() {
+                    return Ok(self.usage_error(command));
+                }
+                for arg in args {
+                    let target = self.resolve_path(arg);
+                    if !self.fs.exists(&target) {
+                        self.fs.write_file(&target, "", "📄");
+                    }
+                }
+                0
+            }
+            "export" => self.builtin_export(args),
+            "unset" => {
+                let (flags, names) = split_options(args);
+                if names.is_empty() {
+                    self.eprintln("unset: not enough arguments");
+                    1
+                } else {
+                    // Like zsh, only -f reaches functions
+                    for name in names {
+                        if flags.contains('f') {
+                            self.functions.remove(name);
+                        } else {
+                            self.env.remove(name);
+                        }
+                    }
+                    0
+                }
+            }
+            "env" | "printenv" => {
+                let lines: Vec<String> = if command == "printenv" && !args.is_empty() {
+                    args.iter().filter_map(|name| self.var(name)).collect()
+                } else {
+                    self.env
+                        .iter()
+                        .map(|(name, value)| format!("{}={}", name, value))
+                        .collect()
+                };
+                for line in lines {
+                    self.println(&line);
+                }
+                0
+            }
+            "local" => {
+                if self.local_frames.is_empty() {
+                    self.eprintln("local: can only be used in a function");
+                    return Ok(1);
+                }
+                for arg in args {
+                    let (name, value) = match arg.split_once('=') {
+                        Some((name, value)) => (name, value.to_string()),
+                        None => (arg.as_str(), String::new()),
+                    };
+                    if !is_valid_identifier(name) {
+                        self.eprintln(&format!("local: not an identifier: {}", name));
+                        return Ok(1);
+                    }
+                    let old = self.env.insert(name.to_string(), value);
+                    if let Some(frame) = self.local_frames.last_mut() {
+                        if !frame.iter().any(|(n, _)| n == name) {
+                            frame.push((name.to_string(), old));
+                        }
+                    }
+                }
+                0
+            }
+            "set" => self.builtin_set(args),
+            "shift" => {
+                let count = match args.first() {
+                    Some(n) => n.parse::<usize>().unwrap_or(usize::MAX),
+                    None => 1,
+                };
+                if count > self.positional.len() {
+                    self.eprintln("shift: shift count must be <= $#");
+                    1
+                } else {
+                    self.positional.drain(..count);
+                    0
+                }
+            }
+            "test" => self.builtin_test(args),
+            "[" => match args.split_last() {
+                Some((last, rest)) if last == "]" => self.builtin_test(rest),
+                _ => {
+                    self.eprintln("[: ']' expected");
+                    2
+                }
+            },
+            "exit" | "return" => {
+                let status = match args.first() {
+                    Some(n) => match n.parse::<i32>() {
+                        Ok(n) => n & 0xff,
+                        Err(_) => {
+                            self.eprintln(&format!("{}: bad math expression: {}", command, n));
+                            1
+                        }
+                    },
+                    None => self.last_status,
+                };
+                return Err(if command == "exit" {
+                    Flow::Exit(status)
+                } else {
+                    Flow::Return(status)
+                });
+            }
+            "break" | "continue" => {
+                let count = args
+                    .first()
+                    .and_then(|n| n.parse::<usize>().ok())
+                    .unwrap_or(1)
+                    .clamp(1, self.loop_depth.max(1));
+                if self.loop_depth == 0 {
+                    0
+                } else {
+                    return Err(if command == "break" {
+                        Flow::Break(count)
+                    } else {
+                        Flow::Continue(count)
+                    });
+                }
+            }
+            "source" | "." => match args.split_first() {
+                Some((script, rest)) => return self.source_file(script, rest),
+                None => {
+                    self.eprintln(&format!("{}: not enough arguments", command));
+                    1
+                }
+            },
+            "sh" | "bash" | "zsh" => return self.builtin_sh(command, args),
+            "history" => self.builtin_history(args),
+            "alias" => self.builtin_alias(args),
+            "unalias" => {
+                let (flags, names) = split_options(args);
+                if flags.contains('a') {
+                    self.aliases.clear();
+                    return Ok(0);
+                }
+                if names.is_empty() {
+                    self.eprintln("unalias: not enough arguments");
+                    return Ok(1);
+                }
+                let mut status = 0;
+                for name in names {
+                    if self.aliases.remove(name).is_none() {
+                        self.eprintln(&format!("unalias: no such hash table element: {}", name));
+                        status = 1;
+                    }
+                }
+                status
+            }
+            "functions" => {
+                let names: Vec<String> = if args.is_empty() {
+                    self.definitions().functions.into_keys().collect()
+                } else {
+                    args.to_vec()
+                };
+                let mut status = 0;
+                for name in names {
+                    match self.function_source(&name) {
+                        Some(source) => self.println(&source),
+                        None => status = 1,
+                    }
+                }
+                status
+            }
+            "unfunction" => {
+                let mut status = 0;
+                for name in args {
+                    if self.functions.remove(name).is_none() {
+                        self.eprintln(&format!("unfunction: no such hash table element: {}", name));
+                        status = 1;
+                    }
+                }
+                status
+            }
+            "help" => self.builtin_help(args),
+            "man" => self.builtin_man(args),
+            "less" | "more" => self.builtin_less(command, args),
+            "defaults" => self.builtin_defaults(args),
+            "base64" => self.builtin_base64(args),
+            "shasum" => self.builtin_shasum(args),
+            "md5" => self.builtin_md5(args),
+            "xxd" => self.builtin_xxd(args),
+            "hexdump" => self.builtin_hexdump(args),
+            "file" => self.builtin_file(args),
+            "pbcopy" => match self.stdin.clone() {
+                Some(input) => {
+                    self.clipboard.copy_text(input);
+                    0
+                }
+                None => {
+                    self.eprintln("pbcopy: no input; pipe or redirect text into it");
+                    1
+                }
+            },
+            "pbpaste" => {
+                if let Some(text) = self.clipboard.text() {
+                    self.print(&text);
+                }
+                0
+            }
+            "notify" => {
+                if args.is_empty() {
+                    self.usage_error(command)
+                } else {
+                    let title = args[0].clone();
+                    let message = args[1..].join(" ");
+                    self.notifications.show(title, message);
+                    0
+                }
+            }
+            _ => {
+                self.eprintln(&format!("command not found: {}", command));
+                127
+            }
+        };
+        Ok(status)
+    }
+
+    /// Answer `--help` and reject unknown options before a builtin runs.
+    /// Returns the exit status when the command shouldn't run.
+    fn check_options(&mut self, command: &str, manual: &Manual, args: &[String]) -> Option<i32> {
+        if manual.options == Options::Literal {
+            return None;
+        }
+        if args.first().is_some_and(|arg| arg == "--help") {
+            self.print(&manual.help());
+            return Some(0);
+        }
+        let Options::Flags(flags) = manual.options else {
+            return None;
+        };
+        for arg in args {
+            if arg == "--" || arg == "-" || !arg.starts_with('-') {
+                break;
+            }
+            if let Some(flag) = arg.chars().skip(1).find(|c| !flags.contains(*c)) {
+                return Some(self.illegal_option(command, flag));
+            }
+        }
+        None
+    }
+
+    /// Report an unknown option, followed by the command's usage
+    fn illegal_option(&mut self, command: &str, option: char) -> i32 {
+        self.eprintln(&format!("{}: illegal option -- {}", command, option));
+        self.usage_error(command)
+    }
+
+    /// Report an option `parse_options` rejected, followed by the usage
+    fn option_error(&mut self, command: &str, error: OptionError) -> i32 {
+        match error {
+            OptionError::Illegal(option) => self.illegal_option(command, option),
+            OptionError::MissingValue(option) => {
+                self.eprintln(&format!(
+                    "{}: option requires an argument -- {}",
+                    command, option
+                ));
+                self.usage_error(command)
+            }
+        }
+    }
+
+    /// Print the command's usage to stderr, for a call it can't make sense of
+    fn usage_error(&mut self, command: &str) -> i32 {
+        if let Some(manual) = manual::lookup(command) {
+            self.eprint(&manual.usage());
+        }
+        1
+    }
+
+    fn builtin_ls(&mut self, args: &[String]) -> i32 {
+        let (flags, operands) = split_options(args);
+        let show_hidden = flags.contains('a');
+        // Like BSD ls, -G (or CLICOLOR) only colors output going to the terminal
+        let colorize = (flags.contains('G') || self.var("CLICOLOR").is_some())
+            && (self.stdout_is_terminal() || self.var("CLICOLOR_FORCE").is_some());
+
+        let mut status = 0;
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+        if operands.is_empty() {
+            dirs.push((String::new(), self.cwd.clone()));
+        }
+        for operand in operands {
+            let path = self.resolve_path(operand);
+            match self.fs.get(&path) {
+                None => {
+                    self.eprintln(&format!("ls: {}: No such file or directory", operand));
+                    status = 1;
+                }
+                Some(entry) if entry.is_file() => files.push(operand.to_string()),
+                Some(_) => dirs.push((operand.to_string(), path)),
+            }
+        }
+
+        if !files.is_empty() {
+            files.sort();
+            self.println(&files.join("  "));
+        }
+        let show_headers = operands.len() > 1;
+        for (index, (display, path)) in dirs.iter().enumerate() {
+            if show_headers {
+                if index > 0 || !files.is_empty() {
+                    self.println("");
+                }
+                self.println(&format!("{}:", display));
+            }
+            let mut entries = self.fs.list_dir(path);
+            entries.retain(|e| show_hidden || !e.metadata.name.starts_with('.'));
+            entries.sort_by(|a, b| a.metadata.name.cmp(&b.metadata.name));
+            let names: Vec<String> = entries
+                .iter()
+                .map(|e| {
+                    if !matches!(e.metadata.entry_type, EntryType::Directory) {
+                        e.metadata.name.clone()
+                    } else if colorize {
+                        format!("{}/", ansi::paint(&e.metadata.name, DIRECTORY_COLOR))
+                    } else {
+                        format!("{}/", e.metadata.name)
+                    }
+                })
+                .collect();
+            if !names.is_empty() {
+                self.println(&names.join("  "));
+            }
+        }
+        status
+    }
+
+    /// `mkdir [-p] directory ...`
+    fn builtin_mkdir(&mut self, args: &[String]) -> i32 {
+        let (flags, operands) = split_options(args);
+        if operands.is_empty() {
+            return self.usage_error("mkdir");
+        }
+        let parents = flags.contains('p');
+        let mut status = 0;
+        for operand in operands {
+            let target = self.resolve_path(operand);
+            if parents {
+                // Create each missing ancestor in turn
+                let mut path = String::new();
+                for component in target.split('/').filter(|c| !c.is_empty()) {
+                    path = format!("{}/{}", path, component);
+                    match self.fs.get(&path) {
+                        Some(entry) if entry.is_directory() => {}
+                        Some(_) => {
+                            self.eprintln(&format!("mkdir: {}: Not a directory", operand));
+                            status = 1;
+                            break;
+                        }
+                        None => self.fs.create_dir(&path),
+                    }
+                }
+                continue;
+            }
+            let parent = match target.rsplit_once('/') {
+                Some(("", _)) | None => "/",
+                Some((parent, _)) => parent,
+            };
+            if self.fs.exists(&target) {
+                self.eprintln(&format!("mkdir: {}: File exists", operand));
+                status = 1;
+            } else if !self
+                .fs
+                .get(parent)
+                .is_some_and(|entry| entry.is_directory())
+            {
+                self.eprintln(&format!("mkdir: {}: No such file or directory", operand));
+                status = 1;
+            } else {
+                self.fs.create_dir(&target);
+            }
+        }
+        status
+    }
+
+    /// `rm [-fr] file ...`
+    fn builtin_rm(&mut self, args: &[String]) -> i32 {
+        let (flags, operands) = split_options(args);
+        let force = flags.contains('f');
+        let recursive = flags.contains('r') || flags.contains('R');
+        if operands.is_empty() {
+            // `rm -f` with nothing to remove quietly succeeds
+            return if force { 0 } else { self.usage_error("rm") };
+        }
+        let mut status = 0;
+        for operand in operands {
+            let target = self.resolve_path(operand);
+            match self.fs.get(&target) {
+                None if force => {}
+                None => {
+                    self.eprintln(&format!("rm: {}: No such file or directory", operand));
+                    status = 1;
+                }
+                Some(entry) if entry.is_directory() && !recursive => {
+                    self.eprintln(&format!("rm: {}: is a directory", operand));
+                    status = 1;
+                }
+                Some(_) => self.remove_tree(&target),
+            }
+        }
+        status
+    }
+
+    /// Delete a file, or a directory and everything below it
+    fn remove_tree(&mut self, path: &str) {
+        for child in self.fs.list_dir(path) {
+            self.remove_tree(&child.metadata.path);
+        }
+        self.fs.delete(path);
+    }
+
+    /// `help [command]`: list every command, or show one command's usage
+    fn builtin_help(&mut self, args: &[String]) -> i32 {
+        if let Some(name) = args.first() {
+            return match manual::lookup(name) {
+                Some(manual) => {
+                    self.print(&manual.help());
+                    0
+                }
+                None => {
+                    self.eprintln(&format!("help: no help topics match `{}'", name));
+                    1
+                }
+            };
+        }
+        let entries: Vec<(String, &str)> = manual::all()
+            .map(|manual| {
+                let mut names = vec![manual.name];
+                names.extend(manual.aliases.iter().copied());
+                (names.join(", "), manual.summary)
+            })
+            .collect();
+        let width = entries
+            .iter()
+            .map(|(names, _)| names.len())
+            .max()
+            .unwrap_or(0);
+        self.println(
+            "Type `man command` for a command's manual, or `command --help` for its usage.",
+        );
+        self.println("");
+        for (names, summary) in entries {
+            self.println(&format!("  {:<width$}  {}", names, summary));
+        }
+        self.println("");
+        self.println("Shell syntax: pipes (|), &&, ||, ;, > >> < redirection, if/elif/else, for, while/until, functions, !! !n !prefix history expansion");
+        0
+    }
+
+    /// `man command ...` shows manual pages in the pager; `man -k keyword`
+    /// searches their names and summaries
+    fn builtin_man(&mut self, args: &[String]) -> i32 {
+        let (flags, names) = split_options(args);
+        if flags.contains('k') {
+            if names.is_empty() {
+                return self.usage_error("man");
+            }
+            let keyword = names.join(" ").to_lowercase();
+            let lines: Vec<String> = manual::all()
+                .filter(|manual| {
+                    manual.name.to_lowercase().contains(&keyword)
+                        || manual.summary.to_lowercase().contains(&keyword)
+                })
+                .map(|manual| format!("{}(1) - {}", manual.name, manual.summary))
+                .collect();
+            if lines.is_empty() {
+                self.println(&format!("{}: nothing appropriate", keyword));
+                return 1;
+            }
+            for line in lines {
+                self.println(&line);
+            }
+            return 0;
+        }
+        if names.is_empty() {
+            self.eprintln("What manual page do you want?");
+            self.eprintln("For example, try 'man man'.");
+            return 1;
+        }
+        let mut status = 0;
+        let mut pages = Vec::new();
+        for name in names {
+            match manual::lookup(name) {
+                Some(manual) => pages.push(manual),
+                None => {
+                    self.eprintln(&format!("No manual entry for {}", name));
+                    status = 1;
+                }
+            }
+        }
+        let Some(first) = pages.first() else {
+            return status;
+        };
+        let text: Vec<String> = pages.iter().map(|manual| manual.page()).collect();
+        let text = text.join("\n");
+        if self.stdout_is_terminal() {
+            self.effects.push(ShellEffect::Page {
+                text,
+                prompt: PagerPrompt::Manual(format!("Manual page {}(1)", first.name)),
+                ignore_case: false,
+            });
+        } else {
+            self.print(&ansi::strip(&text));
+        }
+        status
+    }
+
+    /// `less [-iN] [file ...]` pages files, or standard input at the end of
+    /// a pipeline
+    fn builtin_less(&mut self, command: &str, args: &[String]) -> i32 {
+        let (flags, files) = split_options(args);
+        let mut status = 0;
+        let mut sections = Vec::new();
+        if files.is_empty() {
+            match self.stdin.clone() {
+                Some(input) => sections.push(input),
+                None => {
+                    self.eprintln(&format!(
+                        "Missing filename (\"{} --help\" for help)",
+                        command
+                    ));
+                    return 1;
+                }
+            }
+        }
+        for file in files {
+            match self.fs.get(&self.resolve_path(file)) {
+                Some(entry) if entry.is_directory() => {
+                    self.eprintln(&format!("{} is a directory", file));
+                    status = 1;
+                }
+                Some(entry) => {
+                    let mut content = entry.content.unwrap_or_default();
+                    // Keep the next file's first line off this one's last
+                    if !content.is_empty() && !content.ends_with('\n') {
+                        content.push('\n');
+                    }
+                    // Several files are shown one after another, each under
+                    // a banner with its name
+                    if files.len() > 1 {
+                        sections.push(format!(
+                            "::::::::::::::\n{}\n::::::::::::::\n{}",
+                            file, content
+                        ));
+                    } else {
+                        sections.push(content);
+                    }
+                }
+                None => {
+                    self.eprintln(&format!("{}: No such file or directory", file));
+                    status = 1;
+                }
+            }
+        }
+        if sections.is_empty() {
+            return status;
+        }
+        let mut text = sections.concat();
+        if flags.contains('N') {
+            let numbered: Vec<String> = text
+                .lines()
+                .enumerate()
+                .map(|(index, line)| format!("{:>7} {}\n", index + 1, line))
+                .collect();
+            text = numbered.concat();
+        }
+        // Like less, act as cat when the output isn't the terminal
+        if !self.stdout_is_terminal() {
+            self.print(&text);
+            return status;
+        }
+        let name = match files {
+            [file] => Some(file.clone()),
+            _ => None,
+        };
+        self.effects.push(ShellEffect::Page {
+            text,
+            prompt: PagerPrompt::File(name),
+            ignore_case: flags.contains('i'),
+        });
+        status
+    }
+
+    fn builtin_defaults(&mut self, args: &[String]) -> i32 {
+        let Some((action, rest)) = args.split_first() else {
+            return self.usage_error("defaults");
+        };
+        // -g and -globalDomain stand for the domain every app shares
+        let domain = rest.first().map(|domain| match domain.as_str() {
+            "-g" | "-globalDomain" => preferences::GLOBAL_DOMAIN.to_string(),
+            other => other.to_string(),
+        });
+        let key = rest.get(1);
+        match (action.as_str(), domain, key) {
+            ("domains", None, _) => {
+                let domains: Vec<String> = self
+                    .preferences
+                    .domains()
+                    .into_iter()
+                    .filter(|domain| domain != preferences::GLOBAL_DOMAIN)
+                    .collect();
+                self.println(&domains.join(", "));
+                0
+            }
+            ("read", None, _) => {
+                let mut text = String::from("{\n");
+                for name in self.preferences.domains() {
+                    let entries = self.preferences.domain(&name).unwrap_or_default();
+                    text.push_str(&format!(
+                        "    {} = {};\n",
+                        name,
+                        preferences::describe_domain(&entries, 4)
+                    ));
+                }
+                text.push('}');
+                self.println(&text);
+                0
+            }
+            ("read", Some(domain), None) => match self.preferences.domain(&domain) {
+                Some(entries) => {
+                    self.println(&preferences::describe_domain(&entries, 0));
+                    0
+                }
+                None => {
+                    self.eprintln(&format!("Domain {} does not exist", domain));
+                    1
+                }
+            },
+            ("read", Some(domain), Some(key)) | ("read-type", Some(domain), Some(key)) => {
+                match self.preferences.get(&domain, key) {
+                    Some(value) if action == "read" => {
+                        self.println(&value.describe(0));
+                        0
+                    }
+                    Some(value) => {
+                        self.println(&format!("Type is {}", value.type_name()));
+                        0
+                    }
+                    None => {
+                        self.eprintln(&format!(
+                            "The domain/default pair of ({}, {}) does not exist",
+                            domain, key
+                        ));
+                        1
+                    }
+                }
+            }
+            ("write", Some(domain), Some(key)) if rest.len() > 2 => {
+                let existing = self.preferences.get(&domain, key);
+                match parse_default_value(&rest[2..], existing) {
+                    Ok(value) => {
+                        self.preferences.set(&domain, key, value);
+                        0
+                    }
+                    Err(argument) => {
+                        self.eprintln(&format!(
+                            "Unexpected argument {}; leaving defaults unchanged.",
+                            argument
+                        ));
+                        1
+                    }
+                }
+            }
+            ("delete", Some(domain), None) => {
+                if self.preferences.delete_domain(&domain) {
+                    0
+                } else {
+                    self.eprintln(&format!(
+                        "Domain ({}) not found.\nDefaults have not been changed.",
+                        domain
+                    ));
+                    1
+                }
+            }
+            ("delete", Some(domain), Some(key)) => {
+                if self.preferences.delete(&domain, key) {
+                    0
+                } else {
+                    self.eprintln(&format!(
+                        "Key {} does not exist in domain {}; leaving defaults unchanged",
+                        key, domain
+                    ));
+                    1
+                }
+            }
+            _ => self.usage_error("defaults"),
+        }
+    }
+
+    /// The contents of each operand, or of standard input when there are none
+    /// or for `-` (named None). Operands that can't be read are reported and
+    /// skipped, making the status 1.
+    fn read_operands(
+        &mut self,
+        command: &str,
+        operands: &[String],
+    ) -> (Vec<(Option<String>, String)>, i32) {
+        if operands.is_empty() {
+            return match self.stdin.clone() {
+                Some(input) => (vec![(None, input)], 0),
+                None => (Vec::new(), self.usage_error(command)),
+            };
+        }
+        let mut inputs = Vec::new();
+        let mut status = 0;
+        for operand in operands {
+            if operand == "-" {
+                inputs.push((None, self.stdin.clone().unwrap_or_default()));
+                continue;
+            }
+            match self.fs.get(&self.resolve_path(operand)) {
+                Some(entry) if entry.is_directory() => {
+                    self.eprintln(&format!("{}: {}: Is a directory", command, operand));
+                    status = 1;
+                }
+                Some(entry) => {
+                    inputs.push((Some(operand.clone()), entry.content.unwrap_or_default()))
+                }
+                None => {
+                    self.eprintln(&format!(
+                        "{}: {}: No such file or directory",
+                        command, operand
+                    ));
+                    status = 1;
+                }
+            }
+        }
+        (inputs, status)
+    }
+
+    fn builtin_base64(&mut self, args: &[String]) -> i32 {
+        let (options, operands) = match parse_options(args, "dD", "bio") {
+            Ok(parsed) => parsed,
+            Err(error) => return self.option_error("base64", error),
+        };
+        let mut decode = false;
+        let mut wrap = 0;
+        let mut input = operands.first().cloned();
+        let mut output = None;
+        for (option, value) in options {
+            match option {
+                'd' | 'D' => decode = true,
+                'b' => match value.parse() {
+                    Ok(width) => wrap = width,
+                    Err(_) => {
+                        self.eprintln(&format!("base64: invalid line length: {}", value));
+                        return 1;
+                    }
+                },
+                'i' => input = Some(value),
+                _ => output = Some(value),
+            }
+        }
+        let input: Vec<String> = input.into_iter().collect();
+        let (inputs, status) = self.read_operands("base64", &input);
+        let Some((_, text)) = inputs.into_iter().next() else {
+            return status;
+        };
+        let result = if decode {
+            match encoding::base64_decode(&text) {
+                // File contents are text, so bytes that aren't UTF-8 can't
+                // be kept as they are
+                Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                Err(message) => {
+                    self.eprintln(&format!("base64: {}", message));
+                    return 1;
+                }
+            }
+        } else {
+            let encoded = encoding::base64_encode(text.as_bytes());
+            let lines: Vec<String> = if wrap > 0 {
+                encoded
+                    .as_bytes()
+                    .chunks(wrap)
+                    .map(|line| String::from_utf8_lossy(line).into_owned())
+                    .collect()
+            } else {
+                vec![encoded]
+            };
+            lines.iter().map(|line| format!("{}\n", line)).collect()
+        };
+        match output.filter(|path| path != "-") {
+            Some(path) => match self.output_target(&path, false) {
+                Ok(target) => self.write_output(target, result),
+                Err(message) => {
+                    self.eprintln(&format!("base64: {}", message));
+                    return 1;
+                }
+            },
+            None => self.print(&result),
+        }
+        0
+    }
+
+    fn builtin_shasum(&mut self, args: &[String]) -> i32 {
+        let (options, operands) = match parse_options(args, "bct", "a") {
+            Ok(parsed) => parsed,
+            Err(error) => return self.option_error("shasum", error),
+        };
+        let mut algorithm = None;
+        let mut check = false;
+        for (option, value) in options {
+            match option {
+                'a' => match value.as_str() {
+                    "1" | "224" | "256" => algorithm = Some(value),
+                    _ => {
+                        self.eprintln(&format!("shasum: Unrecognized algorithm: {}", value));
+                        return 1;
+                    }
+                },
+                'c' => check = true,
+                _ => {}
+            }
+        }
+        let digest = |algorithm: &str, text: &str| {
+            let bytes = text.as_bytes();
+            encoding::hex(&match algorithm {
+                "224" => encoding::sha224(bytes),
+                "256" => encoding::sha256(bytes),
+                _ => encoding::sha1(bytes),
+            })
+        };
+        let (inputs, mut status) = self.read_operands("shasum", operands);
+        if !check {
+            let algorithm = algorithm.unwrap_or_else(|| "1".to_string());
+            for (name, text) in inputs {
+                let name = name.unwrap_or_else(|| "-".to_string());
+                self.println(&format!("{}  {}", digest(&algorithm, &text), name));
+            }
+            return status;
+        }
+
+        // Verify "hash  name" lines, telling the algorithm from the hash's
+        // length unless -a gives it
+        let mut failed = 0;
+        let mut malformed = 0;
+        for (_, list) in inputs {
+            for line in list.lines().filter(|line| !line.trim().is_empty()) {
+                let Some((expected, name)) =
+                    line.split_once("  ").or_else(|| line.split_once(" *"))
+                else {
+                    malformed += 1;
+                    continue;
+                };
+                let guessed = match expected.len() {
+                    40 => "1",
+                    56 => "224",
+                    64 => "256",
+                    _ => {
+                        malformed += 1;
+                        continue;
+                    }
+                };
+                let algorithm = algorithm.as_deref().unwrap_or(guessed);
+                let content = self
+                    .fs
+                    .get(&self.resolve_path(name))
+                    .filter(|entry| !entry.is_directory())
+                    .map(|entry| entry.content.unwrap_or_default());
+                match content {
+                    Some(text) if digest(algorithm, &text) == expected.to_lowercase() => {
+                        self.println(&format!("{}: OK", name));
+                    }
+                    Some(_) => {
+                        self.println(&format!("{}: FAILED", name));
+                        failed += 1;
+                    }
+                    None => {
+                        self.eprintln(&format!("shasum: {}: No such file or directory", name));
+                        self.println(&format!("{}: FAILED open or read", name));
+                        failed += 1;
+                    }
+                }
+            }
+        }
+        let plural = |count: usize, one: &str, many: &str| {
+            format!("{} {}", count, if count == 1 { one } else { many })
+        };
+        if malformed > 0 {
+            self.eprintln(&format!(
+                "shasum: WARNING: {} improperly formatted",
+                plural(malformed, "line is", "lines are")
+            ));
+        }
+        if failed > 0 {
+            self.eprintln(&format!(
+                "shasum: WARNING: {} did NOT match",
+                plural(failed, "computed checksum", "computed checksums")
+            ));
+            status = 1;
+        }
+        status
+    }
+
+    fn builtin_md5(&mut self, args: &[String]) -> i32 {
+        let (options, operands) = match parse_options(args, "qr", "s") {
+            Ok(parsed) => parsed,
+            Err(error) => return self.option_error("md5", error),
+        };
+        let quiet = options.iter().any(|(option, _)| *option == 'q');
+        let reverse = options.iter().any(|(option, _)| *option == 'r');
+        let strings: Vec<String> = options
+            .iter()
+            .filter(|(option, _)| *option == 's')
+            .map(|(_, value)| value.clone())
+            .collect();
+        let report = |label: Option<String>, text: &str| {
+            let hash = encoding::hex(&encoding::md5(text.as_bytes()));
+            match label {
+                Some(label) if !quiet && reverse => format!("{} {}", hash, label),
+                Some(label) if !quiet => format!("MD5 ({}) = {}", label, hash),
+                _ => hash,
+            }
+        };
+        for string in &strings {
+            let line = report(Some(format!("\"{}\"", string)), string);
+            self.println(&line);
+        }
+        if !strings.is_empty() && operands.is_empty() {
+            return 0;
+        }
+        let (inputs, status) = self.read_operands("md5", operands);
+        for (name, text) in inputs {
+            let line = report(name, &text);
+            self.println(&line);
+        }
+        status
+    }
+
+    fn builtin_xxd(&mut self, args: &[String]) -> i32 {
+        let (options, operands) = match parse_options(args, "pr", "cls") {
+            Ok(parsed) => parsed,
+            Err(error) => return self.option_error("xxd", error),
+        };
+        let mut plain = false;
+        let mut reverse = false;
+        let mut columns = 16;
+        let mut length = None;
+        let mut seek = 0;
+        for (option, value) in options {
+            match option {
+                'p' => plain = true,
+                'r' => reverse = true,
+                _ => {
+                    let Ok(number) = value.parse::<usize>() else {
+                        self.eprintln(&format!("xxd: invalid number: {}", value));
+                        return 1;
+                    };
+                    match option {
+                        'c' => columns = number.clamp(1, encoding::XXD_MAX_COLUMNS),
+                        'l' => length = Some(number),
+                        _ => seek = number,
+                    }
+                }
+            }
+        }
+        if operands.len() > 1 {
+            return self.usage_error("xxd");
+        }
+        let (inputs, status) = self.read_operands("xxd", operands);
+        let Some((_, text)) = inputs.into_iter().next() else {
+            return status;
+        };
+        if reverse {
+            return match encoding::xxd_reverse(&text, plain) {
+                Ok(bytes) => {
+                    self.print(&String::from_utf8_lossy(&bytes));
+                    0
+                }
+                Err(message) => {
+                    self.eprintln(&format!("xxd: {}", message));
+                    1
+                }
+            };
+        }
+        let bytes = text.as_bytes();
+        let start = seek.min(bytes.len());
+        let end = length.map_or(bytes.len(), |length| {
+            start.saturating_add(length).min(bytes.len())
+        });
+        let dump = if plain {
+            encoding::xxd_plain(&bytes[start..end])
+        } else {
+            encoding::xxd(&bytes[start..end], columns, start)
+        };
+        self.print(&dump);
+        0
+    }
+
+    fn builtin_hexdump(&mut self, args: &[String]) -> i32 {
+        let (options, operands) = match parse_options(args, "C", "ns") {
+            Ok(parsed) => parsed,
+            Err(error) => return self.option_error("hexdump", error),
+        };
+        let mut canonical = false;
+        let mut length = None;
+        let mut skip = 0;
+        for (option, value) in options {
+            if option == 'C' {
+                canonical = true;
+                continue;
+            }
+            let Ok(number) = value.parse::<usize>() else {
+                self.eprintln(&format!("hexdump: {}: bad number", value));
+                return 1;
+            };
+            if option == 'n' {
+                length = Some(number);
+            } else {
+                skip = number;
+            }
+        }
+        // Files are dumped as one stream, as if concatenated
+        let (inputs, status) = self.read_operands("hexdump", operands);
+        let text: String = inputs.into_iter().map(|(_, text)| text).collect();
+        let bytes = text.as_bytes();
+        let start = skip.min(bytes.len());
+        let end = length.map_or(bytes.len(), |length| {
+            start.saturating_add(length).min(bytes.len())
+        });
+        let dump = if canonical {
+            encoding::hexdump_canonical(&bytes[start..end], start)
+        } else {
+            encoding::hexdump(&bytes[start..end], start)
+        };
+        self.print(&dump);
+        status
+    }
+
+    fn builtin_file(&mut self, args: &[String]) -> i32 {
+        let (flags, operands) = split_options(args);
+        if operands.is_empty() {
+            return self.usage_error("file");
+        }
+        for operand in operands {
+            let path = self.resolve_path(operand);
+            let description = match self.fs.get(&path) {
+                Some(entry) => file_types::describe(
+                    &path,
+                    entry.is_directory(),
+                    entry.content.as_deref().unwrap_or_default(),
+                ),
+                None => format!("cannot open `{}' (No such file or directory)", operand),
+            };
+            if flags.contains('b') {
+                self.println(&description);
+            } else {
+                self.println(&format!("{}: {}", operand, description));
+            }
+        }
+        0
+    }
+
+    fn builtin_open(&mut self, args: &[String]) -> i32 {
+        let mut app: Option<AppType> = None;
+        let mut files: Vec<&String> = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "-a" => {
+                    let Some(name) = iter.next() else {
+                        self.eprintln("open: option requires an argument -- a");
+                        return self.usage_error("open");
+                    };
+                    match AppType::from_name(name) {
+                        Some(found) => app = Some(found),
+                        None => {
+                            self.eprintln(&format!("Unable to find application named '{}'", name));
+                            return 1;
+                        }
+                    }
+                }
+                "-e" => app = Some(AppType::TextEdit),
+                "--" => {
+                    files.extend(iter.by_ref());
+                }
+                flag if flag.starts_with('-') && flag.len() > 1 => {
+                    return self.illegal_option("open", flag[1..].chars().next().unwrap_or('-'));
+                }
+                _ => files.push(arg),
+            }
+        }
+
+        if files.is_empty() {
+            return match app {
+                Some(app) => {
+                    self.effects.push(ShellEffect::Open { app, path: None });
+                    0
+                }
+                None => self.usage_error("open"),
+            };
+        }
+
+        // Check every file first so nothing opens if one is missing
+        let mut targets = Vec::new();
+        for file in files {
+            let path = self.resolve_path(file);
+            let Some(entry) = self.fs.get(&path) else {
+                self.eprintln(&format!("The file {} does not exist.", path));
+                return 1;
+            };
+            let target = match &app {
+                Some(app) => {
+                    // Finder only shows folders, and the other apps only files
+                    let is_folder = entry.is_directory();
+                    let document = (is_folder == (*app == AppType::Finder)).then(|| path.clone());
+                    Some((app.clone(), document))
+                }
+                None => file_types::launch_target(&path, entry.is_directory()),
+            };
+            match target {
+                Some(target) => targets.push(target),
+                None => {
+                    self.eprintln(&format!("No application knows how to open {}.", path));
+                    return 1;
+                }
+            }
+        }
+        for (app, path) in targets {
+            self.effects.push(ShellEffect::Open { app, path });
+        }
+        0
+    }
+
+    fn builtin_ps(&mut self, args: &[String]) -> i32 {
+        let mut all = false;
+        let mut terminals = false;
+        let mut detached = false;
+        let mut user_format = false;
+        let mut full_format = false;
+        let mut pids: Option<Vec<u32>> = None;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            // BSD-style options may leave out the dash (`ps aux`)
+            let flags = arg.strip_prefix('-').unwrap_or(arg);
+            for (i, flag) in flags.char_indices() {
+                match flag {
+                    'A' | 'e' => all = true,
+                    'a' => terminals = true,
+                    'x' => detached = true,
+                    'X' => detached = false,
+                    'u' => user_format = true,
+                    'f' => full_format = true,
+                    'p' => {
+                        let rest = &flags[i + 1..];
+                        let list = if rest.is_empty() {
+                            iter.next().cloned()
+                        } else {
+                            Some(rest.to_string())
+                        };
+                        let Some(list) = list else {
+                            self.eprintln("ps: option requires an argument -- p");
+                            return self.usage_error("ps");
+                        };
+                        let selected = pids.get_or_insert_with(Vec::new);
+                        for pid in list.split([',', ' ']).filter(|p| !p.is_empty()) {
+                            match pid.parse() {
+                                Ok(pid) => selected.push(pid),
+                                Err(_) => {
+                                    self.eprintln(&format!("ps: {}: illegal process id", pid));
+                                    return 1;
+                                }
+                            }
+                        }
+                        break;
+                    }
+                    other => return self.illegal_option("ps", other),
+                }
+            }
+        }
+
+        let now = process::now();
+        // Like BSD ps, list your own processes on a terminal unless told otherwise
+        let selected: Vec<process::Process> = self
+            .processes
+            .list()
+            .into_iter()
+            .filter(|p| match &pids {
+                Some(pids) => pids.contains(&p.pid),
+                None if all || (terminals && detached) => true,
+                None if terminals => p.tty.is_some(),
+                None if detached => p.user == process::USER,
+                None => p.user == process::USER && p.tty.is_some(),
+            })
+            .collect();
+        let tty = |p: &process::Process| p.tty.clone().unwrap_or_else(|| "??".to_string());
+
+        if user_format {
+            let row = |cells: [&str; 11]| {
+                format!(
+                    "{:<16} {:>6} {:>5} {:>4} {:>8} {:>6} {:>4}  {:<4} {:>7} {:>9} {}",
+                    cells[0],
+                    cells[1],
+                    cells[2],
+                    cells[3],
+                    cells[4],
+                    cells[5],
+                    cells[6],
+                    cells[7],
+                    cells[8],
+                    cells[9],
+                    cells[10]
+                )
+            };
+            self.println(&row([
+                "USER", "PID", "%CPU", "%MEM", "VSZ", "RSS", "TT", "STAT", "STARTED", "TIME",
+                "COMMAND",
+            ]));
+            for p in &selected {
+                let rss = p.memory_kb(now);
+                let tty = tty(p);
+                let line = row([
+                    &p.user,
+                    &p.pid.to_string(),
+                    &format!("{:.1}", p.cpu_percent(now)),
+                    &format!(
+                        "{:.1}",
+                        rss as f64 * 100.0 / process::PHYSICAL_MEMORY_KB as f64
+                    ),
+                    &p.virtual_kb().to_string(),
+                    &rss.to_string(),
+                    tty.strip_prefix("tty").unwrap_or(&tty),
+                    if p.tty.is_some() { "Ss" } else { "S" },
+                    &format_start_time(p.started),
+                    &format_cpu_time(p.cpu_seconds(now)),
+                    &p.command,
+                ]);
+                self.println(&line);
+            }
+        } else if full_format {
+            let row = |cells: [&str; 8]| {
+                format!(
+                    "{:>5} {:>5} {:>5} {:>3} {:>7} {:<8}{:>10} {}",
+                    cells[0], cells[1], cells[2], cells[3], cells[4], cells[5], cells[6], cells[7]
+                )
+            };
+            self.println(&row([
+                "UID", "PID", "PPID", "C", "STIME", "TTY", "TIME", "CMD",
+            ]));
+            for p in &selected {
+                let line = row([
+                    &p.uid().to_string(),
+                    &p.pid.to_string(),
+                    &p.ppid.to_string(),
+                    "0",
+                    &format_start_time(p.started),
+                    &tty(p),
+                    &format_cpu_time(p.cpu_seconds(now)),
+                    &p.command,
+                ]);
+                self.println(&line);
+            }
+        } else {
+            let row = |cells: [&str; 4]| {
+                format!(
+                    "{:>5} {:<8}{:>10} {}",
+                    cells[0], cells[1], cells[2], cells[3]
+                )
+            };
+            self.println(&row(["PID", "TTY", "TIME", "CMD"]));
+            for p in &selected {
+                let line = row([
+                    &p.pid.to_string(),
+                    &tty(p),
+                    &format_cpu_time(p.cpu_seconds(now)),
+                    &p.command,
+                ]);
+                self.println(&line);
+            }
+        }
+        // `ps -p` fails when none of the processes exist
+        i32::from(pids.is_some() && selected.is_empty())
+    }
+
+    fn builtin_top(&mut self, args: &[String]) -> i32 {
+        let mut key = "pid".to_string();
+        let mut limit: Option<usize> = None;
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let (flag, attached) = match arg.char_indices().nth(2) {
+                Some((i, _)) if arg.starts_with('-') => (&arg[..i], Some(arg[i..].to_string())),
+                _ => (arg.as_str(), None),
+            };
+            match flag {
+                "-o" | "-n" => {
+                    let Some(value) = attached.or_else(|| iter.next().cloned()) else {
+                        self.eprintln(&format!("top: option requires an argument: {}", flag));
+                        return self.usage_error("top");
+                    };
+                    if flag == "-o" {
+                        if !["pid", "command", "cpu", "mem", "time"].contains(&value.as_str()) {
+                            self.eprintln(&format!("top: invalid argument -o: {}", value));
+                            return 1;
+                        }
+                        key = value;
+                    } else {
+                        match value.parse() {
+                            Ok(n) => limit = Some(n),
+                            Err(_) => {
+                                self.eprintln(&format!("top: invalid argument -n: {}", value));
+                                return 1;
+                            }
+                        }
+                    }
+                }
+                _ => match arg.strip_prefix('-').and_then(|flags| flags.chars().next()) {
+                    Some(flag) => return self.illegal_option("top", flag),
+                    None => {
+                        self.eprintln(&format!("top: invalid option or syntax: {}", arg));
+                        return self.usage_error("top");
+                    }
+                },
+            }
+        }
+
+        let now = process::now();
+        let mut list = self.processes.list();
+        match key.as_str() {
+            "command" => list.sort_by(|a, b| a.name.cmp(&b.name)),
+            "cpu" => list.sort_by(|a, b| b.cpu_percent(now).total_cmp(&a.cpu_percent(now))),
+            "mem" => list.sort_by_key(|p| std::cmp::Reverse(p.memory_kb(now))),
+            "time" => list.sort_by(|a, b| b.cpu_seconds(now).total_cmp(&a.cpu_seconds(now))),
+            _ => list.sort_by_key(|p| std::cmp::Reverse(p.pid)),
+        }
+
+        let total_cpu: f64 = list.iter().map(|p| p.cpu_percent(now)).sum();
+        let used_kb: u64 = list.iter().map(|p| p.memory_kb(now)).sum();
+        let running = list.iter().filter(|p| p.cpu_percent(now) >= 5.0).count();
+        // Spread the load over 8 cores, split between user and system time
+        let busy = (total_cpu / 8.0).min(100.0);
+        let load = total_cpu / 100.0;
+        self.println(&format!(
+            "Processes: {} total, {} running, {} sleeping",
+            list.len(),
+            running,
+            list.len() - running
+        ));
+        self.println(&format!(
+            "Load Avg: {:.2}, {:.2}, {:.2}  CPU usage: {:.2}% user, {:.2}% sys, {:.2}% idle",
+            load,
+            load * 1.1,
+            load * 1.2,
+            busy * 0.6,
+            busy * 0.4,
+            100.0 - busy
+        ));
+        self.println(&format!(
+            "PhysMem: {} used, {} unused.",
+            format_memory(used_kb),
+            format_memory(process::PHYSICAL_MEMORY_KB.saturating_sub(used_kb))
+        ));
+        self.println("");
+        let row = |cells: [&str; 6]| {
+            format!(
+                "{:<6} {:<16} {:>5} {:<9} {:<6} {}",
+                cells[0], cells[1], cells[2], cells[3], cells[4], cells[5]
+            )
+        };
+        self.println(&row(["PID", "COMMAND", "%CPU", "TIME", "MEM", "USER"]));
+        for p in list.iter().take(limit.unwrap_or(usize::MAX)) {
+            let centis = (p.cpu_seconds(now) * 100.0) as u64;
+            let line = row([
+                &p.pid.to_string(),
+                &p.name.chars().take(16).collect::<String>(),
+                &format!("{:.1}", p.cpu_percent(now)),
+                &format!(
+                    "{:02}:{:02}.{:02}",
+                    centis / 6000,
+                    centis / 100 % 60,
+                    centis % 100
+                ),
+                &format_memory(p.memory_kb(now)),
+                &p.user,
+            ]);
+            self.println(&line);
+        }
+        0
+    }
+
+    /// Parse a leading `-SIG`, `-s SIG` or `-n NUM` option for `kill` and
+    /// `killall`, returning the signal and the remaining operands
+    fn parse_signal_option<'a>(
+        &mut self,
+        command: &str,
+        args: &'a [String],
+    ) -> Result<(usize, &'a [String]), i32> {
+        let Some(first) = args.first() else {
+            return Ok((15, args));
+        };
+        let (spec, rest) = match first.as_str() {
+            "-s" | "-n" => match args.get(1) {
+                Some(spec) => (spec.as_str(), &args[2..]),
+                None => {
+                    self.eprintln(&format!("{}: -{}: argument expected", command, &first[1..]));
+                    return Err(1);
+                }
+            },
+            "--" => return Ok((15, &args[1..])),
+            flag if flag.starts_with('-') && flag.len() > 1 => (&flag[1..], &args[1..]),
+            _ => return Ok((15, args)),
+        };
+        match process::signal_number(spec) {
+            Some(signal) => Ok((signal, rest)),
+            None => {
+                let spec = spec.to_ascii_uppercase();
+                let name = if spec.starts_with("SIG") {
+                    spec
+                } else {
+                    format!("SIG{}", spec)
+                };
+                self.eprintln(&format!("{}: unknown signal: {}", command, name));
+                self.eprintln(&format!("{}: type kill -l for a list of signals", command));
+                Err(1)
+            }
+        }
+    }
+
+    fn builtin_kill(&mut self, args: &[String]) -> i32 {
+        if args.first().map(String::as_str) == Some("-l") {
+            if args.len() == 1 {
+                self.println(&process::SIGNALS.join(" "));
+                return 0;
+            }
+            let mut status = 0;
+            for spec in &args[1..] {
+                match process::signal_number(spec) {
+                    Some(0) => self.println("0"),
+                    // Names list as numbers and numbers as names
+                    Some(signal) if spec.parse::<usize>().is_ok() => {
+                        self.println(process::SIGNALS[signal - 1])
+                    }
+                    Some(signal) => self.println(&signal.to_string()),
+                    None => {
+                        self.eprintln(&format!("kill: unknown signal: SIG{}", spec));
+                        status = 1;
+                    }
+                }
+            }
+            return status;
+        }
+        let (signal, pids) = match self.parse_signal_option("kill", args) {
+            Ok(parsed) => parsed,
+            Err(status) => return status,
+        };
+        if pids.is_empty() {
+            self.eprintln("kill: not enough arguments");
+            return 1;
+        }
+        let mut status = 0;
+        for arg in pids {
+            let Ok(pid) = arg.parse::<u32>() else {
+                self.eprintln(&format!("kill: illegal pid: {}", arg));
+                status = 1;
+                continue;
+            };
+            if let Err(error) = self.processes.kill(pid, signal) {
+                let reason = match error {
+                    KillError::NoSuchProcess => "no such process",
+                    KillError::NotPermitted => "operation not permitted",
+                };
+                self.eprintln(&format!("kill: kill {} failed: {}", pid, reason));
+                status = 1;
+            }
+        }
+        status
+    }
+
+    fn builtin_killall(&mut self, args: &[String]) -> i32 {
+        let (signal, names) = match self.parse_signal_option("killall", args) {
+            Ok(parsed) => parsed,
+            Err(status) => return status,
+        };
+        if names.is_empty() {
+            return self.usage_error("killall");
+        }
+        let targets: Vec<u32> = self
+            .processes
+            .list()
+            .into_iter()
+            .filter(|p| p.user == process::USER && names.contains(&p.name))
+            .map(|p| p.pid)
+            .collect();
+        if targets.is_empty() {
+            self.eprintln("No matching processes belonging to you were found");
+            return 1;
+        }
+        for pid in targets {
+            let _ = self.processes.kill(pid, signal);
+        }
+        0
+    }
+
+    fn builtin_cd(&mut self, args: &[String]) -> i32 {
+        let (target, announce) = match args.first().map(String::as_str) {
+            None => (self.var("HOME").unwrap_or_else(|| "/".to_string()), false),
+            Some("-") => match self.var("OLDPWD") {
+                Some(old) => (old, true),
+                None => {
+                    self.eprintln("cd: OLDPWD not set");
+                    return 1;
+                }
+            },
+            Some(target) => (target.to_string(), false),
+        };
+        let new_path = self.resolve_path(&target);
+
+        match self.fs.get(&new_path) {
+            Some(entry) if entry.is_directory() => {
+                self.change_directory(new_path.clone());
+                if announce {
+                    let home = self.var("HOME").unwrap_or_default();
+                    self.println(&abbreviate_home(&new_path, &home));
+                }
+                0
+            }
+            Some(_) => {
+                self.eprintln(&format!("cd: not a directory: {}", target));
+                1
+            }
+            None => {
+                self.eprintln(&format!("cd: no such file or directory: {}", target));
+                1
+            }
+        }
+    }
+
+    fn builtin_cat(&mut self, args: &[String]) -> i32 {
+        let (_, args) = split_options(args);
+        if args.is_empty() {
+            return match self.stdin.clone() {
+                Some(input) => {
+                    self.print(&input);
+                    0
+                }
+                None => self.usage_error("cat"),
+            };
+        }
+        let mut status = 0;
+        for arg in args {
+            let target = self.resolve_path(arg);
+            match self.fs.get(&target) {
+                Some(entry) if entry.is_directory() => {
+                    self.eprintln(&format!("cat: {}: Is a directory", arg));
+                    status = 1;
+                }
+                Some(entry) => self.print(&entry.content.unwrap_or_default()),
+                None => {
+                    self.eprintln(&format!("cat: {}: No such file or directory", arg));
+                    status = 1;
+                }
+            }
+        }
+        status
+    }
+
+    fn builtin_grep(&mut self, args: &[String]) -> i32 {
+        let mut ignore_case = false;
+        let mut invert = false;
+        let mut line_numbers = false;
+        let mut count_only = false;
+        let mut files_only = false;
+        let mut quiet = false;
+        let mut fixed = false;
+        let mut color = "never".to_string();
+        let mut pattern: Option<String> = None;
+        let mut operands: Vec<String> = Vec::new();
+
+        let mut rest = args.iter();
+        let mut options_done = false;
+        while let Some(arg) = rest.next() {
+            if options_done || arg == "-" || !arg.starts_with('-') {
+                operands.push(arg.clone());
+                continue;
+            }
+            if arg == "--" {
+                options_done = true;
+                continue;
+            }
+            if let Some(long) = arg.strip_prefix("--") {
+                let (name, value) = match long.split_once('=') {
+                    Some((name, value)) => (name, value),
+                    None => (long, "auto"),
+                };
+                if name != "color" && name != "colour" {
+                    self.eprintln(&format!("grep: unrecognized option `{}'", arg));
+                    self.usage_error("grep");
+                    return 2;
+                }
+                if !matches!(value, "always" | "auto" | "never") {
+                    self.eprintln(&format!("grep: invalid argument `{}' for --color", value));
+                    return 2;
+                }
+                color = value.to_string();
+                continue;
+            }
+            for (index, flag) in arg.char_indices().skip(1) {
+                match flag {
+                    'i' => ignore_case = true,
+                    'v' => invert = true,
+                    'n' => line_numbers = true,
+                    'c' => count_only = true,
+                    'l' => files_only = true,
+                    'q' => quiet = true,
+                    'F' => fixed = true,
+                    'E' => fixed = false,
+                    'e' => {
+                        let attached = &arg[index + 1..];
+                        let value = if attached.is_empty() {
+                            rest.next().cloned()
+                        } else {
+                            Some(attached.to_string())
+                        };
+                        match value {
+                            Some(value) => pattern = Some(value),
+                            None => {
+                                self.eprintln("grep: option requires an argument -- e");
+                                self.usage_error("grep");
+                                return 2;
+                            }
+                        }
+                        break;
+                    }
+                    other => {
+                        self.illegal_option("grep", other);
+                        return 2;
+                    }
+                }
+            }
+        }
+        let pattern = match pattern {
+            Some(pattern) => pattern,
+            None if !operands.is_empty() => operands.remove(0),
+            None => {
+                self.usage_error("grep");
+                return 2;
+            }
+        };
+        let regex = if fixed {
+            Regex::literal(&pattern, ignore_case)
+        } else {
+            match Regex::new(&pattern, ignore_case) {
+                Ok(regex) => regex,
+                Err(error) => {
+                    self.eprintln(&format!("grep: {}", error));
+                    return 2;
+                }
+            }
+        };
+        let colorize = !invert
+            && match color.as_str() {
+                "always" => true,
+                "auto" => self.stdout_is_terminal(),
+                _ => false,
+            };
+        let match_color = self
+            .var("GREP_COLOR")
+            .unwrap_or_else(|| DEFAULT_MATCH_COLOR.to_string());
+
+        let mut inputs: Vec<(String, String)> = Vec::new();
+        let mut errors = false;
+        if operands.is_empty() {
+            match self.stdin.clone() {
+                Some(input) => inputs.push(("(standard input)".to_string(), input)),
+                None => {
+                    self.usage_error("grep");
+                    return 2;
+                }
+            }
+        }
+        for operand in &operands {
+            match self.fs.get(&self.resolve_path(operand)) {
+                Some(entry) if entry.is_directory() => {
+                    self.eprintln(&format!("grep: {}: Is a directory", operand));
+                    errors = true;
+                }
+                Some(entry) => inputs.push((operand.clone(), entry.content.unwrap_or_default())),
+                None => {
+                    self.eprintln(&format!("grep: {}: No such file or directory", operand));
+                    errors = true;
+                }
+            }
+        }
+
+        let show_names = operands.len() > 1;
+        let mut matched_any = false;
+        for (name, content) in inputs {
+            let mut count = 0;
+            for (number, line) in content.lines().enumerate() {
+                if regex.is_match(line) == invert {
+                    continue;
+                }
+                count += 1;
+                matched_any = true;
+                if quiet {
+                    return 0;
+                }
+                if files_only {
+                    break;
+                }
+                if count_only {
+                    continue;
+                }
+                let mut output = String::new();
+                if show_names {
+                    output.push_str(&format!("{}:", name));
+                }
+                if line_numbers {
+                    output.push_str(&format!("{}:", number + 1));
+                }
+                if colorize {
+                    let mut last = 0;
+                    for (start, end) in regex.find_iter(line).filter(|(s, e)| s < e) {
+                        output.push_str(&line[last..start]);
+                        output.push_str(&ansi::paint(&line[start..end], &match_color));
+                        last = end;
+                    }
+                    output.push_str(&line[last..]);
+                } else {
+                    output.push_str(line);
+                }
+                self.println(&output);
+            }
+            if files_only && count > 0 {
+                self.println(&name);
+            } else if count_only {
+                if show_names {
+                    self.println(&format!("{}:{}", name, count));
+                } else {
+                    self.println(&count.to_string());
+                }
+            }
+        }
+        if errors {
+            2
+        } else if matched_any {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// `alias [name[=value] ...]`: define aliases, or show them
+    fn builtin_alias(&mut self, args: &[String]) -> i32 {
+        if args.is_empty() {
+            let lines: Vec<String> = self
+                .aliases
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, quote_word(value)))
+                .collect();
+            for line in lines {
+                self.println(&line);
+            }
+            return 0;
+        }
+        let mut status = 0;
+        for arg in args {
+            if let Some(flag) = arg.strip_prefix('-').and_then(|flags| flags.chars().next()) {
+                return self.illegal_option("alias", flag);
+            }
+            match arg.split_once('=') {
+                Some((name, value)) if !name.is_empty() => {
+                    self.aliases.insert(name.to_string(), value.to_string());
+                }
+                _ => match self.aliases.get(arg) {
+                    Some(value) => {
+                        let line = format!("{}={}", arg, quote_word(value));
+                        self.println(&line);
+                    }
+                    // zsh reports unknown aliases through the status only
+                    None => status = 1,
+                },
+            }
+        }
+        status
+    }
+
+    /// `history [first]`: list events from `first` (negative counts back
+    /// from the newest), or the last few when no range is given
+    fn builtin_history(&mut self, args: &[String]) -> i32 {
+        let total = self.history.len();
+        let first = match args.first() {
+            None => total.saturating_sub(HISTORY_LIST_LENGTH) + 1,
+            Some(arg) => match arg.parse::<i64>() {
+                Ok(n) if n < 0 => total.saturating_sub(n.unsigned_abs() as usize) + 1,
+                Ok(n) if n > 0 && (n as usize) <= total => n as usize,
+                Ok(_) => {
+                    self.eprintln(&format!("history: event not found: {}", arg));
+                    return 1;
+                }
+                Err(_) if arg.starts_with('-') => {
+                    return self.illegal_option("history", arg.chars().nth(1).unwrap_or('-'));
+                }
+                Err(_) => match self
+                    .history
+                    .iter()
+                    .rposition(|event| event.starts_with(arg.as_str()))
+                {
+                    Some(index) => index + 1,
+                    None => {
+                        self.eprintln(&format!("history: event not found: {}", arg));
+                        return 1;
+                    }
+                },
+            },
+        };
+        let lines: Vec<String> = self
+            .history
+            .iter()
+            .enumerate()
+            .skip(first.saturating_sub(1))
+            .map(|(index, event)| format!("{:>5}  {}", index + 1, event))
+            .collect();
+        for line in lines {
+            self.println(&line);
+        }
+        0
+    }
+
+    fn builtin_export(&mut self, args: &[String]) -> i32 {
+        if args.is_empty() {
+            let lines: Vec<String> = self
+                .env
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            for line in lines {
+                self.println(&line);
+            }
+            return 0;
+        }
+        let mut status = 0;
+        for arg in args {
+            let (name, value) = match arg.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (arg.as_str(), None),
+            };
+            if !is_valid_identifier(name) {
+                self.eprintln(&format!("export: not valid in this context: {}", name));
+                status = 1;
+                continue;
+            }
+            if let Some(value) = value {
+                self.set_var(name, value.to_string());
+            }
+        }
+        status
+    }
+
+    fn builtin_set(&mut self, args: &[String]) -> i32 {
+        if args.is_empty() {
+            let lines: Vec<String> = self
+                .env
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            for line in lines {
+                self.println(&line);
+            }
+            return 0;
+        }
+        for (index, arg) in args.iter().enumerate() {
+            if arg == "--" {
+                self.positional = args[index + 1..].to_vec();
+                return 0;
+            }
+            let enable = match arg.chars().next() {
+                Some('-') => true,
+                Some('+') => false,
+                _ => {
+                    self.positional = args[index..].to_vec();
+                    return 0;
+                }
+            };
+            for flag in arg.chars().skip(1) {
+                match flag {
+                    'e' => self.errexit = enable,
+                    'x' => self.xtrace = enable,
+                    _ => return self.illegal_option("set", flag),
+                }
+            }
+        }
+        0
+    }
+
+    /// `sh script [args]`, `sh -c command [name [args]]`, or a script on stdin
+    fn builtin_sh(&mut self, command: &str, args: &[String]) -> ExecResult {
+        match args.split_first() {
+            Some((flag, rest)) if flag == "-c" => {
+                let Some((source, rest)) = rest.split_first() else {
+                    self.eprintln(&format!("{}: -c: option requires an argument", command));
+                    return Ok(2);
+                };
+                let program = match parse(source, &self.aliases) {
+                    Ok(program) => program,
+                    Err(message) => {
+                        self.eprintln(&format!("{}: {}", command, message));
+                        return Ok(2);
+                    }
+                };
+                let (name, rest) = match rest.split_first() {
+                    Some((name, rest)) => (name.as_str(), rest),
+                    None => (command, rest),
+                };
+                self.run_program(name, &program, rest)
+            }
+            Some((script, rest)) => self.run_script(script, rest),
+            None => match self.stdin.clone() {
+                Some(source) => match parse(&source, &self.aliases) {
+                    Ok(program) => self.run_program(command, &program, &[]),
+                    Err(message) => {
+                        self.eprintln(&format!("{}: {}", command, message));
+                        Ok(2)
+                    }
+                },
+                None => Ok(0),
+            },
+        }
+    }
+
+    fn builtin_test(&mut self, args: &[String]) -> i32 {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match self.eval_test(&args) {
+            Ok(true) => 0,
+            Ok(false) => 1,
+            Err(message) => {
+                self.eprintln(&format!("test: {}", message));
+                2
+            }
+        }
+    }
+
+    /// Evaluate a `test`/`[` expression
+    fn eval_test(&self, args: &[&str]) -> Result<bool, String> {
+        if args.len() > 4 {
+            // Combine sub-expressions, `-o` binding looser than `-a`
+            for joiner in ["-o", "-a"] {
+                if let Some(index) = args.iter().rposition(|a| *a == joiner) {
+                    let left = self.eval_test(&args[..index])?;
+                    let right = self.eval_test(&args[index + 1..])?;
+                    return Ok(if joiner == "-o" {
+                        left || right
+                    } else {
+                        left && right
+                    });
+                }
+            }
+        }
+        match args {
+            [] => Ok(false),
+            [value] => Ok(!value.is_empty()),
+            ["!", rest @ ..] => Ok(!self.eval_test(rest)?),
+            ["(", inner @ .., ")"] => self.eval_test(inner),
+            [op, operand] => self.eval_unary_test(op, operand),
+            [left, op, right] => self.eval_binary_test(left, op, right),
+            _ => Err("too many arguments".to_string()),
+        }
+    }
+
+    fn eval_unary_test(&self, op: &str, operand: &str) -> Result<bool, String> {
+        let entry = || self.fs.get(&self.resolve_path(operand));
+        match op {
+            "-z" => Ok(operand.is_empty()),
+            "-n" => Ok(!operand.is_empty()),
+            "-e" | "-r" | "-w" | "-x" => Ok(entry().is_some()),
+            "-f" => Ok(entry().is_some_and(|e| e.is_file())),
+            "-d" => Ok(entry().is_some_and(|e| e.is_directory())),
+            "-s" => Ok(entry().is_some_and(|e| e.content.is_some_and(|c| !c.is_empty()))),
+            _ => Err(format!("unknown condition: {}", op)),
+        }
+    }
+
+    fn eval_binary_test(&self, left: &str, op: &str, right: &str) -> Result<bool, String> {
+        let integer = |value: &str| {
+            value
+                .trim()
+                .parse::<i64>()
+                .map_err(|_| format!("integer expression expected: {}", value))
+        };
+        match op {
+            "=" | "==" => Ok(left == right),
+            "!=" => Ok(left != right),
+            "<" => Ok(left < right),
+            ">" => Ok(left > right),
+            "-eq" => Ok(integer(left)? == integer(right)?),
+            "-ne" => Ok(integer(left)? != integer(right)?),
+            "-lt" => Ok(integer(left)? < integer(right)?),
+            "-le" => Ok(integer(left)? <= integer(right)?),
+            "-gt" => Ok(integer(left)? > integer(right)?),
+            "-ge" => Ok(integer(left)? >= integer(right)?),
+            _ => Err(format!("unknown condition: {}", op)),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::preferences::{Preferences, Value, DOCK_DOMAIN};
+use crate::theme::{use_theme, Theme};
 use crate::wallpaper::{use_wallpaper_context, WALLPAPERS};
 use leptos::prelude::*;
 
@@ -167,16 +169,31 @@ fn GeneralPane() -> impl IntoView {
 
 #[component]
 fn AppearancePane() -> impl IntoView {
+    let theme_ctx = use_theme();
+    let option_class = move |theme: Theme| {
+        if theme_ctx.theme.get() == theme {
+            "appearance-option selected"
+        } else {
+            "appearance-option"
+        }
+    };
+
     view! {
         <div class="settings-pane">
             <h1 class="settings-pane-title">"Appearance"</h1>
             <div class="settings-group">
                 <div class="appearance-options">
-                    <div class="appearance-option selected">
+                    <div
+                        class=move || option_class(Theme::Light)
+                        on:click=move |_| theme_ctx.set(Theme::Light)
+                    >
                         <div class="appearance-preview light"></div>
                         <span>"Light"</span>
                     </div>
-                    <div class="appearance-option">
+                    <div
+                        class=move || option_class(Theme::Dark)
+                        on:click=move |_| theme_ctx.set(Theme::Dark)
+                    >
                         <div class="appearance-preview dark"></div>
                         <span>"Dark"</span>
                     </div>
@@ -192,6 +209,13 @@ fn AppearancePane() -> impl IntoView {
 
 #[component]
 fn DesktopDockPane() -> impl IntoView {
+    let preferences = expect_context::<Preferences>();
+    let magnification = move || {
+        preferences
+            .get_bool(DOCK_DOMAIN, "magnification")
+            .unwrap_or(true)
+    };
+
     view! {
         <div class="settings-pane">
             <h1 class="settings-pane-title">"Desktop & Dock"</h1>
@@ -207,7 +231,17 @@ fn DesktopDockPane() -> impl IntoView {
                     <span class="settings-row-label">"Magnification"</span>
                     <div class="settings-row-control">
                         <label class="toggle">
-                            <input type="checkbox" checked />
+                            <input
+                                type="checkbox"
+                                prop:checked=magnification
+                                on:change=move |ev| {
+                                    preferences.set(
+                                        DOCK_DOMAIN,
+                                        "magnification",
+                                        Value::Bool(event_target_checked(&ev)),
+                                    )
+                                }
+                            />
                             <span class="toggle-slider"></span>
                         </label>
                     </div>
@@ -244,7 +278,7 @@ fn WallpaperPane() -> impl IntoView {
                     view! {
                         <div
                             class=move || if is_selected() { "wallpaper-item selected" } else { "wallpaper-item" }
-                            on:click=move |_| wallpaper_ctx.set_current.set(id)
+                            on:click=move |_| wallpaper_ctx.set(id)
                         >
                             <div class="wallpaper-thumbnail" style=format!("background: {}", gradient) />
                             <span class="wallpaper-name">{name}</span>
@@ -1,5 +1,7 @@
 use leptos::prelude::*;
 
+use crate::window_manager::WindowAction;
+
 /// Minimized window info for dock display
 #[derive(Clone, Debug, PartialEq)]
 pub struct MinimizedWindow {
@@ -9,6 +11,16 @@ pub struct MinimizedWindow {
     pub icon_class: String,
 }
 
+/// An open window, as the Window menu lists it
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListedWindow {
+    pub id: usize,
+    pub title: String,
+    pub app: String,
+    pub is_active: bool,
+    pub is_minimized: bool,
+}
+
 /// System-wide state for VirtualMac
 #[derive(Clone, Copy)]
 pub struct SystemState {
@@ -20,8 +32,10 @@ pub struct SystemState {
     pub active_modal: RwSignal<Option<ModalType>>,
     /// Whether System Settings should be opened
     pub open_system_settings: RwSignal<bool>,
-    /// App to open from dock click (app name)
-    pub open_app: RwSignal<Option<String>>,
+    /// Apps to open, oldest first, from the Dock, Finder or `open`
+    pub open_requests: RwSignal<Vec<OpenRequest>>,
+    /// Documents and folders waiting for the windows they were opened in
+    pub open_documents: RwSignal<Vec<OpenDocument>>,
     /// Whether a desktop reset was requested
     pub reset_desktop: RwSignal<bool>,
     /// Currently active/foreground app name (shown in menu bar)
@@ -30,8 +44,32 @@ pub struct SystemState {
     pub minimized_windows: RwSignal<Vec<MinimizedWindow>>,
     /// Open windows for dock running indicators
     pub open_windows: RwSignal<Vec<String>>,
+    /// Running apps hidden with ⌘H, whose windows stay out of view until
+    /// the app is switched back to
+    pub hidden_apps: RwSignal<Vec<String>>,
     /// Request to restore a minimized window by ID
     pub restore_window_id: RwSignal<Option<usize>>,
+    /// Every open window, oldest first, for the Window menu
+    pub window_list: RwSignal<Vec<ListedWindow>>,
+    /// Request for the window manager to act on the active window
+    pub window_request: RwSignal<Option<WindowAction>>,
+    /// Whether a full-screen window is in view, hiding the menu bar and Dock
+    pub full_screen: RwSignal<bool>,
+}
+
+/// A request to open an app, optionally on a document or folder
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpenRequest {
+    pub app: String,
+    pub path: Option<String>,
+}
+
+/// A path handed to the window an app was opened in: a file for TextEdit,
+/// a folder for Finder
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpenDocument {
+    pub window_id: usize,
+    pub path: String,
 }
 
 /// Power state of the system
@@ -63,12 +101,17 @@ impl SystemState {
             power_state: RwSignal::new(PowerState::Running),
             active_modal: RwSignal::new(None),
             open_system_settings: RwSignal::new(false),
-            open_app: RwSignal::new(None),
+            open_requests: RwSignal::new(Vec::new()),
+            open_documents: RwSignal::new(Vec::new()),
             reset_desktop: RwSignal::new(false),
             active_app: RwSignal::new("Finder".to_string()), // Default to Finder like real macOS
             minimized_windows: RwSignal::new(Vec::new()),
             open_windows: RwSignal::new(Vec::new()),
+            hidden_apps: RwSignal::new(Vec::new()),
             restore_window_id: RwSignal::new(None),
+            window_list: RwSignal::new(Vec::new()),
+            window_request: RwSignal::new(None),
+            full_screen: RwSignal::new(false),
         }
     }
 
@@ -78,7 +121,61 @@ impl SystemState {
     }
 
     pub fn request_open_app(&self, app_name: &str) {
-        self.open_app.set(Some(app_name.to_string()));
+        self.request_open(app_name, None);
+    }
+
+    /// Open an app, optionally on a document or folder. Requests queue up,
+    /// so several made at once each get their window.
+    pub fn request_open(&self, app_name: &str, path: Option<&str>) {
+        self.open_requests.update(|requests| {
+            requests.push(OpenRequest {
+                app: app_name.to_string(),
+                path: path.map(str::to_string),
+            })
+        });
+    }
+
+    /// Take the next document addressed to a window
+    pub fn take_open_document(&self, window_id: usize) -> Option<String> {
+        let pending = self
+            .open_documents
+            .with(|docs| docs.iter().any(|doc| doc.window_id == window_id));
+        if !pending {
+            return None;
+        }
+        self.open_documents
+            .try_update(|docs| {
+                let index = docs.iter().position(|doc| doc.window_id == window_id)?;
+                Some(docs.remove(index).path)
+            })
+            .flatten()
+    }
+
+    /// Whether an app is hidden
+    pub fn is_app_hidden(&self, app_name: &str) -> bool {
+        self.hidden_apps
+            .with_untracked(|apps| apps.iter().any(|app| app == app_name))
+    }
+
+    /// Hide every window of an app until it is switched back to
+    pub fn hide_app(&self, app_name: &str) {
+        if !self.is_app_hidden(app_name) {
+            self.hidden_apps
+                .update(|apps| apps.push(app_name.to_string()));
+        }
+    }
+
+    /// Bring a hidden app's windows back into view
+    pub fn unhide_app(&self, app_name: &str) {
+        if self.is_app_hidden(app_name) {
+            self.hidden_apps
+                .update(|apps| apps.retain(|app| app != app_name));
+        }
+    }
+
+    /// Ask the window manager to act on the active window
+    pub fn request_window_action(&self, action: WindowAction) {
+        self.window_request.set(Some(action));
     }
 
     pub fn lock_screen(&self) {
@@ -1,21 +1,66 @@
-use crate::file_system::{use_file_system, EntryType, VirtualFileSystem};
+use crate::ansi;
+use crate::clipboard::Clipboard;
+use crate::file_system::{use_file_system, VirtualFileSystem};
 use crate::notification::NotificationState;
+use crate::preferences::Preferences;
+use crate::process::ProcessTable;
+use crate::shell::{
+    abbreviate_home, default_environment, expand_history, expand_prompt, resolve_path, PagerPrompt,
+    Shell, ShellDefinitions, ShellEffect, ShellOutput,
+};
 use crate::system_state::SystemState;
+use crate::terminal_editor::TerminalEditor;
+use crate::terminal_pager::TerminalPager;
+use crate::window_manager::use_window_app_state;
 use leptos::ev::KeyboardEvent;
 use leptos::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[allow(dead_code)]
 const STORAGE_KEY: &str = "virtualmac_terminal";
 #[allow(dead_code)]
-const CURRENT_SCHEMA_VERSION: u32 = 1;
+const CURRENT_SCHEMA_VERSION: u32 = 3;
 const MAX_COMMAND_HISTORY: usize = 1000;
+/// Lines of each tab's scrollback kept with the window
+const MAX_SAVED_SCROLLBACK: usize = 500;
+
+/// One tab's shell session
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TabState {
+    id: usize,
+    cwd: String,
+    /// Session environment variables (HOME, PATH, PS1, ...)
+    #[serde(default = "default_environment")]
+    env: BTreeMap<String, String>,
+}
+
+impl TabState {
+    fn new(id: usize, cwd: String) -> Self {
+        let mut env = default_environment();
+        env.insert("PWD".to_string(), cwd.clone());
+        Self { id, cwd, env }
+    }
+
+    /// Tab title: the last component of the working directory
+    fn title(&self) -> String {
+        let home = self.env.get("HOME").map(String::as_str).unwrap_or("/");
+        let path = abbreviate_home(&self.cwd, home);
+        match path.rsplit('/').find(|part| !part.is_empty()) {
+            Some(name) => name.to_string(),
+            None => path,
+        }
+    }
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct TerminalState {
     schema_version: u32,
+    /// Shared by all tabs, like zsh's history file
     command_history: Vec<String>,
-    cwd: String,
+    /// Aliases and functions defined at the prompt, shared by all tabs
+    #[serde(default)]
+    definitions: ShellDefinitions,
 }
 
 impl Default for TerminalState {
@@ -23,7 +68,7 @@ impl Default for TerminalState {
         Self {
             schema_version: CURRENT_SCHEMA_VERSION,
             command_history: Vec::new(),
-            cwd: "/".to_string(),
+            definitions: ShellDefinitions::default(),
         }
     }
 }
@@ -52,7 +97,12 @@ fn load_from_storage() -> TerminalState {
             if let Ok(Some(storage)) = window.local_storage() {
                 if let Ok(Some(json)) = storage.get_item(STORAGE_KEY) {
                     if let Ok(state) = serde_json::from_str::<TerminalState>(&json) {
-                        return state;
+                        // Older versions also kept sessions or tabs here, which
+                        // are now the window's: keep history and definitions
+                        return TerminalState {
+                            schema_version: CURRENT_SCHEMA_VERSION,
+                            ..state
+                        };
                     }
                 }
             }
@@ -61,52 +111,141 @@ fn load_from_storage() -> TerminalState {
     TerminalState::default()
 }
 
-/// Find file/directory completions for tab completion
-fn find_completions(fs: &VirtualFileSystem, partial: &str, cwd: &str) -> Vec<String> {
-    // Determine the directory to search and the prefix to match
-    let (search_dir, prefix) = if partial.contains('/') {
-        let last_slash = partial.rfind('/').unwrap();
-        let dir_part = &partial[..=last_slash];
-        let file_part = &partial[last_slash + 1..];
+/// Command history and definitions, shared by every Terminal window and tab
+#[derive(Clone, Copy)]
+struct SharedShellState {
+    command_history: RwSignal<Vec<String>>,
+    definitions: RwSignal<ShellDefinitions>,
+}
 
-        let resolved_dir = if dir_part.starts_with('/') {
-            dir_part.trim_end_matches('/').to_string()
-        } else {
-            let base = if cwd == "/" {
-                String::new()
-            } else {
-                cwd.to_string()
-            };
-            format!("{}/{}", base, dir_part.trim_end_matches('/'))
-        };
+/// Provide the command history and definitions all Terminal windows share,
+/// saving them as any of them changes
+pub fn provide_terminal_context() {
+    let state = load_from_storage();
+    let shared = SharedShellState {
+        command_history: RwSignal::new(state.command_history),
+        definitions: RwSignal::new(state.definitions),
+    };
+    Effect::new(move |_| {
+        save_to_storage(&TerminalState {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            command_history: shared.command_history.get(),
+            definitions: shared.definitions.get(),
+        });
+    });
+    provide_context(shared);
+}
+
+/// A window's open tabs, in tab bar order
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct TabList {
+    tabs: Vec<TabState>,
+    active_tab: usize,
+    next_tab_id: usize,
+}
+
+impl Default for TabList {
+    fn default() -> Self {
+        Self {
+            tabs: vec![TabState::new(1, "/".to_string())],
+            active_tab: 1,
+            next_tab_id: 2,
+        }
+    }
+}
+
+/// The window's tabs and the scrollback of each by tab ID, kept with the
+/// window across reloads
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct TerminalWindowState {
+    #[serde(default)]
+    tabs: TabList,
+    scrollback: BTreeMap<usize, Vec<String>>,
+}
 
-        // Handle root directory specially
-        let resolved_dir = if resolved_dir.is_empty() {
-            "/".to_string()
+/// A file opened with `nano`
+#[derive(Clone, Debug)]
+struct EditorSession {
+    name: Option<String>,
+    content: Option<String>,
+}
+
+/// Text shown in the pager by `man` or `less`
+#[derive(Clone, Debug)]
+struct PagerSession {
+    text: String,
+    prompt: PagerPrompt,
+    ignore_case: bool,
+}
+
+/// State of a Ctrl+R reverse-i-search
+#[derive(Clone, Debug)]
+struct ReverseSearch {
+    query: String,
+    /// History index of the current match
+    match_index: Option<usize>,
+    /// Whether the last search step found nothing
+    failed: bool,
+    /// Input line to restore if the search is cancelled
+    original: String,
+}
+
+impl ReverseSearch {
+    /// Move to the newest event before `before` containing the query
+    fn search(&mut self, history: &[String], before: usize) {
+        let found = if self.query.is_empty() {
+            None
         } else {
-            resolved_dir
+            history[..before.min(history.len())]
+                .iter()
+                .rposition(|event| event.contains(&self.query))
         };
+        self.failed = found.is_none() && !self.query.is_empty();
+        if found.is_some() {
+            self.match_index = found;
+        }
+    }
 
-        (resolved_dir, file_part.to_string())
+    /// The line the search would run: the match, or the original input
+    fn line(&self, history: &[String]) -> String {
+        self.match_index
+            .and_then(|i| history.get(i).cloned())
+            .unwrap_or_else(|| self.original.clone())
+    }
+}
+
+/// Find file/directory completions for tab completion. Each completion is
+/// the full replacement for `partial`, keeping whatever directory part
+/// (`../`, `~/`, ...) the user typed.
+fn find_completions(fs: &VirtualFileSystem, partial: &str, cwd: &str, home: &str) -> Vec<String> {
+    // Split into the directory part (kept verbatim) and the prefix to match
+    let (dir_part, prefix) = match partial.rfind('/') {
+        Some(last_slash) => partial.split_at(last_slash + 1),
+        None if partial == "~" => return vec!["~/".to_string()],
+        None => ("", partial),
+    };
+    let search_dir = if dir_part.is_empty() {
+        cwd.to_string()
     } else {
-        (cwd.to_string(), partial.to_string())
+        resolve_path(cwd, home, dir_part)
     };
 
-    // Get entries in the directory
-    let entries = fs.list_dir(&search_dir);
-
-    // Filter entries that start with prefix
-    entries
+    let mut completions: Vec<String> = fs
+        .list_dir(&search_dir)
         .iter()
-        .filter(|e| e.metadata.name.starts_with(&prefix))
+        .filter(|e| e.metadata.name.starts_with(prefix))
+        // Hidden files are only offered once the user types the leading `.`
+        .filter(|e| !e.metadata.name.starts_with('.') || prefix.starts_with('.'))
         .map(|e| {
             if e.is_directory() {
-                format!("{}/", e.metadata.name)
+                format!("{}{}/", dir_part, e.metadata.name)
             } else {
-                e.metadata.name.clone()
+                format!("{}{}", dir_part, e.metadata.name)
             }
         })
-        .collect()
+        .collect();
+    completions.sort();
+    completions
 }
 
 /// Find the common prefix among a list of strings
@@ -133,31 +272,133 @@ fn find_common_prefix(strings: &[String]) -> String {
     first.chars().take(prefix_len).collect()
 }
 
-/// Terminal component with simulated shell
+/// Render an output line, turning ANSI color codes into styled spans
+fn render_line(line: &str) -> AnyView {
+    if !line.contains('\x1b') {
+        return line.to_string().into_any();
+    }
+    ansi::parse(line, &mut ansi::Style::default())
+        .into_iter()
+        .map(|span| view! { <span style=span.style.css()>{span.text}</span> })
+        .collect_view()
+        .into_any()
+}
+
+/// Render the reverse-i-search line, highlighting the match
+fn search_view(search: &ReverseSearch, history: &[String]) -> impl IntoView {
+    let label = format!(
+        "({}reverse-i-search)`{}': ",
+        if search.failed { "failed " } else { "" },
+        search.query
+    );
+    let line = search.line(history);
+    let found = search
+        .match_index
+        .filter(|_| !search.query.is_empty())
+        .and_then(|_| line.rfind(&search.query));
+    let (before, matched, after) = match found {
+        Some(start) => {
+            let end = start + search.query.len();
+            (
+                line[..start].to_string(),
+                line[start..end].to_string(),
+                line[end..].to_string(),
+            )
+        }
+        None => (line, String::new(), String::new()),
+    };
+    view! {
+        <span class="terminal-search">
+            {label}
+            {before}
+            <span class="terminal-search-match">{matched}</span>
+            {after}
+        </span>
+    }
+}
+
+/// One tab's shell session: scrollback, prompt and interpreter
 #[component]
-pub fn Terminal() -> impl IntoView {
+fn TerminalSession(
+    tab: TabState,
+    /// Whether this is the tab currently shown
+    #[prop(into)]
+    active: Signal<bool>,
+    /// Command history shared by all tabs
+    command_history: RwSignal<Vec<String>>,
+    /// Aliases and functions shared by all tabs
+    definitions: RwSignal<ShellDefinitions>,
+    /// Each tab's scrollback, saved with the window
+    scrollback: RwSignal<BTreeMap<usize, Vec<String>>>,
+    /// Called with the new cwd and environment after a command changes them
+    #[prop(into)]
+    on_change: Callback<(String, BTreeMap<String, String>)>,
+    /// Called when the shell exits
+    #[prop(into)]
+    on_exit: Callback<()>,
+) -> impl IntoView {
     let system_state = expect_context::<SystemState>();
     let notification_state = expect_context::<NotificationState>();
+    let processes = expect_context::<ProcessTable>();
+    let clipboard = expect_context::<Clipboard>();
+    let preferences = expect_context::<Preferences>();
 
-    // Load persisted state from localStorage
-    let (terminal_state, set_terminal_state) = signal(load_from_storage());
+    // The login shell behind this tab, which `kill` can end
+    let (pid, tty) = processes.spawn_shell();
+    on_cleanup(move || processes.exit(pid));
 
-    let (history, set_history) = signal(vec![
-        String::from("Last login: Thu Jan 16 09:00:00 on ttys000"),
-        String::new(),
-    ]);
+    // Pick up the scrollback from before a reload
+    let tab_id = tab.id;
+    let (history, set_history) = signal(
+        scrollback
+            .with_untracked(|saved| saved.get(&tab_id).cloned())
+            .unwrap_or_else(|| {
+                vec![
+                    format!("Last login: Thu Jan 16 09:00:00 on {}", tty),
+                    String::new(),
+                ]
+            }),
+    );
+    Effect::new(move |_| {
+        let lines = history
+            .with(|lines| lines[lines.len().saturating_sub(MAX_SAVED_SCROLLBACK)..].to_vec());
+        scrollback.update(|saved| {
+            saved.insert(tab_id, lines);
+        });
+    });
     let (input, set_input) = signal(String::new());
-    // Initialize cwd from persisted state
-    let (cwd, set_cwd) = signal(terminal_state.get_untracked().cwd);
+    let (cwd, set_cwd) = signal(tab.cwd);
+    // Session environment, with PWD kept in sync with the restored cwd
+    let (env, set_env) = signal({
+        let mut env = tab.env;
+        env.insert("PWD".to_string(), cwd.get_untracked());
+        env
+    });
 
-    // Command history for up/down arrow navigation - initialized from persisted state
-    let (command_history, set_command_history) =
-        signal::<Vec<String>>(terminal_state.get_untracked().command_history);
+    // Position while navigating the shared history with up/down arrows
     let (history_index, set_history_index) = signal::<Option<usize>>(None);
+    let (search, set_search) = signal::<Option<ReverseSearch>>(None);
     let (saved_input, set_saved_input) = signal::<String>(String::new());
 
     let fs = use_file_system();
     let fs_for_keydown = fs.clone();
+    // Interpreter for this session
+    let shell = StoredValue::new(Shell::new(
+        fs,
+        notification_state,
+        processes,
+        clipboard,
+        preferences,
+        env.get_untracked(),
+        cwd.get_untracked(),
+    ));
+    // File open in the full-screen editor, if any
+    let (editor_session, set_editor_session) = signal::<Option<EditorSession>>(None);
+    // Text open in the full-screen pager, if any
+    let (pager_session, set_pager_session) = signal::<Option<PagerSession>>(None);
+    // Whether the editor or pager has taken over the tab
+    let full_screen =
+        move || editor_session.with(Option::is_some) || pager_session.with(Option::is_some);
     let input_ref: NodeRef<leptos::html::Input> = NodeRef::new();
     let output_ref: NodeRef<leptos::html::Div> = NodeRef::new();
 
@@ -172,240 +413,154 @@ pub fn Terminal() -> impl IntoView {
         }
     });
 
-    // Auto-focus input when Terminal becomes the active app
+    // Auto-focus input when Terminal becomes the active app or this tab is selected
     Effect::new(move |_| {
         let active_app = system_state.active_app.get();
-        if active_app == "Terminal" {
+        if active_app == "Terminal" && active.get() {
             if let Some(input_el) = input_ref.get() {
                 let _ = input_el.focus();
             }
         }
     });
 
-    // Auto-save terminal state to localStorage
-    Effect::new(move |_| {
-        let current_state = terminal_state.get();
-        save_to_storage(&current_state);
+    // Return focus to the prompt when the editor or pager closes
+    Effect::new(move |prev: Option<bool>| {
+        let covered = full_screen();
+        if prev == Some(true) && !covered {
+            if let Some(input_el) = input_ref.get_untracked() {
+                let _ = input_el.focus();
+            }
+        }
+        covered
     });
 
     let prompt = move || {
-        let path = cwd.get();
-        let display = if path == "/" {
-            "~".to_string()
-        } else {
-            path.rsplit('/').next().unwrap_or(&path).to_string()
-        };
-        format!("guest@virtualmac {} % ", display)
+        let env = env.get();
+        let ps1 = env.get("PS1").cloned().unwrap_or_default();
+        expand_prompt(&ps1, &env, &cwd.get())
     };
 
-    let execute_command = move |cmd: String| {
-        let trimmed = cmd.trim().to_string();
-        let parts: Vec<&str> = trimmed.split_whitespace().collect();
-        if parts.is_empty() {
-            return;
+    // Close the tab when its shell is killed
+    Effect::new(move |_| {
+        if !processes.is_running(pid) {
+            on_exit.run(());
         }
+    });
 
-        // Add to command history for up/down navigation
-        set_command_history.update(|h| {
-            // Don't add duplicates of the last command
-            if h.last().map(|s| s.as_str()) != Some(&trimmed) {
-                h.push(trimmed.clone());
-                // Enforce history limit
-                if h.len() > MAX_COMMAND_HISTORY {
-                    h.remove(0);
-                }
+    // Render a command's output and pick up any cwd/env changes it made
+    let apply_output = move |output: ShellOutput| {
+        set_history.update(|h| {
+            if output.effects.contains(&ShellEffect::ClearScreen) {
+                h.clear();
             }
+            h.extend(ansi::split_lines(&output.text));
         });
-        // Persist command history
-        set_terminal_state.update(|state| {
-            state.command_history = command_history.get_untracked();
+        let mut exited = false;
+        for effect in output.effects {
+            match effect {
+                ShellEffect::Edit { name, content } => {
+                    set_editor_session.set(Some(EditorSession { name, content }));
+                }
+                ShellEffect::Page {
+                    text,
+                    prompt,
+                    ignore_case,
+                } => {
+                    set_pager_session.set(Some(PagerSession {
+                        text,
+                        prompt,
+                        ignore_case,
+                    }));
+                }
+                ShellEffect::Exit => exited = true,
+                ShellEffect::Open { app, path } => {
+                    system_state.request_open(&app.to_string(), path.as_deref());
+                }
+                ShellEffect::ClearScreen => {}
+            }
+        }
+        let changed = shell.with_value(|sh| {
+            let mut changed = false;
+            if sh.cwd() != cwd.get_untracked() {
+                set_cwd.set(sh.cwd().to_string());
+                changed = true;
+            }
+            if *sh.env() != env.get_untracked() {
+                set_env.set(sh.env().clone());
+                changed = true;
+            }
+            changed
         });
-        // Reset history navigation state
-        set_history_index.set(None);
-        set_saved_input.set(String::new());
+        if changed {
+            on_change.run((cwd.get_untracked(), env.get_untracked()));
+        }
+        // Share new aliases and functions with the other tabs
+        let defined = shell.with_value(Shell::definitions);
+        if defined != definitions.get_untracked() {
+            definitions.set(defined);
+        }
+        if exited {
+            on_exit.run(());
+        }
+    };
+
+    // Restore saved aliases and functions, then source ~/.zshrc
+    if let Some(output) = shell.try_update_value(|sh| {
+        sh.set_definitions(&definitions.get_untracked());
+        sh.source_startup_files()
+    }) {
+        apply_output(output);
+    }
 
-        let command = parts[0];
-        let args: Vec<&str> = parts[1..].to_vec();
+    let execute_command = move |cmd: String| {
+        let trimmed = cmd.trim().to_string();
+        if trimmed.is_empty() {
+            return;
+        }
 
         // Add command to display history
         set_history.update(|h| {
             h.push(format!("{}{}", prompt(), cmd));
         });
+        // Reset history navigation state
+        set_history_index.set(None);
+        set_saved_input.set(String::new());
 
-        let current_path = cwd.get();
-
-        // Path resolution helper
-        let resolve_path = |target: &str, cwd_path: &str| -> String {
-            if target.starts_with('/') {
-                target.to_string()
-            } else if target == "~" {
-                "/".to_string()
-            } else if target == ".." {
-                let parts: Vec<&str> = cwd_path.split('/').collect();
-                if parts.len() > 2 {
-                    parts[..parts.len() - 1].join("/")
-                } else {
-                    "/".to_string()
-                }
-            } else if let Some(stripped) = target.strip_prefix("./") {
-                format!("{}/{}", cwd_path, stripped)
-            } else if cwd_path == "/" {
-                format!("/{}", target)
-            } else {
-                format!("{}/{}", cwd_path, target)
+        // Expand !!, !n and !prefix against the history before this line
+        let line = match expand_history(&trimmed, &command_history.get_untracked()) {
+            Ok(Some(expanded)) => {
+                // Like zsh, show the expanded line before running it
+                set_history.update(|h| h.push(expanded.clone()));
+                expanded
             }
-        };
-
-        let output = match command {
-            "clear" => {
-                set_history.set(Vec::new());
+            Ok(None) => trimmed,
+            Err(message) => {
+                set_history.update(|h| h.push(format!("zsh: {}", message)));
                 return;
             }
-            "pwd" => current_path.clone(),
-            "echo" => args.join(" "),
-            "whoami" => String::from("guest"),
-            "hostname" => String::from("virtualmac"),
-            "date" => get_current_date(),
-            "ls" => {
-                let target_path = if args.is_empty() {
-                    current_path.clone()
-                } else {
-                    resolve_path(args[0], &current_path)
-                };
-
-                if !fs.exists(&target_path) {
-                    format!("ls: {}: No such file or directory", args.first().unwrap_or(&""))
-                } else {
-                    match fs.get(&target_path) {
-                        Some(entry) if entry.is_file() => {
-                            format!("ls: {}: Not a directory", args.first().unwrap_or(&""))
-                        }
-                        _ => {
-                            let entries = fs.list_dir(&target_path);
-                            if entries.is_empty() {
-                                String::new()
-                            } else {
-                                let mut names: Vec<String> = entries.iter()
-                                    .map(|e| {
-                                        if matches!(e.metadata.entry_type, EntryType::Directory) {
-                                            format!("{}/", e.metadata.name)
-                                        } else {
-                                            e.metadata.name.clone()
-                                        }
-                                    })
-                                    .collect();
-                                names.sort();
-                                names.join("  ")
-                            }
-                        }
-                    }
-                }
-            }
-            "cd" => {
-                if args.is_empty() {
-                    set_cwd.set(String::from("/"));
-                    // Persist cwd change
-                    set_terminal_state.update(|state| {
-                        state.cwd = "/".to_string();
-                    });
-                    return;
-                }
-                let target = args[0];
-                let new_path = resolve_path(target, &current_path);
-
-                match fs.get(&new_path) {
-                    Some(entry) if entry.is_directory() => {
-                        set_cwd.set(new_path.clone());
-                        // Persist cwd change
-                        set_terminal_state.update(|state| {
-                            state.cwd = new_path;
-                        });
-                        return;
-                    }
-                    Some(_) => format!("cd: not a directory: {}", target),
-                    None => format!("cd: no such file or directory: {}", target),
-                }
-            }
-            "cat" => {
-                if args.is_empty() {
-                    String::from("usage: cat <file>")
-                } else {
-                    let target = resolve_path(args[0], &current_path);
-                    match fs.get(&target) {
-                        Some(entry) if entry.is_directory() => {
-                            format!("cat: {}: Is a directory", args[0])
-                        }
-                        Some(entry) => {
-                            entry.content.unwrap_or_else(String::new)
-                        }
-                        None => format!("cat: {}: No such file or directory", args[0]),
-                    }
-                }
-            }
-            "mkdir" => {
-                if args.is_empty() {
-                    String::from("usage: mkdir <directory>")
-                } else {
-                    let target = resolve_path(args[0], &current_path);
-                    if fs.exists(&target) {
-                        format!("mkdir: {}: File exists", args[0])
-                    } else {
-                        fs.create_dir(&target);
-                        String::new()
-                    }
-                }
-            }
-            "rm" => {
-                if args.is_empty() {
-                    String::from("usage: rm <file>")
-                } else {
-                    let target = resolve_path(args[0], &current_path);
-                    if !fs.exists(&target) {
-                        format!("rm: {}: No such file or directory", args[0])
-                    } else {
-                        fs.delete(&target);
-                        String::new()
-                    }
-                }
-            }
-            "touch" => {
-                if args.is_empty() {
-                    String::from("usage: touch <file>")
-                } else {
-                    let target = resolve_path(args[0], &current_path);
-                    if !fs.exists(&target) {
-                        fs.write_file(&target, "", "📄");
-                    }
-                    String::new()
-                }
-            }
-            "help" => String::from("Available commands: ls, cd, pwd, echo, cat, mkdir, rm, touch, clear, whoami, hostname, date, notify, help"),
-            "notify" => {
-                if args.is_empty() {
-                    String::from("usage: notify <title> [message]")
-                } else {
-                    let title = args[0].to_string();
-                    let message = if args.len() > 1 {
-                        args[1..].join(" ")
-                    } else {
-                        String::new()
-                    };
-                    notification_state.show(title, message);
-                    String::new()
-                }
-            }
-            _ => format!("command not found: {}", command),
         };
 
-        set_history.update(|h| {
-            for line in output.lines() {
-                h.push(line.to_string());
-            }
-            // Add empty line for commands with no output
-            if output.is_empty() && command != "clear" {
-                // Don't add anything for empty output
+        // Add to command history for up/down navigation
+        command_history.update(|h| {
+            // Don't add duplicates of the last command
+            if h.last().map(|s| s.as_str()) != Some(&line) {
+                h.push(line.clone());
+                // Enforce history limit
+                if h.len() > MAX_COMMAND_HISTORY {
+                    h.remove(0);
+                }
             }
         });
+
+        let events = command_history.get_untracked();
+        let shared = definitions.get_untracked();
+        if let Some(output) = shell.try_update_value(|sh| {
+            sh.set_history(events);
+            sh.set_definitions(&shared);
+            sh.execute(&line)
+        }) {
+            apply_output(output);
+        }
     };
 
     let on_keydown = move |e: KeyboardEvent| {
@@ -416,6 +571,73 @@ pub fn Terminal() -> impl IntoView {
             return;
         }
 
+        // Ctrl+R starts a reverse-i-search, or steps to an older match
+        if e.ctrl_key() && e.key() == "r" {
+            e.prevent_default();
+            let hist = command_history.get_untracked();
+            let next = match search.get_untracked() {
+                Some(mut current) => {
+                    let before = current.match_index.unwrap_or(hist.len());
+                    current.search(&hist, before);
+                    current
+                }
+                None => ReverseSearch {
+                    query: String::new(),
+                    match_index: None,
+                    failed: false,
+                    original: input.get_untracked(),
+                },
+            };
+            set_search.set(Some(next));
+            return;
+        }
+
+        if let Some(mut current) = search.get_untracked() {
+            // Leave Cmd shortcuts (new tab, ...) to the window
+            if e.meta_key() {
+                return;
+            }
+            let hist = command_history.get_untracked();
+            let key = e.key();
+            match key.as_str() {
+                "Enter" => {
+                    e.prevent_default();
+                    set_search.set(None);
+                    set_input.set(String::new());
+                    execute_command(current.line(&hist));
+                }
+                "Backspace" => {
+                    e.prevent_default();
+                    current.query.pop();
+                    current.match_index = None;
+                    current.search(&hist, hist.len());
+                    set_search.set(Some(current));
+                }
+                // Ctrl+G / Ctrl+C abandon the search and restore the line
+                "g" | "c" if e.ctrl_key() => {
+                    e.prevent_default();
+                    set_search.set(None);
+                    set_input.set(current.original);
+                }
+                _ if key.chars().count() == 1 && !e.ctrl_key() => {
+                    e.prevent_default();
+                    current.query.push_str(&key);
+                    // Stay on the current match if it still fits
+                    let before = current.match_index.map_or(hist.len(), |i| i + 1);
+                    current.search(&hist, before);
+                    set_search.set(Some(current));
+                }
+                "Shift" | "Control" | "Alt" => {}
+                // Any other key accepts the match for editing
+                _ => {
+                    e.prevent_default();
+                    set_search.set(None);
+                    set_input.set(current.line(&hist));
+                }
+            }
+            return;
+        }
+
         match e.key().as_str() {
             "ArrowUp" => {
                 e.prevent_default();
@@ -490,7 +712,13 @@ pub fn Terminal() -> impl IntoView {
                     (current_input[..prefix_end].to_string(), *word)
                 };
 
-                let completions = find_completions(&fs_for_keydown, completing_word, &current_cwd);
+                let home = env
+                    .get()
+                    .get("HOME")
+                    .cloned()
+                    .unwrap_or_else(|| "/".to_string());
+                let completions =
+                    find_completions(&fs_for_keydown, completing_word, &current_cwd, &home);
 
                 match completions.len() {
                     0 => {} // No completions - do nothing
@@ -508,10 +736,13 @@ pub fn Terminal() -> impl IntoView {
                             let new_input = format!("{}{}", prefix, common);
                             set_input.set(new_input);
                         } else {
-                            // Show all options in output
+                            // Show all options in output, without the directory part
+                            let dir_len = completing_word.rfind('/').map_or(0, |i| i + 1);
+                            let names: Vec<&str> =
+                                completions.iter().map(|c| &c[dir_len..]).collect();
                             set_history.update(|h| {
                                 h.push(format!("{}{}", prompt(), current_input));
-                                h.push(completions.join("  "));
+                                h.push(names.join("  "));
                             });
                         }
                     }
@@ -535,16 +766,66 @@ pub fn Terminal() -> impl IntoView {
     };
 
     view! {
-        <div class="terminal" on:click=on_terminal_click>
-            <div class="terminal-output" node_ref=output_ref>
+        <div
+            class="terminal-session"
+            style:display=move || if active.get() { "" } else { "none" }
+            on:click=on_terminal_click
+        >
+            <Show when=move || editor_session.with(Option::is_some)>
+                {move || {
+                    editor_session
+                        .get()
+                        .map(|session| {
+                            let home = env
+                                .get_untracked()
+                                .get("HOME")
+                                .cloned()
+                                .unwrap_or_else(|| "/".to_string());
+                            view! {
+                                <TerminalEditor
+                                    name=session.name
+                                    content=session.content
+                                    cwd=cwd.get_untracked()
+                                    home=home
+                                    on_exit=move || set_editor_session.set(None)
+                                />
+                            }
+                        })
+                }}
+            </Show>
+            <Show when=move || pager_session.with(Option::is_some)>
+                {move || {
+                    pager_session
+                        .get()
+                        .map(|session| {
+                            view! {
+                                <TerminalPager
+                                    text=session.text
+                                    prompt=session.prompt
+                                    ignore_case=session.ignore_case
+                                    on_exit=move || set_pager_session.set(None)
+                                />
+                            }
+                        })
+                }}
+            </Show>
+            <div
+                class="terminal-output"
+                node_ref=output_ref
+                style:display=move || if full_screen() { "none" } else { "" }
+            >
                 <For
                     each=history_items
                     key=|(i, _)| *i
                     children=move |(_, line)| {
-                        view! { <div class="terminal-line">{line.clone()}</div> }
+                        view! { <div class="terminal-line">{render_line(&line)}</div> }
                     }
                 />
-                <div class="terminal-input-line">
+                <div
+                    class="terminal-input-line"
+                    class:searching=move || search.with(Option::is_some)
+                >
+                    {move || search.get().map(|current| search_view(&current, &command_history.get()))}
                     <span class="terminal-prompt">{prompt}</span>
                     <input
                         type="text"
@@ -552,6 +833,7 @@ pub fn Terminal() -> impl IntoView {
                         prop:value=move || input.get()
                         on:input=on_input
                         on:keydown=on_keydown
+                        on:paste=move |e| clipboard.handle_paste(&e)
                         autofocus=true
                         node_ref=input_ref
                     />
@@ -561,28 +843,218 @@ pub fn Terminal() -> impl IntoView {
     }
 }
 
-fn get_current_date() -> String {
-    #[cfg(target_arch = "wasm32")]
-    {
-        let date = js_sys::Date::new_0();
-        let days = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
-        let months = [
-            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
-        ];
-
-        format!(
-            "{} {} {:2} {:02}:{:02}:{:02} UTC {}",
-            days[date.get_day() as usize],
-            months[date.get_month() as usize],
-            date.get_date(),
-            date.get_hours(),
-            date.get_minutes(),
-            date.get_seconds(),
-            date.get_full_year()
-        )
-    }
-    #[cfg(not(target_arch = "wasm32"))]
-    {
-        "Thu Jan 16 12:00:00 UTC 2026".to_string()
+/// Terminal window: a tab bar over one session per tab
+#[component]
+pub fn Terminal() -> impl IntoView {
+    // History and definitions are shared by every Terminal window, while
+    // the tabs belong to this one
+    let SharedShellState {
+        command_history,
+        definitions,
+    } = expect_context::<SharedShellState>();
+    let window_state = use_window_app_state();
+    let restored = window_state
+        .and_then(|w| w.restore::<TerminalWindowState>())
+        .unwrap_or_default();
+    let (tab_list, set_tab_list) = signal(if restored.tabs.tabs.is_empty() {
+        TabList::default()
+    } else {
+        restored.tabs
+    });
+    let scrollback = RwSignal::new(restored.scrollback);
+
+    // Keep the tabs and their scrollback with the window
+    Effect::new(move |_| {
+        let tabs = tab_list.get();
+        let state = TerminalWindowState {
+            scrollback: scrollback.with(|saved| {
+                saved
+                    .iter()
+                    .filter(|(id, _)| tabs.tabs.iter().any(|t| t.id == **id))
+                    .map(|(id, lines)| (*id, lines.clone()))
+                    .collect()
+            }),
+            tabs,
+        };
+        if let Some(window_state) = window_state {
+            window_state.save(&state);
+        }
+    });
+
+    // Open a tab after the active one, in the active tab's directory
+    let new_tab = move || {
+        set_tab_list.update(|state| {
+            let id = state.next_tab_id.max(1);
+            state.next_tab_id = id + 1;
+            let position = state
+                .tabs
+                .iter()
+                .position(|t| t.id == state.active_tab)
+                .map_or(state.tabs.len(), |i| i + 1);
+            let cwd = state
+                .tabs
+                .get(position.wrapping_sub(1))
+                .map_or_else(|| "/".to_string(), |t| t.cwd.clone());
+            state.tabs.insert(position, TabState::new(id, cwd));
+            state.active_tab = id;
+        });
+    };
+
+    // Close a tab, selecting its neighbour; closing the last tab starts a
+    // fresh session in its place
+    let close_tab = move |id: usize| {
+        set_tab_list.update(|state| {
+            let Some(index) = state.tabs.iter().position(|t| t.id == id) else {
+                return;
+            };
+            state.tabs.remove(index);
+            if state.tabs.is_empty() {
+                let id = state.next_tab_id.max(1);
+                state.next_tab_id = id + 1;
+                state.tabs.push(TabState::new(id, "/".to_string()));
+            }
+            if state.active_tab == id || !state.tabs.iter().any(|t| t.id == state.active_tab) {
+                state.active_tab = state.tabs[index.min(state.tabs.len() - 1)].id;
+            }
+        });
+    };
+
+    let select_tab = move |id: usize| {
+        set_tab_list.update(|state| state.active_tab = id);
+    };
+
+    // Select the tab `offset` places from the active one, wrapping around
+    let cycle_tab = move |offset: isize| {
+        set_tab_list.update(|state| {
+            let count = state.tabs.len() as isize;
+            if let Some(index) = state.tabs.iter().position(|t| t.id == state.active_tab) {
+                let next = (index as isize + offset).rem_euclid(count) as usize;
+                state.active_tab = state.tabs[next].id;
+            }
+        });
+    };
+
+    // Tab shortcuts bubble up from the active session's input
+    let on_keydown = move |e: KeyboardEvent| {
+        if e.ctrl_key() && e.key() == "Tab" {
+            e.prevent_default();
+            cycle_tab(if e.shift_key() { -1 } else { 1 });
+            return;
+        }
+        if !e.meta_key() {
+            return;
+        }
+        let tab_count = tab_list.with_untracked(|s| s.tabs.len());
+        match e.key().as_str() {
+            "t" | "T" => {
+                e.prevent_default();
+                new_tab();
+            }
+            // With several tabs Cmd+W closes the tab rather than the window
+            "w" | "W" if tab_count > 1 => {
+                e.prevent_default();
+                e.stop_propagation();
+                close_tab(tab_list.with_untracked(|s| s.active_tab));
+            }
+            "}" | "]" if e.shift_key() => {
+                e.prevent_default();
+                cycle_tab(1);
+            }
+            "{" | "[" if e.shift_key() => {
+                e.prevent_default();
+                cycle_tab(-1);
+            }
+            key @ ("1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9") => {
+                e.prevent_default();
+                let tabs = tab_list.with_untracked(|s| s.tabs.clone());
+                // Cmd+9 always selects the last tab
+                let tab = match key.parse::<usize>() {
+                    Ok(9) => tabs.last(),
+                    Ok(n) => tabs.get(n - 1),
+                    Err(_) => None,
+                };
+                if let Some(tab) = tab {
+                    select_tab(tab.id);
+                }
+            }
+            _ => {}
+        }
+    };
+
+    let tab_ids = move || tab_list.with(|s| s.tabs.iter().map(|t| t.id).collect::<Vec<_>>());
+
+    view! {
+        <div class="terminal" on:keydown=on_keydown>
+            <Show when=move || tab_list.with(|s| s.tabs.len() > 1)>
+                <div class="terminal-tabs">
+                    <For
+                        each=tab_ids
+                        key=|id| *id
+                        children=move |id| {
+                            let title = move || {
+                                tab_list
+                                    .with(|s| {
+                                        s.tabs.iter().find(|t| t.id == id).map(TabState::title)
+                                    })
+                                    .unwrap_or_default()
+                            };
+                            view! {
+                                <div
+                                    class="terminal-tab"
+                                    class:active=move || {
+                                        tab_list.with(|s| s.active_tab == id)
+                                    }
+                                    on:click=move |_| select_tab(id)
+                                >
+                                    <span
+                                        class="terminal-tab-close"
+                                        on:click=move |e| {
+                                            e.stop_propagation();
+                                            close_tab(id);
+                                        }
+                                    >
+                                        "×"
+                                    </span>
+                                    <span class="terminal-tab-title">{title}</span>
+                                </div>
+                            }
+                        }
+                    />
+                    <div class="terminal-tab-new" title="New Tab" on:click=move |_| new_tab()>
+                        "+"
+                    </div>
+                </div>
+            </Show>
+            <For
+                each=tab_ids
+                key=|id| *id
+                children=move |id| {
+                    let tab = tab_list
+                        .with_untracked(|s| s.tabs.iter().find(|t| t.id == id).cloned())
+                        .unwrap_or_else(|| TabState::new(id, "/".to_string()));
+                    let on_change = move |cwd: String, env: BTreeMap<String, String>| {
+                        set_tab_list.update(|state| {
+                            if let Some(tab) = state.tabs.iter_mut().find(|t| t.id == id) {
+                                tab.cwd = cwd;
+                                tab.env = env;
+                            }
+                        });
+                    };
+                    view! {
+                        <TerminalSession
+                            tab=tab
+                            active=Signal::derive(move || {
+                                tab_list.with(|s| s.active_tab == id)
+                            })
+                            command_history=command_history
+                            definitions=definitions
+                            scrollback=scrollback
+                            on_change=on_change
+                            on_exit=move || close_tab(id)
+                        />
+                    }
+                }
+            />
+        </div>
     }
 }
@@ -0,0 +1,608 @@
+use crate::file_system::use_file_system;
+use crate::shell::resolve_path;
+use leptos::ev::KeyboardEvent;
+use leptos::prelude::*;
+
+/// Height of one editor row in pixels (matches `.nano-line` in styles.css)
+const LINE_HEIGHT_PX: i32 = 16;
+
+/// What the status line is currently asking for
+#[derive(Clone, Debug, PartialEq)]
+enum PromptKind {
+    /// ^O, optionally exiting once the file is written
+    WriteOut { then_exit: bool },
+    /// ^W
+    Search,
+    /// ^X with unsaved changes
+    SaveOnExit,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Prompt {
+    kind: PromptKind,
+    input: String,
+}
+
+/// Work the component must do after a key press
+enum Outcome {
+    None,
+    Write { name: String, exit: bool },
+    Exit,
+}
+
+/// Text buffer and cursor state for the nano-style editor
+#[derive(Clone, Debug)]
+struct Editor {
+    /// File name as typed by the user (resolved against the cwd on write)
+    name: Option<String>,
+    lines: Vec<Vec<char>>,
+    row: usize,
+    col: usize,
+    /// Column the cursor tries to keep when moving between lines
+    goal_col: usize,
+    /// First visible row
+    top: usize,
+    modified: bool,
+    cut_buffer: Vec<Vec<char>>,
+    /// Whether the previous key was ^K, so consecutive cuts accumulate
+    cutting: bool,
+    last_search: String,
+    prompt: Option<Prompt>,
+    status: Option<String>,
+}
+
+impl Editor {
+    fn new(name: Option<String>, content: Option<String>) -> Self {
+        let status = match (&name, &content) {
+            (Some(_), Some(content)) => Some(format!("[ Read {} lines ]", content.lines().count())),
+            (Some(_), None) => Some("[ New File ]".to_string()),
+            (None, _) => None,
+        };
+        let lines = content
+            .unwrap_or_default()
+            .split('\n')
+            .map(|line| line.chars().collect())
+            .collect();
+        Self {
+            name,
+            lines,
+            row: 0,
+            col: 0,
+            goal_col: 0,
+            top: 0,
+            modified: false,
+            cut_buffer: Vec::new(),
+            cutting: false,
+            last_search: String::new(),
+            prompt: None,
+            status,
+        }
+    }
+
+    fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| line.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn title(&self) -> String {
+        match &self.name {
+            Some(name) => format!("File: {}", name.rsplit('/').next().unwrap_or(name)),
+            None => "New Buffer".to_string(),
+        }
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].len()
+    }
+
+    fn set_col(&mut self, col: usize) {
+        self.col = col;
+        self.goal_col = col;
+    }
+
+    fn move_to_row(&mut self, row: usize) {
+        self.row = row.min(self.lines.len() - 1);
+        self.col = self.goal_col.min(self.line_len());
+    }
+
+    /// Keep the cursor row inside the visible window
+    fn scroll_to_cursor(&mut self, rows: usize) {
+        let rows = rows.max(1);
+        if self.row < self.top {
+            self.top = self.row;
+        } else if self.row >= self.top + rows {
+            self.top = self.row + 1 - rows;
+        }
+    }
+
+    fn insert_char(&mut self, c: char) {
+        let col = self.col;
+        self.lines[self.row].insert(col, c);
+        self.set_col(col + 1);
+        self.modified = true;
+    }
+
+    fn insert_newline(&mut self) {
+        let rest = self.lines[self.row].split_off(self.col);
+        self.lines.insert(self.row + 1, rest);
+        self.row += 1;
+        self.set_col(0);
+        self.modified = true;
+    }
+
+    fn backspace(&mut self) {
+        if self.col > 0 {
+            self.lines[self.row].remove(self.col - 1);
+            self.set_col(self.col - 1);
+            self.modified = true;
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.set_col(self.line_len());
+            self.lines[self.row].extend(line);
+            self.modified = true;
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.col < self.line_len() {
+            self.lines[self.row].remove(self.col);
+            self.modified = true;
+        } else if self.row + 1 < self.lines.len() {
+            let next = self.lines.remove(self.row + 1);
+            self.lines[self.row].extend(next);
+            self.modified = true;
+        }
+    }
+
+    fn cut_line(&mut self, continuing: bool) {
+        if !continuing {
+            self.cut_buffer.clear();
+        }
+        if self.lines.len() == 1 {
+            self.cut_buffer.push(std::mem::take(&mut self.lines[0]));
+        } else {
+            self.cut_buffer.push(self.lines.remove(self.row));
+            self.row = self.row.min(self.lines.len() - 1);
+        }
+        self.set_col(0);
+        self.modified = true;
+    }
+
+    fn paste(&mut self) {
+        if self.cut_buffer.is_empty() {
+            return;
+        }
+        for (offset, line) in self.cut_buffer.iter().enumerate() {
+            self.lines.insert(self.row + offset, line.clone());
+        }
+        self.row += self.cut_buffer.len();
+        self.set_col(0);
+        self.modified = true;
+    }
+
+    /// Case-insensitive search forward from the cursor, wrapping at the end
+    fn search(&mut self, query: &str) {
+        let needle: Vec<char> = query.chars().map(|c| c.to_ascii_lowercase()).collect();
+        let find_in = |line: &[char], from: usize| -> Option<usize> {
+            if needle.len() > line.len() {
+                return None;
+            }
+            (from..=line.len() - needle.len()).find(|&start| {
+                line[start..start + needle.len()]
+                    .iter()
+                    .zip(&needle)
+                    .all(|(a, b)| a.to_ascii_lowercase() == *b)
+            })
+        };
+
+        let count = self.lines.len();
+        for step in 0..=count {
+            let row = (self.row + step) % count;
+            let from = if step == 0 { self.col + 1 } else { 0 };
+            if let Some(col) = find_in(&self.lines[row], from) {
+                let only_occurrence = step == count && col == self.col;
+                let wrapped = step > 0 && row <= self.row;
+                self.row = row;
+                self.set_col(col);
+                self.status = if only_occurrence {
+                    Some("[ This is the only occurrence ]".to_string())
+                } else if wrapped {
+                    Some("[ Search Wrapped ]".to_string())
+                } else {
+                    None
+                };
+                return;
+            }
+        }
+        self.status = Some(format!("[ \"{}\" not found ]", query));
+    }
+
+    fn cursor_position(&self) -> String {
+        let percent = |part: usize, whole: usize| part * 100 / whole.max(1);
+        format!(
+            "[ line {}/{} ({}%), col {}/{} ({}%) ]",
+            self.row + 1,
+            self.lines.len(),
+            percent(self.row + 1, self.lines.len()),
+            self.col + 1,
+            self.line_len() + 1,
+            percent(self.col + 1, self.line_len() + 1),
+        )
+    }
+
+    /// Record a successful ^O
+    fn written(&mut self, name: String) {
+        self.status = Some(format!("[ Wrote {} lines ]", self.text().lines().count()));
+        self.name = Some(name);
+        self.modified = false;
+    }
+
+    fn handle_key(&mut self, key: &str, ctrl: bool, rows: usize) -> Outcome {
+        if self.prompt.is_some() {
+            return self.handle_prompt_key(key, ctrl);
+        }
+        self.status = None;
+        let continuing_cut = std::mem::take(&mut self.cutting);
+
+        let outcome = match (ctrl, key) {
+            (true, "o") | (true, "O") => {
+                self.prompt = Some(Prompt {
+                    kind: PromptKind::WriteOut { then_exit: false },
+                    input: self.name.clone().unwrap_or_default(),
+                });
+                Outcome::None
+            }
+            (true, "x") | (true, "X") => {
+                if self.modified {
+                    self.prompt = Some(Prompt {
+                        kind: PromptKind::SaveOnExit,
+                        input: String::new(),
+                    });
+                    Outcome::None
+                } else {
+                    Outcome::Exit
+                }
+            }
+            (true, "w") | (true, "W") => {
+                self.prompt = Some(Prompt {
+                    kind: PromptKind::Search,
+                    input: String::new(),
+                });
+                Outcome::None
+            }
+            (true, "k") | (true, "K") => {
+                self.cut_line(continuing_cut);
+                self.cutting = true;
+                Outcome::None
+            }
+            (true, "u") | (true, "U") => {
+                self.paste();
+                Outcome::None
+            }
+            (true, "c") | (true, "C") => {
+                self.status = Some(self.cursor_position());
+                Outcome::None
+            }
+            (true, "a") | (true, "A") | (_, "Home") => {
+                self.set_col(0);
+                Outcome::None
+            }
+            (true, "e") | (true, "E") | (_, "End") => {
+                self.set_col(self.line_len());
+                Outcome::None
+            }
+            (true, "y") | (true, "Y") | (_, "PageUp") => {
+                self.top = self.top.saturating_sub(rows);
+                self.move_to_row(self.row.saturating_sub(rows));
+                Outcome::None
+            }
+            (true, "v") | (true, "V") | (_, "PageDown") => {
+                self.top = (self.top + rows).min(self.lines.len() - 1);
+                self.move_to_row(self.row + rows);
+                Outcome::None
+            }
+            (_, "ArrowUp") => {
+                self.move_to_row(self.row.saturating_sub(1));
+                Outcome::None
+            }
+            (_, "ArrowDown") => {
+                self.move_to_row(self.row + 1);
+                Outcome::None
+            }
+            (_, "ArrowLeft") => {
+                if self.col > 0 {
+                    self.set_col(self.col - 1);
+                } else if self.row > 0 {
+                    self.row -= 1;
+                    self.set_col(self.line_len());
+                }
+                Outcome::None
+            }
+            (_, "ArrowRight") => {
+                if self.col < self.line_len() {
+                    self.set_col(self.col + 1);
+                } else if self.row + 1 < self.lines.len() {
+                    self.row += 1;
+                    self.set_col(0);
+                }
+                Outcome::None
+            }
+            (false, "Enter") => {
+                self.insert_newline();
+                Outcome::None
+            }
+            (false, "Backspace") => {
+                self.backspace();
+                Outcome::None
+            }
+            (false, "Delete") => {
+                self.delete();
+                Outcome::None
+            }
+            (false, "Tab") => {
+                self.insert_char('\t');
+                Outcome::None
+            }
+            (false, key) if key.chars().count() == 1 => {
+                self.insert_char(key.chars().next().unwrap_or(' '));
+                Outcome::None
+            }
+            _ => Outcome::None,
+        };
+        self.scroll_to_cursor(rows);
+        outcome
+    }
+
+    fn handle_prompt_key(&mut self, key: &str, ctrl: bool) -> Outcome {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return Outcome::None;
+        };
+        let cancel = key == "Escape" || (ctrl && matches!(key, "c" | "C"));
+
+        if prompt.kind == PromptKind::SaveOnExit {
+            let outcome = match key {
+                "y" | "Y" => match self.name.clone() {
+                    Some(name) => Outcome::Write { name, exit: true },
+                    None => {
+                        prompt.kind = PromptKind::WriteOut { then_exit: true };
+                        return Outcome::None;
+                    }
+                },
+                "n" | "N" => Outcome::Exit,
+                _ if cancel || matches!(key, "c" | "C") => {
+                    self.status = Some("[ Cancelled ]".to_string());
+                    Outcome::None
+                }
+                _ => return Outcome::None,
+            };
+            self.prompt = None;
+            return outcome;
+        }
+
+        if cancel {
+            self.prompt = None;
+            self.status = Some("[ Cancelled ]".to_string());
+            return Outcome::None;
+        }
+        match key {
+            "Enter" => {
+                let Some(Prompt { kind, input }) = self.prompt.take() else {
+                    return Outcome::None;
+                };
+                match kind {
+                    PromptKind::WriteOut { then_exit } if !input.is_empty() => Outcome::Write {
+                        name: input,
+                        exit: then_exit,
+                    },
+                    PromptKind::Search => {
+                        let query = if input.is_empty() {
+                            self.last_search.clone()
+                        } else {
+                            input
+                        };
+                        if query.is_empty() {
+                            self.status = Some("[ Cancelled ]".to_string());
+                        } else {
+                            self.search(&query);
+                            self.last_search = query;
+                        }
+                        Outcome::None
+                    }
+                    _ => {
+                        self.status = Some("[ Cancelled ]".to_string());
+                        Outcome::None
+                    }
+                }
+            }
+            "Backspace" => {
+                prompt.input.pop();
+                Outcome::None
+            }
+            key if !ctrl && key.chars().count() == 1 => {
+                prompt.input.push_str(key);
+                Outcome::None
+            }
+            _ => Outcome::None,
+        }
+    }
+
+    fn prompt_label(&self) -> Option<String> {
+        let prompt = self.prompt.as_ref()?;
+        Some(match prompt.kind {
+            PromptKind::WriteOut { .. } => "File Name to Write: ".to_string(),
+            PromptKind::Search if self.last_search.is_empty() => "Search: ".to_string(),
+            PromptKind::Search => format!("Search [{}]: ", self.last_search),
+            PromptKind::SaveOnExit => {
+                "Save modified buffer (ANSWERING \"No\" WILL DESTROY CHANGES) ? ".to_string()
+            }
+        })
+    }
+}
+
+/// Full-screen nano-style text editor shown inside the Terminal
+#[component]
+pub fn TerminalEditor(
+    /// File name as typed after `nano`, if any
+    name: Option<String>,
+    /// Current file content; `None` for a new file
+    content: Option<String>,
+    /// Directory used to resolve the file name when writing
+    cwd: String,
+    home: String,
+    #[prop(into)] on_exit: Callback<()>,
+) -> impl IntoView {
+    let fs = use_file_system();
+    let (editor, set_editor) = signal(Editor::new(name, content));
+    let editor_ref: NodeRef<leptos::html::Div> = NodeRef::new();
+    let body_ref: NodeRef<leptos::html::Div> = NodeRef::new();
+
+    // Take keyboard focus when opened
+    Effect::new(move |_| {
+        if let Some(el) = editor_ref.get() {
+            let _ = el.focus();
+        }
+    });
+
+    let visible_rows = move || {
+        body_ref
+            .get_untracked()
+            .map(|el| (el.client_height() / LINE_HEIGHT_PX).max(1) as usize)
+            .unwrap_or(20)
+    };
+
+    let on_keydown = move |e: KeyboardEvent| {
+        // Leave Cmd shortcuts to the window manager
+        if e.meta_key() {
+            return;
+        }
+        e.prevent_default();
+        e.stop_propagation();
+
+        let rows = visible_rows();
+        let mut outcome = Outcome::None;
+        set_editor.update(|ed| outcome = ed.handle_key(&e.key(), e.ctrl_key(), rows));
+
+        match outcome {
+            Outcome::Write { name, exit } => {
+                let path = resolve_path(&cwd, &home, &name);
+                let parent = match path.rfind('/') {
+                    Some(0) | None => "/".to_string(),
+                    Some(idx) => path[..idx].to_string(),
+                };
+                let writable = fs.get(&parent).is_some_and(|e| e.is_directory())
+                    && !fs.get(&path).is_some_and(|e| e.is_directory());
+                if !writable {
+                    set_editor.update(|ed| {
+                        ed.status = Some(format!(
+                            "[ Error writing {}: No such file or directory ]",
+                            name
+                        ));
+                    });
+                    return;
+                }
+                fs.write_file(&path, &editor.with_untracked(Editor::text), "📄");
+                set_editor.update(|ed| ed.written(name));
+                if exit {
+                    on_exit.run(());
+                }
+            }
+            Outcome::Exit => on_exit.run(()),
+            Outcome::None => {}
+        }
+    };
+
+    let on_click = move |e: leptos::ev::MouseEvent| {
+        e.stop_propagation();
+        if let Some(el) = editor_ref.get() {
+            let _ = el.focus();
+        }
+    };
+
+    let lines = move || {
+        let ed = editor.get();
+        let show_cursor = ed.prompt.is_none();
+        ed.lines
+            .iter()
+            .enumerate()
+            .skip(ed.top)
+            .map(|(row, line)| {
+                if show_cursor && row == ed.row {
+                    let before: String = line[..ed.col].iter().collect();
+                    let at: String = line.get(ed.col).map_or(" ".to_string(), |c| c.to_string());
+                    let after: String = line.get(ed.col + 1..).unwrap_or(&[]).iter().collect();
+                    view! {
+                        <div class="nano-line">
+                            {before}
+                            <span class="nano-cursor">{at}</span>
+                            {after}
+                        </div>
+                    }
+                    .into_any()
+                } else {
+                    let text: String = line.iter().collect();
+                    view! { <div class="nano-line">{text}</div> }.into_any()
+                }
+            })
+            .collect_view()
+    };
+
+    let status_line = move || {
+        let ed = editor.get();
+        match ed.prompt_label() {
+            Some(label) => {
+                let input = ed.prompt.map(|p| p.input).unwrap_or_default();
+                view! {
+                    <div class="nano-status nano-prompt">
+                        {label}
+                        {input}
+                        <span class="nano-cursor">" "</span>
+                    </div>
+                }
+                .into_any()
+            }
+            None => view! {
+                <div class="nano-status">
+                    <span class="nano-status-message">{ed.status}</span>
+                </div>
+            }
+            .into_any(),
+        }
+    };
+
+    let shortcut = |key: &'static str, label: &'static str| {
+        view! {
+            <span class="nano-shortcut">
+                <span class="nano-shortcut-key">{key}</span>
+                {label}
+            </span>
+        }
+    };
+
+    view! {
+        <div class="nano-editor" tabindex="0" node_ref=editor_ref on:keydown=on_keydown on:click=on_click>
+            <div class="nano-header">
+                <span>"GNU nano 2.0.6"</span>
+                <span class="nano-title">{move || editor.get().title()}</span>
+                <span>{move || if editor.get().modified { "Modified" } else { "" }}</span>
+            </div>
+            <div class="nano-body" node_ref=body_ref>{lines}</div>
+            {status_line}
+            <div class="nano-shortcuts">
+                {shortcut("^O", "WriteOut")}
+                {shortcut("^W", "Where Is")}
+                {shortcut("^K", "Cut Text")}
+                {shortcut("^A", "Line Start")}
+                {shortcut("^Y", "Prev Page")}
+                {shortcut("^X", "Exit")}
+                {shortcut("^C", "Cur Pos")}
+                {shortcut("^U", "UnCut Text")}
+                {shortcut("^E", "Line End")}
+                {shortcut("^V", "Next Page")}
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,331 @@
+use crate::ansi;
+use crate::pattern::Regex;
+use crate::shell::PagerPrompt;
+use leptos::ev::{KeyboardEvent, WheelEvent};
+use leptos::prelude::*;
+
+/// Height of one pager row in pixels (matches `.pager-line` in styles.css)
+const LINE_HEIGHT_PX: i32 = 16;
+
+/// Lines scrolled per mouse wheel notch
+const WHEEL_LINES: usize = 3;
+
+/// The last pattern searched for, repeated by `n` and `N`
+#[derive(Clone, Debug)]
+struct Search {
+    regex: Regex,
+    forward: bool,
+}
+
+/// Scroll position and search state for the less-style pager
+#[derive(Clone, Debug)]
+struct Pager {
+    /// Lines as written, with their escape sequences
+    lines: Vec<String>,
+    /// The same lines as plain text, for searching
+    plain: Vec<String>,
+    /// First visible line
+    top: usize,
+    /// The `/` or `?` search being typed
+    prompt: Option<(char, String)>,
+    search: Option<Search>,
+    /// Shown on the status line until the next key
+    message: Option<String>,
+    ignore_case: bool,
+}
+
+impl Pager {
+    fn new(text: &str, ignore_case: bool) -> Self {
+        let lines = ansi::split_lines(text);
+        let plain = lines.iter().map(|line| ansi::strip(line)).collect();
+        Self {
+            lines,
+            plain,
+            top: 0,
+            prompt: None,
+            search: None,
+            message: None,
+            ignore_case,
+        }
+    }
+
+    /// Highest top line that still fills the screen
+    fn max_top(&self, rows: usize) -> usize {
+        self.lines.len().saturating_sub(rows.max(1))
+    }
+
+    fn at_end(&self, rows: usize) -> bool {
+        self.top + rows >= self.lines.len()
+    }
+
+    fn scroll_down(&mut self, count: usize, rows: usize) {
+        // A search can leave the top past the last full screen; stay there
+        let max = self.max_top(rows);
+        if self.top < max {
+            self.top = (self.top + count).min(max);
+        }
+    }
+
+    fn scroll_up(&mut self, count: usize) {
+        self.top = self.top.saturating_sub(count);
+    }
+
+    /// Move to the next line matching the search, below (or above) the top
+    /// line
+    fn find(&mut self, forward: bool) {
+        let Some(search) = &self.search else {
+            self.message = Some("No previous regular expression".to_string());
+            return;
+        };
+        let forward = forward == search.forward;
+        let found = if forward {
+            (self.top + 1..self.plain.len()).find(|&row| search.regex.is_match(&self.plain[row]))
+        } else {
+            (0..self.top)
+                .rev()
+                .find(|&row| search.regex.is_match(&self.plain[row]))
+        };
+        match found {
+            Some(row) => self.top = row,
+            None => self.message = Some("Pattern not found".to_string()),
+        }
+    }
+
+    fn handle_prompt_key(&mut self, key: &str) {
+        let Some((kind, input)) = &mut self.prompt else {
+            return;
+        };
+        match key {
+            "Enter" => {
+                let (kind, input) = (*kind, std::mem::take(input));
+                self.prompt = None;
+                // An empty pattern repeats the previous search
+                if !input.is_empty() {
+                    match Regex::new(&input, self.ignore_case) {
+                        Ok(regex) => {
+                            self.search = Some(Search {
+                                regex,
+                                forward: kind == '/',
+                            })
+                        }
+                        Err(error) => {
+                            self.message = Some(error);
+                            return;
+                        }
+                    }
+                }
+                self.find(true);
+            }
+            "Escape" => self.prompt = None,
+            "Backspace" if input.pop().is_none() => self.prompt = None,
+            key if key.chars().count() == 1 => input.push_str(key),
+            _ => {}
+        }
+    }
+
+    /// Apply a key press; returns true when the pager should close
+    fn handle_key(&mut self, key: &str, ctrl: bool, rows: usize) -> bool {
+        if self.prompt.is_some() {
+            self.handle_prompt_key(key);
+            return false;
+        }
+        if self.message.take().is_some() && key == "Enter" {
+            return false;
+        }
+        let half = (rows / 2).max(1);
+        match (ctrl, key) {
+            (false, "q") | (false, "Q") => return true,
+            (false, "j") | (false, "e") | (_, "ArrowDown") | (_, "Enter") | (true, "n") => {
+                self.scroll_down(1, rows)
+            }
+            (false, "k") | (false, "y") | (_, "ArrowUp") | (true, "p") => self.scroll_up(1),
+            (false, " ") | (false, "f") | (_, "PageDown") | (true, "f") | (true, "v") => {
+                self.scroll_down(rows, rows)
+            }
+            (false, "b") | (_, "PageUp") | (true, "b") => self.scroll_up(rows),
+            (false, "d") | (true, "d") => self.scroll_down(half, rows),
+            (false, "u") | (true, "u") => self.scroll_up(half),
+            (false, "g") | (false, "<") | (_, "Home") => self.top = 0,
+            (false, "G") | (false, ">") | (_, "End") => self.top = self.max_top(rows),
+            (false, "/") | (false, "?") => {
+                self.prompt = Some((key.chars().next().unwrap_or('/'), String::new()))
+            }
+            (false, "n") => self.find(true),
+            (false, "N") => self.find(false),
+            _ => {}
+        }
+        false
+    }
+
+    /// Status line text when no search is being typed
+    fn status(&self, prompt: &PagerPrompt, rows: usize) -> String {
+        if let Some(message) = &self.message {
+            return format!("{}  (press RETURN)", message);
+        }
+        match prompt {
+            PagerPrompt::Manual(title) => format!(
+                "{} line {}{} (press q to quit)",
+                title,
+                self.top + 1,
+                if self.at_end(rows) { " (END)" } else { "" }
+            ),
+            _ if self.at_end(rows) => "(END)".to_string(),
+            PagerPrompt::File(Some(name)) if self.top == 0 => name.clone(),
+            PagerPrompt::File(_) => ":".to_string(),
+        }
+    }
+}
+
+/// Render a line, styling its escape sequences and highlighting matches of
+/// the current search
+fn render_line(line: &str, plain: &str, search: Option<&Regex>) -> impl IntoView {
+    let matches: Vec<(usize, usize)> = search
+        .map(|regex| {
+            regex
+                .find_iter(plain)
+                .filter(|(start, end)| end > start)
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut pieces: Vec<(String, String, bool)> = Vec::new();
+    let mut offset = 0;
+    for span in ansi::parse(line, &mut ansi::Style::default()) {
+        let css = span.style.css();
+        for c in span.text.chars() {
+            let highlighted = matches
+                .iter()
+                .any(|&(start, end)| offset >= start && offset < end);
+            match pieces.last_mut() {
+                Some((text, style, matched)) if *style == css && *matched == highlighted => {
+                    text.push(c)
+                }
+                _ => pieces.push((c.to_string(), css.clone(), highlighted)),
+            }
+            offset += c.len_utf8();
+        }
+    }
+    let spans = pieces
+        .into_iter()
+        .map(|(text, style, matched)| {
+            // The match highlight replaces the line's own colors
+            let style = if matched { String::new() } else { style };
+            view! { <span style=style class:pager-match=matched>{text}</span> }
+        })
+        .collect_view();
+    view! { <div class="pager-line">{spans}</div> }
+}
+
+#[component]
+pub fn TerminalPager(
+    /// Text to page through, which may contain ANSI styling
+    text: String,
+    /// What the status line describes
+    prompt: PagerPrompt,
+    /// Whether searches ignore case
+    ignore_case: bool,
+    #[prop(into)] on_exit: Callback<()>,
+) -> impl IntoView {
+    let (pager, set_pager) = signal(Pager::new(&text, ignore_case));
+    let pager_ref: NodeRef<leptos::html::Div> = NodeRef::new();
+    let body_ref: NodeRef<leptos::html::Div> = NodeRef::new();
+    // Rows on screen, measured once mounted
+    let (rows, set_rows) = signal(20usize);
+
+    let visible_rows = move || {
+        body_ref
+            .get_untracked()
+            .map(|el| (el.client_height() / LINE_HEIGHT_PX).max(1) as usize)
+            .unwrap_or(20)
+    };
+
+    // Take keyboard focus when opened
+    Effect::new(move |_| {
+        if let Some(el) = pager_ref.get() {
+            let _ = el.focus();
+            set_rows.set(visible_rows());
+        }
+    });
+
+    let on_keydown = move |e: KeyboardEvent| {
+        // Leave Cmd shortcuts to the window manager
+        if e.meta_key() {
+            return;
+        }
+        e.prevent_default();
+        e.stop_propagation();
+
+        let visible = visible_rows();
+        set_rows.set(visible);
+        let mut quit = false;
+        set_pager.update(|p| quit = p.handle_key(&e.key(), e.ctrl_key(), visible));
+        if quit {
+            on_exit.run(());
+        }
+    };
+
+    let on_wheel = move |e: WheelEvent| {
+        e.prevent_default();
+        let visible = visible_rows();
+        set_rows.set(visible);
+        set_pager.update(|p| {
+            if e.delta_y() > 0.0 {
+                p.scroll_down(WHEEL_LINES, visible);
+            } else if e.delta_y() < 0.0 {
+                p.scroll_up(WHEEL_LINES);
+            }
+        });
+    };
+
+    let on_click = move |e: leptos::ev::MouseEvent| {
+        e.stop_propagation();
+        if let Some(el) = pager_ref.get() {
+            let _ = el.focus();
+        }
+    };
+
+    let lines = move || {
+        let p = pager.get();
+        let search = p.search.as_ref().map(|s| &s.regex);
+        p.lines
+            .iter()
+            .zip(&p.plain)
+            .skip(p.top)
+            .take(rows.get())
+            .map(|(line, plain)| render_line(line, plain, search))
+            .collect_view()
+    };
+
+    let status_line = move || {
+        let p = pager.get();
+        match &p.prompt {
+            Some((kind, input)) => view! {
+                <div class="pager-status">
+                    {kind.to_string()}
+                    {input.clone()}
+                    <span class="pager-cursor">" "</span>
+                </div>
+            }
+            .into_any(),
+            None => view! {
+                <div class="pager-status">
+                    <span class="pager-status-message">{p.status(&prompt, rows.get())}</span>
+                </div>
+            }
+            .into_any(),
+        }
+    };
+
+    view! {
+        <div
+            class="terminal-pager"
+            tabindex="0"
+            node_ref=pager_ref
+            on:keydown=on_keydown
+            on:wheel=on_wheel
+            on:click=on_click
+        >
+            <div class="pager-body" node_ref=body_ref>{lines}</div>
+            {status_line}
+        </div>
+    }
+}
@@ -1,10 +1,16 @@
-use leptos::ev::MouseEvent;
+use leptos::ev::{KeyboardEvent, MouseEvent};
 use leptos::html::Div;
 use leptos::prelude::*;
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 
+use crate::clipboard::Clipboard;
+use crate::file_system::{use_file_system, VirtualFileSystem};
+use crate::sheet::{use_window_sheet, ButtonRole, SheetButton, WindowSheet};
+use crate::system_state::SystemState;
+use crate::window_manager::use_window_app_state;
+
 #[allow(dead_code)]
 const STORAGE_KEY: &str = "virtualmac_textedit";
 #[allow(dead_code)]
@@ -64,6 +70,25 @@ fn load_from_storage() -> TextEditState {
     TextEditState::default()
 }
 
+/// Ask where to save a new document, and whether to replace a file already
+/// there
+async fn choose_save_path(fs: &VirtualFileSystem, sheet: WindowSheet) -> Option<String> {
+    let path = sheet.save_panel("Untitled.txt", "/Documents").await?;
+    if fs.exists(&path) {
+        let name = path.rsplit('/').next().unwrap_or_default();
+        let title = format!("“{}” already exists. Do you want to replace it?", name);
+        let buttons = vec![
+            SheetButton::new("Cancel", ButtonRole::Cancel),
+            SheetButton::new("Replace", ButtonRole::Default),
+        ];
+        let message = "A file with the same name already exists. Replacing it will overwrite its current contents.";
+        if sheet.alert(&title, message, buttons).await != Some(1) {
+            return None;
+        }
+    }
+    Some(path)
+}
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = document)]
@@ -112,6 +137,16 @@ pub fn TextEdit() -> impl IntoView {
         }
     });
 
+    // File the document was opened from or saved to, and whether it has
+    // changed since
+    let document_path = RwSignal::new(None::<String>);
+    let edited = RwSignal::new(false);
+
+    let fs = use_file_system();
+    let system_state = expect_context::<SystemState>();
+    let clipboard = expect_context::<Clipboard>();
+    let window_sheet = use_window_sheet();
+
     // Apply toolbar settings to editor context after content restoration
     Effect::new({
         move |_| {
@@ -145,6 +180,114 @@ pub fn TextEdit() -> impl IntoView {
         save_to_storage(&current_state);
     });
 
+    // Write the document to its file, asking where first if it has none
+    let save = move |fs: VirtualFileSystem| async move {
+        let path = match document_path.get_untracked() {
+            Some(path) => path,
+            None => choose_save_path(&fs, window_sheet?).await?,
+        };
+        let text = doc_ref.get_untracked()?.inner_text();
+        fs.write_file(&path, &text, "📄");
+        document_path.set(Some(path));
+        edited.set(false);
+        Some(())
+    };
+
+    let fs_for_save = fs.clone();
+    let on_keydown = move |e: KeyboardEvent| {
+        if (e.meta_key() || e.ctrl_key()) && e.key().eq_ignore_ascii_case("s") {
+            e.prevent_default();
+            let fs = fs_for_save.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                save(fs).await;
+            });
+        }
+    };
+
+    // Ask whether to save unsaved changes before the document goes away:
+    // `Some(true)` once saved, `Some(false)` to drop them and `None` to keep
+    // the document
+    let ask_to_save = move |sheet: WindowSheet, fs: VirtualFileSystem| async move {
+        let name = document_path
+            .get_untracked()
+            .and_then(|path| path.rsplit('/').next().map(str::to_string))
+            .unwrap_or_else(|| "Untitled".to_string());
+        let title = format!(
+            "Do you want to save the changes made to the document “{}”?",
+            name
+        );
+        let buttons = vec![
+            SheetButton::new("Don’t Save", ButtonRole::Normal),
+            SheetButton::new("Cancel", ButtonRole::Cancel),
+            SheetButton::new("Save", ButtonRole::Default),
+        ];
+        let message = "Your changes will be lost if you don’t save them.";
+        match sheet.alert(&title, message, buttons).await {
+            Some(0) => Some(false),
+            Some(2) => save(fs).await.map(|()| true),
+            _ => None,
+        }
+    };
+
+    // Ask about unsaved changes before the window closes
+    if let Some(sheet) = window_sheet {
+        let fs = fs.clone();
+        sheet.guard_close(move || {
+            if !edited.get_untracked() {
+                return false;
+            }
+            let fs = fs.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let Some(saved) = ask_to_save(sheet, fs).await else {
+                    return;
+                };
+                if !saved {
+                    // Drop the draft too, so the changes don't come back
+                    let mut state = textedit_state.get_untracked();
+                    state.content = String::new();
+                    save_to_storage(&state);
+                }
+                sheet.close();
+            });
+            true
+        });
+    }
+
+    // Load a file opened in this window from elsewhere (e.g. `open
+    // notes.txt` in Terminal), first asking about unsaved changes to the
+    // one it replaces
+    let window_id = use_window_app_state().map(|state| state.window_id());
+    Effect::new(move |_| {
+        let Some(el) = doc_ref.get() else {
+            return;
+        };
+        let Some(path) = window_id.and_then(|id| system_state.take_open_document(id)) else {
+            return;
+        };
+        let fs = fs.clone();
+        let load = move |fs: &VirtualFileSystem| {
+            let text = fs.read_file(&path).unwrap_or_default();
+            el.set_inner_text(&text);
+            document_path.set(Some(path));
+            edited.set(false);
+            content_restored.set_value(true);
+            set_char_count.set(text.chars().count());
+            set_word_count.set(text.split_whitespace().count());
+            let content = el.inner_html();
+            set_textedit_state.update(|state| {
+                state.content = content;
+            });
+        };
+        match window_sheet.filter(|_| edited.get_untracked()) {
+            Some(sheet) => wasm_bindgen_futures::spawn_local(async move {
+                if ask_to_save(sheet, fs.clone()).await.is_some() {
+                    load(&fs);
+                }
+            }),
+            None => load(&fs),
+        }
+    });
+
     // Web-safe fonts that work across browsers
     const FONTS: &[(&str, &str)] = &[
         (
@@ -254,6 +397,7 @@ pub fn TextEdit() -> impl IntoView {
             set_textedit_state.update(|state| {
                 state.content = content;
             });
+            edited.set(true);
         }
     };
 
@@ -276,7 +420,7 @@ pub fn TextEdit() -> impl IntoView {
     }
 
     view! {
-        <div class="textedit">
+        <div class="textedit" on:keydown=on_keydown>
             <div class="textedit-toolbar">
                 <div class="textedit-toolbar-group">
                     <button
@@ -388,6 +532,7 @@ pub fn TextEdit() -> impl IntoView {
                     contenteditable="true"
                     node_ref=doc_ref
                     on:input=update_counts
+                    on:paste=move |e| clipboard.handle_paste(&e)
                     style=move || format!("font-size: {}px;", font_size.get())
                 >
                     // Content restored by Effect on mount
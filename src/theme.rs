@@ -1,5 +1,8 @@
+use crate::preferences::{Preferences, Value, GLOBAL_DOMAIN};
 use leptos::prelude::*;
 
+const INTERFACE_STYLE_KEY: &str = "AppleInterfaceStyle";
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Theme {
     Light,
@@ -24,14 +27,28 @@ impl Theme {
 
 #[derive(Clone, Copy)]
 pub struct ThemeContext {
-    pub theme: ReadSignal<Theme>,
-    pub set_theme: WriteSignal<Theme>,
+    pub theme: Memo<Theme>,
+    preferences: Preferences,
 }
 
 impl ThemeContext {
+    /// Store the appearance in the global domain, where `defaults` sees it
+    pub fn set(&self, theme: Theme) {
+        match theme {
+            Theme::Dark => self.preferences.set(
+                GLOBAL_DOMAIN,
+                INTERFACE_STYLE_KEY,
+                Value::String("Dark".to_string()),
+            ),
+            Theme::Light => {
+                self.preferences.delete(GLOBAL_DOMAIN, INTERFACE_STYLE_KEY);
+            }
+        }
+    }
+
     pub fn toggle(&self) {
         let current = self.theme.get();
-        self.set_theme.set(current.toggle());
+        self.set(current.toggle());
     }
 
     pub fn is_dark(&self) -> bool {
@@ -54,54 +71,29 @@ fn apply_theme_to_document(theme: Theme) {
     }
 }
 
-/// Load saved theme from localStorage
-fn load_saved_theme() -> Theme {
-    #[cfg(target_arch = "wasm32")]
-    {
-        if let Some(window) = web_sys::window() {
-            if let Ok(Some(storage)) = window.local_storage() {
-                if let Ok(Some(saved)) = storage.get_item("virtualmac-theme") {
-                    return match saved.as_str() {
-                        "dark" => Theme::Dark,
-                        _ => Theme::Light,
-                    };
-                }
-            }
-        }
-    }
-    Theme::Light
-}
-
-/// Save theme preference to localStorage
-#[allow(unused_variables)]
-fn save_theme(theme: Theme) {
-    #[cfg(target_arch = "wasm32")]
-    {
-        if let Some(window) = web_sys::window() {
-            if let Ok(Some(storage)) = window.local_storage() {
-                let _ = storage.set_item("virtualmac-theme", theme.as_str());
-            }
-        }
-    }
-}
-
 /// Provide theme context to the application
 #[component]
 pub fn ThemeProvider(children: Children) -> impl IntoView {
-    let initial_theme = load_saved_theme();
-    let (theme, set_theme) = signal(initial_theme);
+    let preferences = expect_context::<Preferences>();
+    // Like macOS, dark mode is AppleInterfaceStyle = Dark and light mode is
+    // the key being absent
+    let theme =
+        Memo::new(
+            move |_| match preferences.get_string(GLOBAL_DOMAIN, INTERFACE_STYLE_KEY) {
+                Some(style) if style.eq_ignore_ascii_case("dark") => Theme::Dark,
+                _ => Theme::Light,
+            },
+        );
 
     // Apply theme on mount
-    apply_theme_to_document(initial_theme);
+    apply_theme_to_document(theme.get_untracked());
 
     // Watch for theme changes and apply them
     Effect::new(move || {
-        let current_theme = theme.get();
-        apply_theme_to_document(current_theme);
-        save_theme(current_theme);
+        apply_theme_to_document(theme.get());
     });
 
-    let context = ThemeContext { theme, set_theme };
+    let context = ThemeContext { theme, preferences };
     provide_context(context);
 
     children()
@@ -1,5 +1,8 @@
+use crate::preferences::{Preferences, Value, DESKTOP_DOMAIN};
 use leptos::prelude::*;
 
+const BACKGROUND_KEY: &str = "Background";
+
 /// A wallpaper definition with id, name, and gradient
 #[derive(Clone, Debug, PartialEq)]
 pub struct Wallpaper {
@@ -81,16 +84,35 @@ pub const WALLPAPERS: &[Wallpaper] = &[
 /// Context for managing wallpaper state
 #[derive(Clone, Copy)]
 pub struct WallpaperContext {
-    pub current: ReadSignal<&'static str>,
-    pub set_current: WriteSignal<&'static str>,
+    pub current: Memo<&'static str>,
+    preferences: Preferences,
+}
+
+impl WallpaperContext {
+    pub fn set(&self, id: &'static str) {
+        self.preferences.set(
+            DESKTOP_DOMAIN,
+            BACKGROUND_KEY,
+            Value::String(id.to_string()),
+        );
+    }
 }
 
-/// Provide wallpaper context to the application
+/// Provide wallpaper context to the application, following the desktop
+/// domain's Background preference
 pub fn provide_wallpaper_context() -> WallpaperContext {
-    let (current, set_current) = signal(WALLPAPERS[0].id);
+    let preferences = expect_context::<Preferences>();
+    let current = Memo::new(move |_| {
+        let id = preferences.get_string(DESKTOP_DOMAIN, BACKGROUND_KEY);
+        WALLPAPERS
+            .iter()
+            .find(|w| Some(w.id) == id.as_deref())
+            .unwrap_or(&WALLPAPERS[0])
+            .id
+    });
     let ctx = WallpaperContext {
         current,
-        set_current,
+        preferences,
     };
     provide_context(ctx);
     ctx
@@ -1,6 +1,8 @@
 use leptos::ev::MouseEvent;
 use leptos::prelude::*;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::closure::Closure;
@@ -9,13 +11,48 @@ use wasm_bindgen::JsCast;
 
 /// Menu bar height in pixels (matches CSS --menubar-height)
 const MENU_BAR_HEIGHT: f64 = 25.0;
+/// Height the Dock takes along the bottom of the screen, kept clear of tiles
+const DOCK_HEIGHT: f64 = 82.0;
+/// How close to a screen edge the pointer must be to snap a dragged window
+const SNAP_EDGE: f64 = 6.0;
+/// How far from the top and bottom of a side edge snaps to a quarter
+const SNAP_CORNER: f64 = 80.0;
+/// How far a tiled window is dragged before it returns to its previous size
+const DRAG_OUT_DISTANCE: f64 = 4.0;
+/// Space Mission Control leaves around its grid and for the Dock below it
+const MISSION_CONTROL_MARGIN: f64 = 40.0;
+const MISSION_CONTROL_DOCK_SPACE: f64 = 110.0;
+/// Gap between Mission Control cells, and the height of each title
+const MISSION_CONTROL_GAP: f64 = 24.0;
+const MISSION_CONTROL_TITLE_HEIGHT: f64 = 24.0;
+/// How much of a window stays on screen when the viewport shrinks, so its
+/// title bar can still be grabbed
+const MIN_VISIBLE: f64 = 100.0;
+/// Width of the Stage Manager strip along the left edge, the margin around
+/// its cells and the most height a cell takes
+const STAGE_STRIP_WIDTH: f64 = 170.0;
+const STAGE_STRIP_MARGIN: f64 = 16.0;
+const STAGE_CELL_HEIGHT: f64 = 140.0;
+/// Room under each strip thumbnail for the app's name
+const STAGE_TITLE_HEIGHT: f64 = 20.0;
+/// How far each window of a strip group peeks out from behind the next
+const STAGE_GROUP_OFFSET: f64 = 6.0;
+/// Length of the Mission Control enter/exit transition in milliseconds
+#[allow(dead_code)]
+const MISSION_CONTROL_DURATION: i32 = 300;
+
+/// A window's position and size: (x, y, width, height)
+type Frame = (f64, f64, f64, f64);
 
 use crate::calculator::Calculator;
 use crate::finder::Finder;
 use crate::notes::Notes;
 use crate::notification::NotificationState;
+use crate::preferences::{Preferences, WINDOW_MANAGER_DOMAIN};
+use crate::process::ProcessTable;
+use crate::sheet::{SheetView, WindowSheet};
 use crate::system_settings::SystemSettings;
-use crate::system_state::{MinimizedWindow, SystemState};
+use crate::system_state::{ListedWindow, MinimizedWindow, OpenDocument, SystemState};
 use crate::terminal::Terminal;
 use crate::textedit::TextEdit;
 
@@ -28,6 +65,128 @@ pub enum WindowAction {
     CloseActive,
     HideActive,
     QuitAll,
+    /// Tile the active window to part of the screen
+    Tile(TileLayout),
+    /// Put a tiled or maximized window back to its frame from before
+    RestorePreviousSize,
+    /// Enter or leave Mission Control
+    MissionControl,
+    /// Switch this many Spaces to the right (negative for left)
+    SwitchSpace(i32),
+    /// Carry the active window this many Spaces to the right
+    MoveToSpace(i32),
+    /// Put the active window in or out of full screen
+    ToggleFullScreen,
+    /// Open another window of the active app
+    NewWindow,
+    /// Minimize the active window to the Dock
+    MinimizeActive,
+    /// Minimize every window of the active app
+    MinimizeAll,
+    /// Maximize or restore the active window
+    ZoomActive,
+    /// Hide every app but the active one
+    HideOthers,
+    /// Focus the active app's next window (negative for previous)
+    CycleWindows(i32),
+    /// Raise every window of the active app above other apps' windows
+    BringAllToFront,
+    /// Show and focus a window, from the Window menu
+    FocusWindow(WindowId),
+}
+
+/// Screen regions a window can be tiled to, as in Window > Move & Resize
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TileLayout {
+    Fill,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl TileLayout {
+    /// Frame (x, y, width, height) of this region for a viewport, between
+    /// the menu bar and the Dock, or down to the bottom edge while the Dock
+    /// is hidden in full screen
+    pub fn frame(self, (width, height): (f64, f64), dock_hidden: bool) -> Frame {
+        let top = MENU_BAR_HEIGHT;
+        let bottom = if dock_hidden { 0.0 } else { DOCK_HEIGHT };
+        let full_height = height - top - bottom;
+        let half_width = (width / 2.0).round();
+        let half_height = (full_height / 2.0).round();
+        let (right_width, bottom_height) = (width - half_width, full_height - half_height);
+        match self {
+            TileLayout::Fill => (0.0, top, width, full_height),
+            TileLayout::Left => (0.0, top, half_width, full_height),
+            TileLayout::Right => (half_width, top, right_width, full_height),
+            TileLayout::Top => (0.0, top, width, half_height),
+            TileLayout::Bottom => (0.0, top + half_height, width, bottom_height),
+            TileLayout::TopLeft => (0.0, top, half_width, half_height),
+            TileLayout::TopRight => (half_width, top, right_width, half_height),
+            TileLayout::BottomLeft => (0.0, top + half_height, half_width, bottom_height),
+            TileLayout::BottomRight => (half_width, top + half_height, right_width, bottom_height),
+        }
+    }
+
+    /// The region a window dragged to this pointer position snaps to: the
+    /// left and right edges give halves, their ends give quarters and the
+    /// top edge fills the screen
+    fn at_pointer(x: f64, y: f64, (width, height): (f64, f64)) -> Option<TileLayout> {
+        let at_left = x <= SNAP_EDGE;
+        let at_right = x >= width - SNAP_EDGE;
+        if at_left || at_right {
+            let upper = y <= MENU_BAR_HEIGHT + SNAP_CORNER;
+            let lower = y >= height - SNAP_CORNER;
+            return Some(match (at_left, upper, lower) {
+                (true, true, _) => TileLayout::TopLeft,
+                (true, _, true) => TileLayout::BottomLeft,
+                (true, _, _) => TileLayout::Left,
+                (false, true, _) => TileLayout::TopRight,
+                (false, _, true) => TileLayout::BottomRight,
+                (false, _, _) => TileLayout::Right,
+            });
+        }
+        (y <= SNAP_EDGE).then_some(TileLayout::Fill)
+    }
+}
+
+/// Control-Option shortcuts for tiling the active window
+#[cfg(target_arch = "wasm32")]
+fn tile_shortcut(code: &str) -> Option<WindowAction> {
+    let layout = match code {
+        "ArrowLeft" => TileLayout::Left,
+        "ArrowRight" => TileLayout::Right,
+        "ArrowUp" => TileLayout::Top,
+        "ArrowDown" => TileLayout::Bottom,
+        "KeyU" => TileLayout::TopLeft,
+        "KeyI" => TileLayout::TopRight,
+        "KeyJ" => TileLayout::BottomLeft,
+        "KeyK" => TileLayout::BottomRight,
+        "Enter" => TileLayout::Fill,
+        "Backspace" => return Some(WindowAction::RestorePreviousSize),
+        _ => return None,
+    };
+    Some(WindowAction::Tile(layout))
+}
+
+/// Size of the browser viewport, which windows tile within
+fn viewport_size() -> (f64, f64) {
+    #[cfg(target_arch = "wasm32")]
+    {
+        if let Some(window) = web_sys::window() {
+            let width = window.inner_width().ok().and_then(|w| w.as_f64());
+            let height = window.inner_height().ok().and_then(|h| h.as_f64());
+            if let (Some(width), Some(height)) = (width, height) {
+                return (width, height);
+            }
+        }
+    }
+    (1920.0, 1080.0)
 }
 
 /// Context for triggering window manager actions from outside the component
@@ -37,6 +196,59 @@ pub struct WindowManagerContext {
     pub action_trigger: WriteSignal<WindowAction>,
 }
 
+/// Handle the app in a window uses to keep an opaque blob of its own state
+/// (Finder's folder, Terminal's scrollback, ...) with the window, so it
+/// survives a reload
+#[derive(Clone, Copy)]
+pub struct WindowAppState {
+    window_id: WindowId,
+    windows: ReadSignal<Vec<WindowState>>,
+    set_windows: WriteSignal<Vec<WindowState>>,
+}
+
+impl WindowAppState {
+    /// The window the app is shown in
+    pub fn window_id(&self) -> WindowId {
+        self.window_id
+    }
+
+    /// The state last saved for this window, if it still parses as `T`
+    pub fn restore<T: DeserializeOwned>(&self) -> Option<T> {
+        self.windows.with_untracked(|windows| {
+            let json = windows
+                .iter()
+                .find(|w| w.id == self.window_id)?
+                .app_state
+                .as_deref()?;
+            serde_json::from_str(json).ok()
+        })
+    }
+
+    /// Save the app's state with the window
+    pub fn save<T: Serialize>(&self, state: &T) {
+        let Ok(json) = serde_json::to_string(state) else {
+            return;
+        };
+        let changed = self.windows.with_untracked(|windows| {
+            windows
+                .iter()
+                .any(|w| w.id == self.window_id && w.app_state.as_ref() != Some(&json))
+        });
+        if changed {
+            self.set_windows.update(|windows| {
+                if let Some(win) = windows.iter_mut().find(|w| w.id == self.window_id) {
+                    win.app_state = Some(json);
+                }
+            });
+        }
+    }
+}
+
+/// The state handle of the window the calling app is shown in
+pub fn use_window_app_state() -> Option<WindowAppState> {
+    use_context::<WindowAppState>()
+}
+
 /// Unique identifier for windows
 pub type WindowId = usize;
 
@@ -52,6 +264,23 @@ pub enum AppType {
 }
 
 impl AppType {
+    pub const ALL: [AppType; 6] = [
+        AppType::Finder,
+        AppType::Calculator,
+        AppType::Terminal,
+        AppType::TextEdit,
+        AppType::Notes,
+        AppType::SystemSettings,
+    ];
+
+    /// Find an app by its display name, ignoring case and a `.app` suffix
+    pub fn from_name(name: &str) -> Option<AppType> {
+        let name = name.strip_suffix(".app").unwrap_or(name);
+        Self::ALL
+            .into_iter()
+            .find(|app| app.to_string().eq_ignore_ascii_case(name))
+    }
+
     /// Get the icon character for this app type
     pub fn icon(&self) -> &'static str {
         match self {
@@ -64,6 +293,27 @@ impl AppType {
         }
     }
 
+    /// Smallest size a window of this app resizes to
+    pub fn min_size(&self) -> (f64, f64) {
+        match self {
+            AppType::Calculator => (240.0, 420.0),
+            AppType::SystemSettings => (560.0, 360.0),
+            AppType::Terminal => (320.0, 160.0),
+            AppType::TextEdit => (300.0, 200.0),
+            AppType::Notes => (480.0, 280.0),
+            AppType::Finder => (420.0, 240.0),
+        }
+    }
+
+    /// Largest size a window of this app resizes to, short of the viewport
+    pub fn max_size(&self) -> (f64, f64) {
+        match self {
+            AppType::Calculator => (360.0, 640.0),
+            AppType::SystemSettings => (1000.0, f64::INFINITY),
+            _ => (f64::INFINITY, f64::INFINITY),
+        }
+    }
+
     /// Get the CSS class for this app type's icon
     pub fn icon_class(&self) -> &'static str {
         match self {
@@ -112,14 +362,22 @@ pub struct WindowState {
     pub z_index: i32,
     pub is_minimized: bool,
     pub is_maximized: bool,
-    /// Stored position/size before maximizing
+    /// Full screen on a Space of its own, with the menu bar and Dock hidden
+    pub is_fullscreen: bool,
+    /// Stored position/size before maximizing or tiling
     pub pre_maximize: Option<(f64, f64, f64, f64)>,
+    /// Region the window is tiled to, if any
+    pub tile: Option<TileLayout>,
+    /// Index of the Space (desktop) the window is on
+    pub space: usize,
     /// Type of application in this window
     pub app_type: AppType,
     /// Current animation state
     pub animation: AnimationState,
     /// Target X position for minimize/restore animation (screen coordinates)
     pub animation_target_x: Option<f64>,
+    /// Serialized state of the app in the window, see [`WindowAppState`]
+    pub app_state: Option<String>,
 }
 
 impl WindowState {
@@ -142,11 +400,57 @@ impl WindowState {
             z_index: id as i32,
             is_minimized: false,
             is_maximized: false,
+            is_fullscreen: false,
             pre_maximize: None,
+            tile: None,
+            space: 0,
             app_type,
             animation: AnimationState::None,
             animation_target_x: None,
+            app_state: None,
+        }
+    }
+
+    /// Where the window is drawn; maximized and full-screen windows cover
+    /// the viewport
+    fn frame(&self, (width, height): (f64, f64)) -> Frame {
+        if self.is_maximized || self.is_fullscreen {
+            (0.0, 0.0, width, height)
+        } else {
+            (self.x, self.y, self.width, self.height)
+        }
+    }
+
+    /// Whether the window is in view on `space`: neither minimized nor
+    /// belonging to a hidden app
+    pub fn is_shown(&self, space: usize, hidden_apps: &[String]) -> bool {
+        !self.is_minimized
+            && self.space == space
+            && !hidden_apps.contains(&self.app_type.to_string())
+    }
+
+    /// Fit the window to a viewport: a tiled window re-tiles, any other
+    /// shrinks to fit within its app's limits and moves back until its
+    /// title bar is reachable
+    fn fit_to_viewport(&mut self, viewport: (f64, f64)) {
+        if let Some(layout) = self.tile {
+            (self.x, self.y, self.width, self.height) = layout.frame(viewport, self.is_fullscreen);
+            return;
         }
+        let (width, height) = viewport;
+        let (min_width, min_height) = self.app_type.min_size();
+        let (max_width, max_height) = self.app_type.max_size();
+        self.width = self.width.min(max_width).min(width).max(min_width);
+        self.height = self
+            .height
+            .min(max_height)
+            .min(height - MENU_BAR_HEIGHT)
+            .max(min_height);
+        self.x = self
+            .x
+            .min(width - MIN_VISIBLE)
+            .max(MIN_VISIBLE - self.width);
+        self.y = self.y.min(height - MIN_VISIBLE).max(MENU_BAR_HEIGHT);
     }
 
     /// Convert to persistable form
@@ -161,7 +465,11 @@ impl WindowState {
             z_index: self.z_index,
             is_minimized: self.is_minimized,
             is_maximized: self.is_maximized,
+            is_fullscreen: self.is_fullscreen,
             pre_maximize: self.pre_maximize,
+            tile: self.tile,
+            space: self.space,
+            app_state: self.app_state.clone(),
         }
     }
 
@@ -186,10 +494,14 @@ impl WindowState {
             z_index: persisted.z_index,
             is_minimized: persisted.is_minimized,
             is_maximized: persisted.is_maximized,
+            is_fullscreen: persisted.is_fullscreen,
             pre_maximize: persisted.pre_maximize,
+            tile: persisted.tile,
+            space: persisted.space,
             app_type: persisted.app_type.clone(),
             animation: AnimationState::None,
             animation_target_x: None,
+            app_state: persisted.app_state.clone(),
         }
     }
 }
@@ -206,7 +518,56 @@ struct PersistedWindow {
     z_index: i32,
     is_minimized: bool,
     is_maximized: bool,
+    #[serde(default)]
+    is_fullscreen: bool,
     pre_maximize: Option<(f64, f64, f64, f64)>,
+    #[serde(default)]
+    tile: Option<TileLayout>,
+    #[serde(default)]
+    space: usize,
+    /// Opaque state saved by the window's app
+    #[serde(default)]
+    app_state: Option<String>,
+}
+
+/// The Spaces windows are spread over, and the one on screen
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Spaces {
+    count: usize,
+    current: usize,
+    /// The window each full-screen Space belongs to, by index; rebuilt from
+    /// the windows on load since window IDs are reassigned
+    #[serde(skip)]
+    full_screen: Vec<Option<WindowId>>,
+}
+
+impl Spaces {
+    /// Point each Space at the full-screen window on it, if any
+    #[allow(dead_code)]
+    fn assign_full_screen(&mut self, windows: &[WindowState]) {
+        self.full_screen = (0..self.count)
+            .map(|space| {
+                windows
+                    .iter()
+                    .find(|w| w.is_fullscreen && w.space == space)
+                    .map(|w| w.id)
+            })
+            .collect();
+    }
+
+    fn is_full_screen(&self, index: usize) -> bool {
+        self.full_screen.get(index).is_some_and(Option::is_some)
+    }
+}
+
+impl Default for Spaces {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            current: 0,
+            full_screen: vec![None],
+        }
+    }
 }
 
 /// Full desktop state for persistence
@@ -217,6 +578,8 @@ struct PersistedDesktopState {
     windows: Vec<PersistedWindow>,
     next_window_id: usize,
     top_z_index: i32,
+    #[serde(default)]
+    spaces: Spaces,
 }
 
 #[allow(dead_code)]
@@ -226,7 +589,7 @@ const STORAGE_KEY: &str = "virtualmac_desktop";
 
 /// Save desktop state to localStorage
 #[allow(unused_variables)]
-fn save_desktop_state(windows: &[WindowState], next_id: usize, top_z: i32) {
+fn save_desktop_state(windows: &[WindowState], next_id: usize, top_z: i32, spaces: Spaces) {
     #[cfg(target_arch = "wasm32")]
     {
         let state = PersistedDesktopState {
@@ -234,6 +597,7 @@ fn save_desktop_state(windows: &[WindowState], next_id: usize, top_z: i32) {
             windows: windows.iter().map(|w| w.to_persisted()).collect(),
             next_window_id: next_id,
             top_z_index: top_z,
+            spaces,
         };
         if let Some(window) = web_sys::window() {
             if let Ok(Some(storage)) = window.local_storage() {
@@ -246,8 +610,8 @@ fn save_desktop_state(windows: &[WindowState], next_id: usize, top_z: i32) {
 }
 
 /// Load desktop state from localStorage
-/// Returns (windows, next_id, top_z, schema_mismatch)
-fn load_desktop_state() -> Option<(Vec<WindowState>, usize, i32, bool)> {
+/// Returns (windows, next_id, top_z, spaces, schema_mismatch)
+fn load_desktop_state() -> Option<(Vec<WindowState>, usize, i32, Spaces, bool)> {
     #[cfg(target_arch = "wasm32")]
     {
         if let Some(window) = web_sys::window() {
@@ -255,16 +619,28 @@ fn load_desktop_state() -> Option<(Vec<WindowState>, usize, i32, bool)> {
                 if let Ok(Some(json)) = storage.get_item(STORAGE_KEY) {
                     if let Ok(state) = serde_json::from_str::<PersistedDesktopState>(&json) {
                         let schema_mismatch = state.schema_version != CURRENT_SCHEMA_VERSION;
+                        let mut spaces = state.spaces.clone();
+                        spaces.count = spaces.count.max(1);
+                        spaces.current = spaces.current.min(spaces.count - 1);
+                        // Saved on a larger screen, windows may now be out of reach
+                        let viewport = viewport_size();
                         let windows: Vec<WindowState> = state
                             .windows
                             .iter()
                             .enumerate()
-                            .map(|(i, pw)| WindowState::from_persisted(pw, i + 1))
+                            .map(|(i, pw)| {
+                                let mut window = WindowState::from_persisted(pw, i + 1);
+                                window.space = window.space.min(spaces.count - 1);
+                                window.fit_to_viewport(viewport);
+                                window
+                            })
                             .collect();
+                        spaces.assign_full_screen(&windows);
                         return Some((
                             windows,
                             state.next_window_id,
                             state.top_z_index,
+                            spaces,
                             schema_mismatch,
                         ));
                     }
@@ -323,6 +699,141 @@ enum ResizeDirection {
     SW,
 }
 
+impl ResizeDirection {
+    /// The edges this handle moves: (left, right, top, bottom)
+    fn edges(self) -> (bool, bool, bool, bool) {
+        use ResizeDirection::*;
+        (
+            matches!(self, W | NW | SW),
+            matches!(self, E | NE | SE),
+            matches!(self, N | NE | NW),
+            matches!(self, S | SE | SW),
+        )
+    }
+}
+
+/// Mission Control phase; `Closing` runs the transition back to each
+/// window's own frame
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+enum MissionControl {
+    #[default]
+    Off,
+    Open,
+    Closing,
+}
+
+/// Thumbnail frames for Mission Control: the windows, in reading order of
+/// where they sit on screen, fill a grid of equal cells between the menu bar
+/// and the Dock, each scaled down to fit its cell with room for a title
+fn mission_control_layout(
+    frames: &[(WindowId, Frame)],
+    (width, height): (f64, f64),
+) -> Vec<(WindowId, Frame)> {
+    if frames.is_empty() {
+        return Vec::new();
+    }
+    let mut ordered = frames.to_vec();
+    ordered.sort_by(|(_, a), (_, b)| {
+        let a = (a.1 + a.3 / 2.0, a.0 + a.2 / 2.0);
+        let b = (b.1 + b.3 / 2.0, b.0 + b.2 / 2.0);
+        a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let count = ordered.len();
+    let columns = (count as f64).sqrt().ceil() as usize;
+    let rows = count.div_ceil(columns);
+    let left = MISSION_CONTROL_MARGIN;
+    let top = MENU_BAR_HEIGHT + MISSION_CONTROL_MARGIN;
+    let cell_width = (width - 2.0 * MISSION_CONTROL_MARGIN) / columns as f64;
+    let cell_height = (height - top - MISSION_CONTROL_DOCK_SPACE) / rows as f64;
+
+    ordered
+        .into_iter()
+        .enumerate()
+        .map(|(index, (id, (_, _, w, h)))| {
+            let (row, column) = (index / columns, index % columns);
+            // Center a short last row
+            let in_row = (count - row * columns).min(columns);
+            let row_left = left + (columns - in_row) as f64 * cell_width / 2.0;
+            let scale = ((cell_width - MISSION_CONTROL_GAP) / w)
+                .min((cell_height - MISSION_CONTROL_GAP - MISSION_CONTROL_TITLE_HEIGHT) / h)
+                .min(1.0);
+            let (thumb_width, thumb_height) = (w * scale, h * scale);
+            let x = row_left + column as f64 * cell_width + (cell_width - thumb_width) / 2.0;
+            let y = top
+                + row as f64 * cell_height
+                + (cell_height - MISSION_CONTROL_TITLE_HEIGHT - thumb_height) / 2.0;
+            (id, (x, y, thumb_width, thumb_height))
+        })
+        .collect()
+}
+
+/// An app waiting in the Stage Manager strip
+#[derive(Clone, Debug, PartialEq)]
+struct StageGroup {
+    app: AppType,
+    /// The strip cell the group is drawn in
+    cell: Frame,
+    /// Where each of the app's windows is drawn, front-most last
+    thumbnails: Vec<(WindowId, Frame)>,
+}
+
+/// Stage Manager's strip: a cell per app, most recently used at the top,
+/// each holding the app's windows scaled down and fanned out a little.
+/// `groups` lists each app's windows front-most last.
+fn stage_manager_layout(
+    groups: Vec<(AppType, Vec<(WindowId, Frame)>)>,
+    (_, height): (f64, f64),
+) -> Vec<StageGroup> {
+    if groups.is_empty() {
+        return Vec::new();
+    }
+    let count = groups.len() as f64;
+    let available = height - MENU_BAR_HEIGHT - MISSION_CONTROL_DOCK_SPACE;
+    let cell_height = ((available - STAGE_STRIP_MARGIN * (count - 1.0)) / count)
+        .clamp(STAGE_TITLE_HEIGHT, STAGE_CELL_HEIGHT);
+    let total = cell_height * count + STAGE_STRIP_MARGIN * (count - 1.0);
+    let top = MENU_BAR_HEIGHT + ((available - total) / 2.0).max(0.0);
+    let cell_width = STAGE_STRIP_WIDTH - 2.0 * STAGE_STRIP_MARGIN;
+
+    groups
+        .into_iter()
+        .enumerate()
+        .map(|(index, (app, frames))| {
+            let cell = (
+                STAGE_STRIP_MARGIN,
+                top + index as f64 * (cell_height + STAGE_STRIP_MARGIN),
+                cell_width,
+                cell_height,
+            );
+            let fan = STAGE_GROUP_OFFSET * (frames.len() - 1) as f64;
+            let (room_width, room_height) = (
+                cell_width - fan,
+                (cell_height - STAGE_TITLE_HEIGHT - fan).max(1.0),
+            );
+            let depth = frames.len();
+            let thumbnails = frames
+                .into_iter()
+                .enumerate()
+                .map(|(layer, (id, (_, _, w, h)))| {
+                    let scale = (room_width / w).min(room_height / h).min(1.0);
+                    let (thumb_width, thumb_height) = (w * scale, h * scale);
+                    // Windows further back sit up and to the left
+                    let behind = (depth - 1 - layer) as f64 * STAGE_GROUP_OFFSET;
+                    let x = cell.0 + fan + (room_width - thumb_width) / 2.0 - behind;
+                    let y = cell.1 + fan + (room_height - thumb_height) / 2.0 - behind;
+                    (id, (x, y, thumb_width, thumb_height))
+                })
+                .collect();
+            StageGroup {
+                app,
+                cell,
+                thumbnails,
+            }
+        })
+        .collect()
+}
+
 /// Default windows when no persisted state exists
 fn default_windows() -> Vec<WindowState> {
     vec![
@@ -354,13 +865,15 @@ fn default_windows() -> Vec<WindowState> {
 pub fn WindowManager() -> impl IntoView {
     let system_state = expect_context::<SystemState>();
     let notification_state = expect_context::<NotificationState>();
+    let processes = expect_context::<ProcessTable>();
+    let preferences = expect_context::<Preferences>();
 
     // Load persisted state or use defaults
-    let (initial_windows, initial_next_id, initial_top_z, schema_mismatch) = load_desktop_state()
-        .unwrap_or_else(|| {
+    let (initial_windows, initial_next_id, initial_top_z, initial_spaces, schema_mismatch) =
+        load_desktop_state().unwrap_or_else(|| {
             let defaults = default_windows();
             let count = defaults.len();
-            (defaults, count + 1, count as i32, false)
+            (defaults, count + 1, count as i32, Spaces::default(), false)
         });
 
     // Show notification if schema changed (desktop was reset due to update)
@@ -376,21 +889,323 @@ pub fn WindowManager() -> impl IntoView {
     let (next_id, set_next_id) = signal(initial_next_id);
     let (drag_op, set_drag_op) = signal(DragOperation::None);
     let (top_z_index, set_top_z_index) = signal(initial_top_z);
+    // Region a dragged window will tile to if dropped now
+    let (snap_preview, set_snap_preview) = signal(None::<TileLayout>);
+    let (mission_control, set_mission_control) = signal(MissionControl::Off);
+    let (spaces, set_spaces) = signal(initial_spaces);
+    // Slide-in animation class while switching Spaces
+    let (space_slide, set_space_slide) = signal(None::<&'static str>);
+    // Browser viewport, which windows are kept within
+    let (viewport, set_viewport) = signal(viewport_size());
+    // Window whose Mission Control thumbnail is being dragged to a Space
+    let (space_drag, set_space_drag) = signal(None::<WindowId>);
+
+    // Show another Space, sliding its windows in from the side it lies on
+    let switch_space = move |index: usize| {
+        let Spaces { count, current, .. } = spaces.get_untracked();
+        if index == current || index >= count {
+            return;
+        }
+        set_space_slide.set(Some(if index > current {
+            "space-enter-right"
+        } else {
+            "space-enter-left"
+        }));
+        set_spaces.update(|spaces| spaces.current = index);
+        #[cfg(target_arch = "wasm32")]
+        {
+            let cb = Closure::once(Box::new(move || {
+                set_space_slide.set(None);
+            }) as Box<dyn FnOnce()>);
+
+            let window = web_sys::window().unwrap();
+            window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    cb.as_ref().unchecked_ref(),
+                    MISSION_CONTROL_DURATION,
+                )
+                .unwrap();
+            cb.forget();
+        }
+    };
+
+    // Move a window to another desktop; full-screen windows and Spaces keep
+    // to themselves
+    let move_window_to_space = move |window_id: WindowId, index: usize| {
+        if spaces.with_untracked(|spaces| spaces.is_full_screen(index)) {
+            return;
+        }
+        set_windows.update(|windows| {
+            if let Some(win) = windows
+                .iter_mut()
+                .find(|w| w.id == window_id && !w.is_fullscreen)
+            {
+                win.space = index;
+            }
+        });
+    };
+
+    let add_space = move || {
+        set_spaces.update(|spaces| {
+            spaces.count += 1;
+            spaces.full_screen.push(None);
+        });
+    };
+
+    // Insert a Space before `index`, shifting later Spaces right
+    let insert_space = move |index: usize, owner: Option<WindowId>| {
+        set_windows.update(|windows| {
+            for win in windows.iter_mut().filter(|w| w.space >= index) {
+                win.space += 1;
+            }
+        });
+        set_spaces.update(|spaces| {
+            spaces.count += 1;
+            spaces.full_screen.insert(index, owner);
+            if spaces.current >= index {
+                spaces.current += 1;
+            }
+        });
+    };
+
+    // Remove a Space, moving its windows to the one before it
+    let remove_space = move |index: usize| {
+        if spaces.get_untracked().count <= 1 {
+            return;
+        }
+        let renumber = move |space: usize| {
+            if space > index {
+                space - 1
+            } else if space == index {
+                index.saturating_sub(1)
+            } else {
+                space
+            }
+        };
+        set_windows.update(|windows| {
+            for win in windows.iter_mut() {
+                win.space = renumber(win.space);
+            }
+        });
+        set_spaces.update(|spaces| {
+            spaces.count -= 1;
+            spaces.full_screen.remove(index);
+            spaces.current = renumber(spaces.current);
+        });
+    };
+
+    // Where each visible window sits while Mission Control is open
+    let mission_control_frames = Memo::new(move |_| {
+        if mission_control.get() != MissionControl::Open {
+            return Vec::new();
+        }
+        let viewport = viewport.get();
+        let current = spaces.get().current;
+        let hidden_apps = system_state.hidden_apps.get();
+        let frames: Vec<(WindowId, Frame)> = windows.with(|windows| {
+            windows
+                .iter()
+                .filter(|w| w.is_shown(current, &hidden_apps))
+                .map(|w| (w.id, w.frame(viewport)))
+                .collect()
+        });
+        mission_control_layout(&frames, viewport)
+    });
+
+    // Stage Manager keeps the front app's windows on stage and the other
+    // apps in view in a strip down the left, stepping aside for full screen
+    let stage_manager = Memo::new(move |_| {
+        preferences
+            .get_bool(WINDOW_MANAGER_DOMAIN, "GloballyEnabled")
+            .unwrap_or(false)
+            && !system_state.full_screen.get()
+    });
+
+    // The apps in the strip, ordered by their front window
+    let stage_groups = Memo::new(move |_| {
+        if !stage_manager.get() || mission_control.get() == MissionControl::Open {
+            return Vec::new();
+        }
+        let viewport = viewport.get();
+        let current = spaces.get().current;
+        let hidden_apps = system_state.hidden_apps.get();
+        let groups = windows.with(|windows| {
+            let mut shown: Vec<&WindowState> = windows
+                .iter()
+                .filter(|w| w.is_shown(current, &hidden_apps))
+                .collect();
+            shown.sort_by_key(|w| std::cmp::Reverse(w.z_index));
+            let mut groups: Vec<(AppType, Vec<(WindowId, Frame)>)> = Vec::new();
+            let Some(stage) = shown.first().map(|w| w.app_type.clone()) else {
+                return groups;
+            };
+            for win in shown.iter().filter(|w| w.app_type != stage) {
+                let frame = (win.id, win.frame(viewport));
+                match groups.iter_mut().find(|(app, _)| *app == win.app_type) {
+                    Some((_, frames)) => frames.insert(0, frame),
+                    None => groups.push((win.app_type.clone(), vec![frame])),
+                }
+            }
+            groups
+        });
+        stage_manager_layout(groups, viewport)
+    });
+    let strip_frame = move |window_id: WindowId| {
+        stage_groups.with(|groups| {
+            groups
+                .iter()
+                .flat_map(|group| &group.thumbnails)
+                .find(|(id, _)| *id == window_id)
+                .map(|(_, frame)| *frame)
+        })
+    };
+
+    // The app on stage, while Stage Manager is on
+    let stage_app = Memo::new(move |_| {
+        if stage_manager.get() {
+            AppType::from_name(&system_state.active_app.get())
+        } else {
+            None
+        }
+    });
+
+    // An app taking the stage is drawn, as a group, in the middle of the
+    // space beside the strip. The shift is kept apart from the windows'
+    // frames, so they are back where they were once Stage Manager is off.
+    // Windows dragged out of a tile stay where the pointer put them.
+    let (stage_exempt, set_stage_exempt) = signal(Vec::<WindowId>::new());
+    Effect::new(move |_| {
+        stage_app.track();
+        set_stage_exempt.set(Vec::new());
+    });
+    // Which windows make up the stage group, on which screen. Their
+    // positions are left out, so dragging one doesn't re-center the rest.
+    let stage_group = Memo::new(move |_| {
+        let app = stage_app.get()?;
+        let viewport = viewport.get();
+        let current = spaces.with(|spaces| spaces.current);
+        let hidden_apps = system_state.hidden_apps.get();
+        let ids: Vec<WindowId> = stage_exempt.with(|exempt| {
+            windows.with(|windows| {
+                windows
+                    .iter()
+                    .filter(|w| {
+                        w.app_type == app
+                            && w.is_shown(current, &hidden_apps)
+                            && !w.is_maximized
+                            && !w.is_fullscreen
+                            && w.tile.is_none()
+                            && !exempt.contains(&w.id)
+                    })
+                    .map(|w| w.id)
+                    .collect()
+            })
+        });
+        Some((viewport, ids))
+    });
+    let stage_offset = Memo::new(move |_| {
+        let ((width, _), ids) = stage_group.get()?;
+        let (left, right) = windows.with_untracked(|windows| {
+            windows.iter().filter(|w| ids.contains(&w.id)).fold(
+                None,
+                |bounds: Option<(f64, f64)>, w| {
+                    let (left, right) = bounds.unwrap_or((w.x, w.x + w.width));
+                    Some((left.min(w.x), right.max(w.x + w.width)))
+                },
+            )
+        })?;
+        let target = STAGE_STRIP_WIDTH + (width - STAGE_STRIP_WIDTH - (right - left)) / 2.0;
+        Some((target.max(0.0) - left, ids))
+    });
+    // How far a window is drawn from its frame to sit centered on stage
+    let stage_dx = move |w: &WindowState| {
+        if w.is_maximized || w.is_fullscreen || w.tile.is_some() {
+            return 0.0;
+        }
+        stage_offset.with(|offset| match offset {
+            Some((dx, ids)) if ids.contains(&w.id) => *dx,
+            _ => 0.0,
+        })
+    };
+
+    // Follow the browser window as it resizes
+    #[cfg(target_arch = "wasm32")]
+    {
+        let cb = Closure::wrap(Box::new(move || {
+            set_viewport.set(viewport_size());
+        }) as Box<dyn Fn()>);
+        if let Some(window) = web_sys::window() {
+            let _ = window.add_event_listener_with_callback("resize", cb.as_ref().unchecked_ref());
+        }
+        cb.forget();
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    let _ = set_viewport;
+
+    // Keep every window within reach when the viewport changes
+    Effect::new(move |previous_run: Option<()>| {
+        let viewport = viewport.get();
+        if previous_run.is_some() {
+            set_windows.update(|windows| {
+                for win in windows.iter_mut() {
+                    win.fit_to_viewport(viewport);
+                }
+            });
+        }
+    });
 
     // Auto-sync active app from windows state
     // This Effect watches windows and automatically updates the active app in menu bar
     // whenever windows change (open, close, focus, minimize, restore, etc.)
     Effect::new(move |_| {
         let current_windows = windows.get();
+        let current_space = spaces.get().current;
+        let hidden_apps = system_state.hidden_apps.get();
         let active_app = current_windows
             .iter()
-            .filter(|w| !w.is_minimized)
+            .filter(|w| w.is_shown(current_space, &hidden_apps))
             .max_by_key(|w| w.z_index)
             .map(|w| w.app_type.to_string())
             .unwrap_or_else(|| "Finder".to_string());
         system_state.set_active_app(&active_app);
     });
 
+    // Hide the menu bar and Dock while a full-screen window is in view
+    Effect::new(move |_| {
+        let current_space = spaces.get().current;
+        let full_screen = windows.with(|windows| {
+            windows
+                .iter()
+                .any(|w| w.is_fullscreen && !w.is_minimized && w.space == current_space)
+        });
+        if system_state.full_screen.get_untracked() != full_screen {
+            system_state.full_screen.set(full_screen);
+        }
+    });
+
+    // A full-screen window's Space goes away with it, whether it leaves full
+    // screen, closes or quits
+    Effect::new(move |_| {
+        let owners = spaces.with(|spaces| spaces.full_screen.clone());
+        let vacated: Vec<usize> = windows.with(|windows| {
+            owners
+                .iter()
+                .enumerate()
+                .rev()
+                .filter_map(|(index, owner)| {
+                    let id = (*owner)?;
+                    let present = windows
+                        .iter()
+                        .any(|w| w.id == id && w.is_fullscreen && w.space == index);
+                    (!present).then_some(index)
+                })
+                .collect()
+        });
+        for index in vacated {
+            remove_space(index);
+        }
+    });
+
     // Sync minimized windows to SystemState for dock display
     Effect::new(move |_| {
         let current_windows = windows.get();
@@ -416,15 +1231,47 @@ pub fn WindowManager() -> impl IntoView {
             .collect();
         open_apps.sort();
         open_apps.dedup();
+        // An app that quits is no longer hidden
+        let quit = system_state
+            .hidden_apps
+            .with_untracked(|hidden| hidden.iter().any(|app| !open_apps.contains(app)));
+        if quit {
+            system_state
+                .hidden_apps
+                .update(|hidden| hidden.retain(|app| open_apps.contains(app)));
+        }
         system_state.open_windows.set(open_apps);
     });
 
+    // Every open window runs as a process
+    Effect::new(move |_| {
+        let current: Vec<(WindowId, AppType)> =
+            windows.with(|windows| windows.iter().map(|w| (w.id, w.app_type.clone())).collect());
+        processes.sync_windows(&current);
+    });
+
+    // Close windows whose process was killed (`kill`, Force Quit)
+    Effect::new(move |_| {
+        let terminated = processes.terminated_windows.get();
+        if !terminated.is_empty() {
+            processes.terminated_windows.set(Vec::new());
+            set_windows.update(|windows| {
+                windows.retain(|w| !terminated.contains(&w.id));
+            });
+        }
+    });
+
     // Save state when windows change (debounced via effect)
     Effect::new(move |_| {
         let current_windows = windows.get();
         let current_next_id = next_id.get();
         let current_top_z = top_z_index.get();
-        save_desktop_state(&current_windows, current_next_id, current_top_z);
+        save_desktop_state(
+            &current_windows,
+            current_next_id,
+            current_top_z,
+            spaces.get(),
+        );
     });
 
     // Watch for desktop reset request
@@ -439,6 +1286,7 @@ pub fn WindowManager() -> impl IntoView {
             set_windows.set(defaults);
             set_next_id.set(count + 1);
             set_top_z_index.set(count as i32);
+            set_spaces.set(Spaces::default());
         }
     });
 
@@ -448,6 +1296,8 @@ pub fn WindowManager() -> impl IntoView {
             // Reset the signal
             system_state.open_system_settings.set(false);
 
+            system_state.unhide_app(&AppType::SystemSettings.to_string());
+
             // Check if System Settings is already open
             let already_open = windows
                 .get()
@@ -461,14 +1311,22 @@ pub fn WindowManager() -> impl IntoView {
                     .find(|w| w.app_type == AppType::SystemSettings)
                 {
                     let window_id = win.id;
+                    // A minimized window comes back on the Space in view
+                    let space = if win.is_minimized {
+                        spaces.get_untracked().current
+                    } else {
+                        win.space
+                    };
                     let new_z = top_z_index.get() + 1;
                     set_top_z_index.set(new_z);
                     set_windows.update(|windows| {
                         if let Some(w) = windows.iter_mut().find(|w| w.id == window_id) {
                             w.z_index = new_z;
                             w.is_minimized = false;
+                            w.space = space;
                         }
                     });
+                    switch_space(space);
                 }
             } else {
                 // Create new System Settings window
@@ -487,73 +1345,106 @@ pub fn WindowManager() -> impl IntoView {
                         AppType::SystemSettings,
                     );
                     new_window.z_index = new_z;
+                    new_window.space = spaces.get_untracked().current;
                     windows.push(new_window);
                 });
             }
         }
     });
 
-    // Watch for dock app open requests
-    Effect::new(move |_| {
-        if let Some(app_name) = system_state.open_app.get() {
-            // Reset the signal
-            system_state.open_app.set(None);
-
-            // Map app name to AppType
-            let app_type = match app_name.as_str() {
-                "Finder" => Some(AppType::Finder),
-                "Calculator" => Some(AppType::Calculator),
-                "Terminal" => Some(AppType::Terminal),
-                "TextEdit" => Some(AppType::TextEdit),
-                "Notes" => Some(AppType::Notes),
-                "System Settings" => Some(AppType::SystemSettings),
-                _ => None,
-            };
+    // Open a new window for an app, cascaded from its front window here
+    let open_window = move |app_type: AppType| -> WindowId {
+        let id = next_id.get_untracked();
+        set_next_id.set(id + 1);
+        let new_z = top_z_index.get_untracked() + 1;
+        set_top_z_index.set(new_z);
 
-            if let Some(target_type) = app_type {
-                // Check if app is already open
-                let existing = windows
-                    .get()
-                    .iter()
-                    .find(|w| w.app_type == target_type)
-                    .map(|w| (w.id, w.is_minimized));
+        // Get default dimensions for each app
+        let (title, x, y, w, h) = match app_type {
+            AppType::Calculator => ("Calculator", 200.0, 150.0, 280.0, 540.0),
+            AppType::Terminal => ("Terminal", 300.0, 120.0, 600.0, 400.0),
+            AppType::TextEdit => ("TextEdit", 350.0, 200.0, 500.0, 400.0),
+            AppType::Notes => ("Notes", 450.0, 220.0, 700.0, 500.0),
+            AppType::SystemSettings => ("System Settings", 150.0, 100.0, 680.0, 500.0),
+            AppType::Finder => ("Finder", 100.0, 80.0, 600.0, 400.0),
+        };
+        let space = spaces.get_untracked().current;
+        let (x, y) = windows.with_untracked(|windows| {
+            windows
+                .iter()
+                .filter(|w| w.app_type == app_type && w.space == space && !w.is_minimized)
+                .max_by_key(|w| w.z_index)
+                .map_or((x, y), |w| (w.x + 22.0, w.y + 22.0))
+        });
 
-                if let Some((window_id, is_minimized)) = existing {
-                    // Bring existing window to front
-                    let new_z = top_z_index.get() + 1;
-                    set_top_z_index.set(new_z);
-                    set_windows.update(|windows| {
-                        if let Some(w) = windows.iter_mut().find(|w| w.id == window_id) {
-                            w.z_index = new_z;
-                            if is_minimized {
-                                w.is_minimized = false;
-                            }
-                        }
-                    });
-                } else {
-                    // Create new window for this app
-                    let id = next_id.get();
-                    set_next_id.set(id + 1);
-                    let new_z = top_z_index.get() + 1;
-                    set_top_z_index.set(new_z);
+        set_windows.update(|windows| {
+            let mut new_window = WindowState::new_with_app(id, title, x, y, w, h, app_type);
+            new_window.z_index = new_z;
+            new_window.space = space;
+            new_window.fit_to_viewport(viewport.get_untracked());
+            windows.push(new_window);
+        });
+        id
+    };
 
-                    // Get default dimensions for each app
-                    let (title, x, y, w, h) = match target_type {
-                        AppType::Calculator => ("Calculator", 200.0, 150.0, 280.0, 540.0),
-                        AppType::Terminal => ("Terminal", 300.0, 120.0, 600.0, 400.0),
-                        AppType::TextEdit => ("TextEdit", 350.0, 200.0, 500.0, 400.0),
-                        AppType::Notes => ("Notes", 450.0, 220.0, 700.0, 500.0),
-                        AppType::SystemSettings => ("System Settings", 150.0, 100.0, 680.0, 500.0),
-                        AppType::Finder => ("Finder", 100.0, 80.0, 600.0, 400.0),
-                    };
+    // Open apps as requested, each bringing its front window forward or a
+    // new one. A document goes to the window it was opened in; when several
+    // are opened for an app at once, all but the first get new windows.
+    Effect::new(move |_| {
+        let requests = system_state.open_requests.get();
+        if requests.is_empty() {
+            return;
+        }
+        system_state.open_requests.set(Vec::new());
 
-                    set_windows.update(|windows| {
-                        let mut new_window =
-                            WindowState::new_with_app(id, title, x, y, w, h, target_type);
-                        new_window.z_index = new_z;
-                        windows.push(new_window);
-                    });
-                }
+        let mut opened: Vec<AppType> = Vec::new();
+        for request in requests {
+            let Some(target_type) = AppType::from_name(&request.app) else {
+                continue;
+            };
+            // A hidden app comes back with all its windows
+            system_state.unhide_app(&target_type.to_string());
+
+            // Check if app is already open, preferring its front window
+            let existing = windows
+                .with_untracked(|windows| {
+                    windows
+                        .iter()
+                        .filter(|w| w.app_type == target_type)
+                        .max_by_key(|w| (!w.is_minimized, w.z_index))
+                        .map(|w| (w.id, w.is_minimized, w.space))
+                })
+                .filter(|_| !opened.contains(&target_type));
+            opened.push(target_type.clone());
+
+            let window_id = if let Some((window_id, is_minimized, space)) = existing {
+                // Bring existing window to front, on its own Space unless
+                // it comes back from the Dock
+                let space = if is_minimized {
+                    spaces.get_untracked().current
+                } else {
+                    space
+                };
+                let new_z = top_z_index.get_untracked() + 1;
+                set_top_z_index.set(new_z);
+                set_windows.update(|windows| {
+                    if let Some(w) = windows.iter_mut().find(|w| w.id == window_id) {
+                        w.z_index = new_z;
+                        w.space = space;
+                        if is_minimized {
+                            w.is_minimized = false;
+                        }
+                    }
+                });
+                switch_space(space);
+                window_id
+            } else {
+                open_window(target_type)
+            };
+            if let Some(path) = request.path {
+                system_state
+                    .open_documents
+                    .update(|docs| docs.push(OpenDocument { window_id, path }));
             }
         }
     });
@@ -570,13 +1461,69 @@ pub fn WindowManager() -> impl IntoView {
     #[cfg(target_arch = "wasm32")]
     {
         let cb = Closure::wrap(Box::new(move |e: web_sys::KeyboardEvent| {
-            // Check for Cmd (Meta) key on Mac or Ctrl on other platforms
-            if e.meta_key() || e.ctrl_key() {
-                match e.key().as_str() {
-                    "q" | "Q" => {
-                        e.prevent_default();
-                        set_action_trigger.set(WindowAction::QuitAll);
-                    }
+            let key = e.key();
+            let open = mission_control.get_untracked() == MissionControl::Open;
+            if key == "F3"
+                || (e.ctrl_key() && !e.alt_key() && !e.meta_key() && key == "ArrowUp")
+                || (open && key == "Escape")
+            {
+                e.prevent_default();
+                set_action_trigger.set(WindowAction::MissionControl);
+                return;
+            }
+            if e.ctrl_key() && e.meta_key() && e.code() == "KeyF" {
+                e.prevent_default();
+                set_action_trigger.set(WindowAction::ToggleFullScreen);
+                return;
+            }
+            if e.ctrl_key() && !e.alt_key() && !e.meta_key() {
+                let step = match key.as_str() {
+                    "ArrowLeft" => Some(-1),
+                    "ArrowRight" => Some(1),
+                    _ => None,
+                };
+                if let Some(step) = step {
+                    e.prevent_default();
+                    set_action_trigger.set(if e.shift_key() {
+                        WindowAction::MoveToSpace(step)
+                    } else {
+                        WindowAction::SwitchSpace(step)
+                    });
+                    return;
+                }
+            }
+            if e.ctrl_key() && e.alt_key() && !e.meta_key() {
+                if let Some(action) = tile_shortcut(&e.code()) {
+                    e.prevent_default();
+                    set_action_trigger.set(action);
+                    return;
+                }
+            }
+            if e.meta_key() {
+                let action = match (e.code().as_str(), e.alt_key()) {
+                    ("Backquote", _) => Some(WindowAction::CycleWindows(if e.shift_key() {
+                        -1
+                    } else {
+                        1
+                    })),
+                    ("KeyM", false) => Some(WindowAction::MinimizeActive),
+                    ("KeyM", true) => Some(WindowAction::MinimizeAll),
+                    ("KeyH", true) => Some(WindowAction::HideOthers),
+                    _ => None,
+                };
+                if let Some(action) = action {
+                    e.prevent_default();
+                    set_action_trigger.set(action);
+                    return;
+                }
+            }
+            // Check for Cmd (Meta) key on Mac or Ctrl on other platforms
+            if e.meta_key() || e.ctrl_key() {
+                match e.key().as_str() {
+                    "q" | "Q" => {
+                        e.prevent_default();
+                        set_action_trigger.set(WindowAction::QuitAll);
+                    }
                     "w" | "W" => {
                         e.prevent_default();
                         set_action_trigger.set(WindowAction::CloseActive);
@@ -600,6 +1547,14 @@ pub fn WindowManager() -> impl IntoView {
         cb.forget();
     }
 
+    // Forward window requests from the menu bar
+    Effect::new(move |_| {
+        if let Some(action) = system_state.window_request.get() {
+            system_state.window_request.set(None);
+            set_action_trigger.set(action);
+        }
+    });
+
     // Bring window to front (active app auto-updates via Effect watching windows)
     let bring_to_front = move |window_id: WindowId| {
         let new_z = top_z_index.get() + 1;
@@ -612,15 +1567,51 @@ pub fn WindowManager() -> impl IntoView {
         });
     };
 
-    // Close window (active app auto-updates via Effect watching windows)
-    let close_window = move |window_id: WindowId| {
+    // Bring every window of an app forward, keeping their order
+    let bring_app_to_front = move |app: &AppType| {
+        let mut raised: Vec<(i32, WindowId)> = windows.with_untracked(|windows| {
+            windows
+                .iter()
+                .filter(|w| w.app_type == *app && !w.is_minimized)
+                .map(|w| (w.z_index, w.id))
+                .collect()
+        });
+        raised.sort_unstable();
+        let top = top_z_index.get_untracked();
+        set_top_z_index.set(top + raised.len() as i32);
         set_windows.update(|windows| {
-            windows.retain(|w| w.id != window_id);
+            for (offset, (_, window_id)) in raised.iter().enumerate() {
+                if let Some(win) = windows.iter_mut().find(|w| w.id == *window_id) {
+                    win.z_index = top + 1 + offset as i32;
+                }
+            }
         });
     };
 
-    // Minimize window with genie animation
+    // Sheet slots of open windows, so closing can ask the app first
+    let window_sheets = StoredValue::new(HashMap::<WindowId, WindowSheet>::new());
+
+    // Close window (active app auto-updates via Effect watching windows),
+    // unless a sheet is up or the app wants to ask about it
+    let close_window = move |window_id: WindowId| {
+        let keeps_open = window_sheets
+            .with_value(|sheets| sheets.get(&window_id).copied())
+            .is_some_and(|sheet| sheet.keeps_open());
+        if !keeps_open {
+            set_windows.update(|windows| {
+                windows.retain(|w| w.id != window_id);
+            });
+        }
+    };
+
+    // Minimize window with genie animation. A full-screen window owns its
+    // Space, so it has to leave full screen before it can go to the Dock.
     let minimize_window = move |window_id: WindowId| {
+        if windows
+            .with_untracked(|windows| windows.iter().any(|w| w.id == window_id && w.is_fullscreen))
+        {
+            return;
+        }
         // Calculate the target X position for the animation
         // The minimized dock appears to the right of the main dock, centered as a whole
         #[cfg(target_arch = "wasm32")]
@@ -705,12 +1696,51 @@ pub fn WindowManager() -> impl IntoView {
         }
     };
 
-    // Maximize/restore window
+    // Enter full screen on a new Space beside the window's own, or leave it
+    // for the Space it came from
+    let toggle_full_screen = move |window_id: WindowId| {
+        let Some((space, is_fullscreen)) = windows.with_untracked(|windows| {
+            windows
+                .iter()
+                .find(|w| w.id == window_id)
+                .map(|w| (w.space, w.is_fullscreen))
+        }) else {
+            return;
+        };
+        if is_fullscreen {
+            let origin = space.saturating_sub(1);
+            set_windows.update(|windows| {
+                if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
+                    win.is_fullscreen = false;
+                    win.space = origin;
+                }
+            });
+            switch_space(origin);
+        } else {
+            insert_space(space + 1, Some(window_id));
+            set_windows.update(|windows| {
+                if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
+                    win.is_fullscreen = true;
+                    win.space = space + 1;
+                }
+            });
+            switch_space(space + 1);
+        }
+    };
+
+    // Maximize/restore window; the green button takes a full-screen window
+    // out of full screen
     let maximize_window = move |window_id: WindowId| {
+        if windows
+            .with_untracked(|windows| windows.iter().any(|w| w.id == window_id && w.is_fullscreen))
+        {
+            toggle_full_screen(window_id);
+            return;
+        }
         set_windows.update(|windows| {
             if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
                 if win.is_maximized {
-                    // Restore
+                    // Restore, within a viewport that may have shrunk since
                     if let Some((x, y, w, h)) = win.pre_maximize {
                         win.x = x;
                         win.y = y;
@@ -719,15 +1749,92 @@ pub fn WindowManager() -> impl IntoView {
                     }
                     win.is_maximized = false;
                     win.pre_maximize = None;
+                    win.fit_to_viewport(viewport.get_untracked());
                 } else {
-                    // Maximize
-                    win.pre_maximize = Some((win.x, win.y, win.width, win.height));
+                    // Maximize; a tiled window already holds its untiled frame
+                    if win.tile.take().is_none() {
+                        win.pre_maximize = Some((win.x, win.y, win.width, win.height));
+                    }
                     win.is_maximized = true;
                 }
             }
         });
     };
 
+    // Tile a window to a region of the screen, remembering its frame
+    let tile_window = move |window_id: WindowId, layout: TileLayout| {
+        let (x, y, width, height) = layout.frame(
+            viewport.get_untracked(),
+            system_state.full_screen.get_untracked(),
+        );
+        set_windows.update(|windows| {
+            if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
+                if win.is_fullscreen {
+                    return;
+                }
+                if win.tile.is_none() && !win.is_maximized {
+                    win.pre_maximize = Some((win.x, win.y, win.width, win.height));
+                }
+                win.is_maximized = false;
+                win.tile = Some(layout);
+                win.x = x;
+                win.y = y;
+                win.width = width;
+                win.height = height;
+            }
+        });
+    };
+
+    // Enter Mission Control, or leave it by animating windows back into place
+    let toggle_mission_control = move || {
+        if mission_control.get_untracked() != MissionControl::Open {
+            set_mission_control.set(MissionControl::Open);
+            return;
+        }
+        set_mission_control.set(MissionControl::Closing);
+        #[cfg(target_arch = "wasm32")]
+        {
+            let cb = Closure::once(Box::new(move || {
+                if mission_control.get_untracked() == MissionControl::Closing {
+                    set_mission_control.set(MissionControl::Off);
+                }
+            }) as Box<dyn FnOnce()>);
+
+            let window = web_sys::window().unwrap();
+            window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    cb.as_ref().unchecked_ref(),
+                    MISSION_CONTROL_DURATION,
+                )
+                .unwrap();
+            cb.forget();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            set_mission_control.set(MissionControl::Off);
+        }
+    };
+
+    // Return a tiled or maximized window to its frame from before
+    let restore_previous_size = move |window_id: WindowId| {
+        set_windows.update(|windows| {
+            if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
+                if win.tile.is_none() && !win.is_maximized {
+                    return;
+                }
+                if let Some((x, y, w, h)) = win.pre_maximize.take() {
+                    win.x = x;
+                    win.y = y;
+                    win.width = w;
+                    win.height = h;
+                }
+                win.tile = None;
+                win.is_maximized = false;
+                win.fit_to_viewport(viewport.get_untracked());
+            }
+        });
+    };
+
     // Restore minimized window with genie animation
     let restore_window = move |window_id: WindowId| {
         // Calculate the source X position (where the item is in the minimized dock)
@@ -776,11 +1883,20 @@ pub fn WindowManager() -> impl IntoView {
         #[cfg(not(target_arch = "wasm32"))]
         let source_x = 960.0;
 
+        if let Some(app) = windows.with_untracked(|windows| {
+            windows
+                .iter()
+                .find(|w| w.id == window_id)
+                .map(|w| w.app_type.to_string())
+        }) {
+            system_state.unhide_app(&app);
+        }
         bring_to_front(window_id);
         // Start restore animation - remove minimized and add restoring
         set_windows.update(|windows| {
             if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
                 win.is_minimized = false;
+                win.space = spaces.get_untracked().current;
                 win.animation = AnimationState::Restoring;
                 win.animation_target_x = Some(source_x);
             }
@@ -831,7 +1947,7 @@ pub fn WindowManager() -> impl IntoView {
 
         let windows_val = windows.get();
         if let Some(win) = windows_val.iter().find(|w| w.id == window_id) {
-            if !win.is_maximized {
+            if !win.is_maximized && !win.is_fullscreen {
                 set_drag_op.set(DragOperation::Move {
                     window_id,
                     start_x: e.client_x() as f64,
@@ -851,7 +1967,16 @@ pub fn WindowManager() -> impl IntoView {
 
         let windows_val = windows.get();
         if let Some(win) = windows_val.iter().find(|w| w.id == window_id) {
-            if !win.is_maximized {
+            if !win.is_maximized && !win.is_fullscreen {
+                // Resizing makes a tiled window free-form again
+                if win.tile.is_some() {
+                    set_windows.update(|windows| {
+                        if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
+                            win.tile = None;
+                            win.pre_maximize = None;
+                        }
+                    });
+                }
                 set_drag_op.set(DragOperation::Resize {
                     window_id,
                     direction,
@@ -866,102 +1991,155 @@ pub fn WindowManager() -> impl IntoView {
         }
     };
 
-    // Handle mouse move for drag/resize
-    let on_mouse_move = move |e: MouseEvent| {
-        let op = drag_op.get();
-        match op {
-            DragOperation::None => {}
-            DragOperation::Move {
-                window_id,
-                start_x,
-                start_y,
-                window_start_x,
-                window_start_y,
-            } => {
-                let dx = e.client_x() as f64 - start_x;
-                let dy = e.client_y() as f64 - start_y;
-                set_windows.update(|windows| {
-                    if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
-                        win.x = window_start_x + dx;
-                        win.y = (window_start_y + dy).max(MENU_BAR_HEIGHT);
-                    }
-                });
+    // Resize the window from the dragged handle, within its app's size
+    // limits and the viewport, keeping the title bar below the menu bar
+    let resize_window = move |pointer_x: f64, pointer_y: f64| {
+        let DragOperation::Resize {
+            window_id,
+            direction,
+            start_x,
+            start_y,
+            window_start_x,
+            window_start_y,
+            window_start_width,
+            window_start_height,
+        } = drag_op.get_untracked()
+        else {
+            return;
+        };
+        let (dx, dy) = (pointer_x - start_x, pointer_y - start_y);
+        let (left, right, top, bottom) = direction.edges();
+        let (viewport_width, viewport_height) = viewport.get_untracked();
+
+        set_windows.update(|windows| {
+            let Some(win) = windows.iter_mut().find(|w| w.id == window_id) else {
+                return;
+            };
+            let (min_width, min_height) = win.app_type.min_size();
+            let (max_width, max_height) = win.app_type.max_size();
+            let max_width = max_width.min(viewport_width);
+            let mut max_height = max_height.min(viewport_height - MENU_BAR_HEIGHT);
+            if top {
+                max_height = max_height.min(window_start_y + window_start_height - MENU_BAR_HEIGHT);
+            }
+            let fit_width = |width: f64| width.min(max_width).max(min_width);
+            let fit_height = |height: f64| height.min(max_height).max(min_height);
+
+            if right {
+                win.width = fit_width(window_start_width + dx);
+            } else if left {
+                win.width = fit_width(window_start_width - dx);
+                win.x = window_start_x + window_start_width - win.width;
+            }
+            if bottom {
+                win.height = fit_height(window_start_height + dy);
+            } else if top {
+                win.height = fit_height(window_start_height - dy);
+                win.y = window_start_y + window_start_height - win.height;
+            }
+        });
+    };
+
+    // Move the dragged window with the pointer, previewing the region it
+    // would tile to. A tiled window first returns to its previous size,
+    // keeping the grab point under the pointer.
+    let drag_window = move |pointer_x: f64, pointer_y: f64| {
+        let DragOperation::Move {
+            window_id,
+            start_x,
+            start_y,
+            window_start_x,
+            window_start_y,
+        } = drag_op.get_untracked()
+        else {
+            return;
+        };
+        let dx = pointer_x - start_x;
+        let dy = pointer_y - start_y;
+
+        let tiled = windows.with_untracked(|windows| {
+            windows
+                .iter()
+                .find(|w| w.id == window_id && w.tile.is_some())
+                .map(|w| (w.width, w.height, w.pre_maximize))
+        });
+        if let Some((width, height, previous)) = tiled {
+            if dx.abs().max(dy.abs()) < DRAG_OUT_DISTANCE {
+                return;
             }
-            DragOperation::Resize {
+            let (restored_width, restored_height) =
+                previous.map_or((width, height), |(_, _, w, h)| (w, h));
+            let new_x = start_x - (start_x - window_start_x) * restored_width / width;
+            // Placed under the pointer, it leaves the stage group's shift behind
+            set_stage_exempt.update(|exempt| exempt.push(window_id));
+            set_windows.update(|windows| {
+                if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
+                    win.x = new_x;
+                    win.width = restored_width;
+                    win.height = restored_height;
+                    win.tile = None;
+                    win.pre_maximize = None;
+                }
+            });
+            set_drag_op.set(DragOperation::Move {
                 window_id,
-                direction,
                 start_x,
                 start_y,
-                window_start_x,
+                window_start_x: new_x,
                 window_start_y,
-                window_start_width,
-                window_start_height,
-            } => {
-                let dx = e.client_x() as f64 - start_x;
-                let dy = e.client_y() as f64 - start_y;
+            });
+        }
 
-                set_windows.update(|windows| {
-                    if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
-                        let min_width = 200.0;
-                        let min_height = 100.0;
+        let DragOperation::Move {
+            window_start_x,
+            window_start_y,
+            ..
+        } = drag_op.get_untracked()
+        else {
+            return;
+        };
+        set_windows.update(|windows| {
+            if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
+                win.x = window_start_x + dx;
+                win.y = (window_start_y + dy).max(MENU_BAR_HEIGHT);
+            }
+        });
+        let target = TileLayout::at_pointer(pointer_x, pointer_y, viewport.get_untracked());
+        if snap_preview.get_untracked() != target {
+            set_snap_preview.set(target);
+        }
+    };
 
-                        match direction {
-                            ResizeDirection::E => {
-                                win.width = (window_start_width + dx).max(min_width);
-                            }
-                            ResizeDirection::W => {
-                                let new_width = (window_start_width - dx).max(min_width);
-                                let actual_dx = window_start_width - new_width;
-                                win.x = window_start_x + actual_dx;
-                                win.width = new_width;
-                            }
-                            ResizeDirection::S => {
-                                win.height = (window_start_height + dy).max(min_height);
-                            }
-                            ResizeDirection::N => {
-                                let new_height = (window_start_height - dy).max(min_height);
-                                let actual_dy = window_start_height - new_height;
-                                win.y = window_start_y + actual_dy;
-                                win.height = new_height;
-                            }
-                            ResizeDirection::SE => {
-                                win.width = (window_start_width + dx).max(min_width);
-                                win.height = (window_start_height + dy).max(min_height);
-                            }
-                            ResizeDirection::SW => {
-                                let new_width = (window_start_width - dx).max(min_width);
-                                let actual_dx = window_start_width - new_width;
-                                win.x = window_start_x + actual_dx;
-                                win.width = new_width;
-                                win.height = (window_start_height + dy).max(min_height);
-                            }
-                            ResizeDirection::NE => {
-                                win.width = (window_start_width + dx).max(min_width);
-                                let new_height = (window_start_height - dy).max(min_height);
-                                let actual_dy = window_start_height - new_height;
-                                win.y = window_start_y + actual_dy;
-                                win.height = new_height;
-                            }
-                            ResizeDirection::NW => {
-                                let new_width = (window_start_width - dx).max(min_width);
-                                let actual_dx = window_start_width - new_width;
-                                win.x = window_start_x + actual_dx;
-                                win.width = new_width;
-                                let new_height = (window_start_height - dy).max(min_height);
-                                let actual_dy = window_start_height - new_height;
-                                win.y = window_start_y + actual_dy;
-                                win.height = new_height;
-                            }
-                        }
-                    }
-                });
+    // End a drag, tiling the window if it was dropped on a snap region
+    let end_drag = move || {
+        if let DragOperation::Move { window_id, .. } = drag_op.get_untracked() {
+            if let Some(layout) = snap_preview.get_untracked() {
+                tile_window(window_id, layout);
+            }
+        }
+        if snap_preview.get_untracked().is_some() {
+            set_snap_preview.set(None);
+        }
+        set_drag_op.set(DragOperation::None);
+    };
+
+    // Handle mouse move for drag/resize
+    let on_mouse_move = move |e: MouseEvent| {
+        let op = drag_op.get();
+        match op {
+            DragOperation::None => {}
+            DragOperation::Move { .. } => {
+                drag_window(e.client_x() as f64, e.client_y() as f64);
+            }
+            DragOperation::Resize { .. } => {
+                resize_window(e.client_x() as f64, e.client_y() as f64);
             }
         }
     };
 
     // Handle mouse up to end drag/resize
     let on_mouse_up = move |_: MouseEvent| {
-        set_drag_op.set(DragOperation::None);
+        end_drag();
     };
 
     // Set up document-level mouse event listeners for drag/resize operations.
@@ -972,96 +2150,17 @@ pub fn WindowManager() -> impl IntoView {
             let op = drag_op.get_untracked();
             match op {
                 DragOperation::None => {}
-                DragOperation::Move {
-                    window_id,
-                    start_x,
-                    start_y,
-                    window_start_x,
-                    window_start_y,
-                } => {
-                    let dx = e.client_x() as f64 - start_x;
-                    let dy = e.client_y() as f64 - start_y;
-                    set_windows.update(|windows| {
-                        if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
-                            win.x = window_start_x + dx;
-                            win.y = (window_start_y + dy).max(MENU_BAR_HEIGHT);
-                        }
-                    });
+                DragOperation::Move { .. } => {
+                    drag_window(e.client_x() as f64, e.client_y() as f64);
                 }
-                DragOperation::Resize {
-                    window_id,
-                    direction,
-                    start_x,
-                    start_y,
-                    window_start_x,
-                    window_start_y,
-                    window_start_width,
-                    window_start_height,
-                } => {
-                    let dx = e.client_x() as f64 - start_x;
-                    let dy = e.client_y() as f64 - start_y;
-
-                    set_windows.update(|windows| {
-                        if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
-                            let min_width = 200.0;
-                            let min_height = 100.0;
-
-                            match direction {
-                                ResizeDirection::E => {
-                                    win.width = (window_start_width + dx).max(min_width);
-                                }
-                                ResizeDirection::W => {
-                                    let new_width = (window_start_width - dx).max(min_width);
-                                    let actual_dx = window_start_width - new_width;
-                                    win.x = window_start_x + actual_dx;
-                                    win.width = new_width;
-                                }
-                                ResizeDirection::S => {
-                                    win.height = (window_start_height + dy).max(min_height);
-                                }
-                                ResizeDirection::N => {
-                                    let new_height = (window_start_height - dy).max(min_height);
-                                    let actual_dy = window_start_height - new_height;
-                                    win.y = window_start_y + actual_dy;
-                                    win.height = new_height;
-                                }
-                                ResizeDirection::SE => {
-                                    win.width = (window_start_width + dx).max(min_width);
-                                    win.height = (window_start_height + dy).max(min_height);
-                                }
-                                ResizeDirection::SW => {
-                                    let new_width = (window_start_width - dx).max(min_width);
-                                    let actual_dx = window_start_width - new_width;
-                                    win.x = window_start_x + actual_dx;
-                                    win.width = new_width;
-                                    win.height = (window_start_height + dy).max(min_height);
-                                }
-                                ResizeDirection::NE => {
-                                    win.width = (window_start_width + dx).max(min_width);
-                                    let new_height = (window_start_height - dy).max(min_height);
-                                    let actual_dy = window_start_height - new_height;
-                                    win.y = window_start_y + actual_dy;
-                                    win.height = new_height;
-                                }
-                                ResizeDirection::NW => {
-                                    let new_width = (window_start_width - dx).max(min_width);
-                                    let actual_dx = window_start_width - new_width;
-                                    win.x = window_start_x + actual_dx;
-                                    win.width = new_width;
-                                    let new_height = (window_start_height - dy).max(min_height);
-                                    let actual_dy = window_start_height - new_height;
-                                    win.y = window_start_y + actual_dy;
-                                    win.height = new_height;
-                                }
-                            }
-                        }
-                    });
+                DragOperation::Resize { .. } => {
+                    resize_window(e.client_x() as f64, e.client_y() as f64);
                 }
             }
         }) as Box<dyn Fn(web_sys::MouseEvent)>);
 
         let doc_mouseup_handler = Closure::wrap(Box::new(move |_: web_sys::MouseEvent| {
-            set_drag_op.set(DragOperation::None);
+            end_drag();
         }) as Box<dyn Fn(web_sys::MouseEvent)>);
 
         // Add document-level listeners
@@ -1085,14 +2184,52 @@ pub fn WindowManager() -> impl IntoView {
 
     // Get the active (top z-index non-minimized) window
     let active_window_id = move || {
+        let current_space = spaces.get().current;
+        let hidden_apps = system_state.hidden_apps.get();
         windows
             .get()
             .iter()
-            .filter(|w| !w.is_minimized)
+            .filter(|w| w.is_shown(current_space, &hidden_apps))
             .max_by_key(|w| w.z_index)
             .map(|w| w.id)
     };
 
+    // Windows of the app whose window is frontmost, oldest first
+    let active_app_windows = move || {
+        let active = active_window_id();
+        windows.with_untracked(|windows| {
+            let Some(app) = active
+                .and_then(|id| windows.iter().find(|w| w.id == id))
+                .map(|w| w.app_type.clone())
+            else {
+                return Vec::new();
+            };
+            windows
+                .iter()
+                .filter(|w| w.app_type == app)
+                .cloned()
+                .collect::<Vec<_>>()
+        })
+    };
+
+    // List every window for the Window menu
+    Effect::new(move |_| {
+        let active = active_window_id();
+        let listed: Vec<ListedWindow> = windows.with(|windows| {
+            windows
+                .iter()
+                .map(|w| ListedWindow {
+                    id: w.id,
+                    title: w.title.clone(),
+                    app: w.app_type.to_string(),
+                    is_active: active == Some(w.id),
+                    is_minimized: w.is_minimized,
+                })
+                .collect()
+        });
+        system_state.window_list.set(listed);
+    });
+
     // Handle keyboard shortcut actions
     Effect::new(move |_| {
         let action = action_trigger.get();
@@ -1100,26 +2237,146 @@ pub fn WindowManager() -> impl IntoView {
             WindowAction::None => {}
             WindowAction::CloseActive => {
                 if let Some(window_id) = active_window_id() {
-                    set_windows.update(|windows| {
-                        windows.retain(|w| w.id != window_id);
-                    });
+                    close_window(window_id);
                 }
                 set_action_trigger.set(WindowAction::None);
             }
             WindowAction::HideActive => {
+                if let Some(app) = active_app_windows().first() {
+                    system_state.hide_app(&app.app_type.to_string());
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::QuitAll => {
+                let ids: Vec<WindowId> =
+                    windows.with_untracked(|windows| windows.iter().map(|w| w.id).collect());
+                for window_id in ids {
+                    close_window(window_id);
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::Tile(layout) => {
                 if let Some(window_id) = active_window_id() {
-                    set_windows.update(|windows| {
-                        if let Some(win) = windows.iter_mut().find(|w| w.id == window_id) {
-                            win.is_minimized = true;
+                    tile_window(window_id, layout);
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::RestorePreviousSize => {
+                if let Some(window_id) = active_window_id() {
+                    restore_previous_size(window_id);
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::MissionControl => {
+                toggle_mission_control();
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::NewWindow => {
+                let app = active_app_windows()
+                    .first()
+                    .map_or(AppType::Finder, |w| w.app_type.clone());
+                open_window(app);
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::MinimizeActive => {
+                if let Some(window_id) = active_window_id() {
+                    minimize_window(window_id);
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::MinimizeAll => {
+                let current_space = spaces.get_untracked().current;
+                for win in active_app_windows() {
+                    if !win.is_minimized && win.space == current_space {
+                        minimize_window(win.id);
+                    }
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::ZoomActive => {
+                if let Some(window_id) = active_window_id() {
+                    maximize_window(window_id);
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::HideOthers => {
+                if let Some(app) = active_app_windows().first().map(|w| w.app_type.to_string()) {
+                    for other in system_state.open_windows.get_untracked() {
+                        if other != app {
+                            system_state.hide_app(&other);
                         }
-                    });
+                    }
                 }
                 set_action_trigger.set(WindowAction::None);
             }
-            WindowAction::QuitAll => {
-                set_windows.update(|windows| {
-                    windows.clear();
+            WindowAction::CycleWindows(step) => {
+                let current_space = spaces.get_untracked().current;
+                let cycle: Vec<WindowId> = active_app_windows()
+                    .iter()
+                    .filter(|w| w.is_shown(current_space, &[]))
+                    .map(|w| w.id)
+                    .collect();
+                let position =
+                    active_window_id().and_then(|active| cycle.iter().position(|id| *id == active));
+                if let Some(position) = position {
+                    let next = (position as i32 + step).rem_euclid(cycle.len() as i32);
+                    bring_to_front(cycle[next as usize]);
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::BringAllToFront => {
+                if let Some(win) = active_app_windows().first() {
+                    bring_app_to_front(&win.app_type);
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::FocusWindow(window_id) => {
+                let state = windows.with_untracked(|windows| {
+                    windows
+                        .iter()
+                        .find(|w| w.id == window_id)
+                        .map(|w| (w.is_minimized, w.space))
                 });
+                match state {
+                    Some((true, _)) => restore_window(window_id),
+                    Some((false, space)) => {
+                        switch_space(space);
+                        bring_to_front(window_id);
+                    }
+                    None => {}
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::ToggleFullScreen => {
+                if let Some(window_id) = active_window_id() {
+                    toggle_full_screen(window_id);
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::SwitchSpace(step) => {
+                let target = spaces.get_untracked().current as i32 + step;
+                if target >= 0 {
+                    switch_space(target as usize);
+                }
+                set_action_trigger.set(WindowAction::None);
+            }
+            WindowAction::MoveToSpace(step) => {
+                let target = spaces.get_untracked().current as i32 + step;
+                if let Some(window_id) = active_window_id() {
+                    if target >= 0 && (target as usize) < spaces.get_untracked().count {
+                        let target = target as usize;
+                        move_window_to_space(window_id, target);
+                        // Full-screen windows and Spaces refuse the move
+                        let moved = windows.with_untracked(|windows| {
+                            windows
+                                .iter()
+                                .any(|w| w.id == window_id && w.space == target)
+                        });
+                        if moved {
+                            switch_space(target);
+                        }
+                    }
+                }
                 set_action_trigger.set(WindowAction::None);
             }
         }
@@ -1127,10 +2384,47 @@ pub fn WindowManager() -> impl IntoView {
 
     view! {
         <div
-            class="windows-container"
+            class=move || {
+                let mut classes = vec!["windows-container"];
+                if mission_control.get() != MissionControl::Off {
+                    classes.push("mission-control");
+                }
+                if stage_manager.get() {
+                    classes.push("stage-manager");
+                }
+                if let Some(slide) = space_slide.get() {
+                    classes.push(slide);
+                }
+                classes.join(" ")
+            }
             on:mousemove=on_mouse_move
             on:mouseup=on_mouse_up
         >
+            // Stage Manager strip: the thumbnails are the windows themselves,
+            // drawn over cells that swap their app onto the stage
+            {move || stage_groups.get().into_iter().map(|group| {
+                let (x, y, width, height) = group.cell;
+                let style = format!(
+                    "left: {}px; top: {}px; width: {}px; height: {}px;",
+                    x, y, width, height
+                );
+                let name = group.app.to_string();
+                let app = group.app;
+                view! {
+                    <div class="stage-strip-item" style=style on:click=move |_| bring_app_to_front(&app)>
+                        <div class="stage-strip-title">{name}</div>
+                    </div>
+                }
+            }).collect_view()}
+            // Where a dragged window will tile, drawn just beneath it
+            {move || snap_preview.get().map(|layout| {
+                let (x, y, width, height) = layout.frame(viewport.get(), system_state.full_screen.get());
+                let style = format!(
+                    "left: {}px; top: {}px; width: {}px; height: {}px; z-index: {};",
+                    x, y, width, height, top_z_index.get_untracked()
+                );
+                view! { <div class="snap-preview" style=style /> }
+            })}
             <For
                 each=move || windows.get()
                 key=|window| window.id
@@ -1138,6 +2432,21 @@ pub fn WindowManager() -> impl IntoView {
                     let window_id = window.id;
                     let is_active = move || active_window_id() == Some(window_id);
 
+                    // Scoped to this window, so its app saves with it
+                    provide_context(WindowAppState {
+                        window_id,
+                        windows,
+                        set_windows,
+                    });
+                    let window_sheet = WindowSheet::new(window_id, set_windows);
+                    provide_context(window_sheet);
+                    window_sheets.update_value(|sheets| {
+                        sheets.insert(window_id, window_sheet);
+                    });
+                    on_cleanup(move || {
+                        window_sheets.try_update_value(|sheets| sheets.remove(&window_id));
+                    });
+
                     let app_type_for_class = window.app_type.clone();
                     let class_str = move || {
                         let win = windows.get().iter().find(|w| w.id == window_id).cloned();
@@ -1147,8 +2456,16 @@ pub fn WindowManager() -> impl IntoView {
                         }
                         if let Some(w) = &win {
                             if is_active() { classes.push("active"); }
+                            if window_sheet.is_presented() { classes.push("has-sheet"); }
+                            if strip_frame(window_id).is_some() { classes.push("in-strip"); }
                             if w.is_minimized { classes.push("minimized"); }
                             if w.is_maximized { classes.push("maximized"); }
+                            if w.is_fullscreen { classes.push("fullscreen"); }
+                            if w.tile.is_some() { classes.push("tiled"); }
+                            if w.space != spaces.get().current { classes.push("other-space"); }
+                            if system_state.hidden_apps.with(|apps| apps.contains(&w.app_type.to_string())) {
+                                classes.push("hidden-app");
+                            }
                             match w.animation {
                                 AnimationState::Minimizing => classes.push("minimizing"),
                                 AnimationState::Restoring => classes.push("restoring"),
@@ -1161,14 +2478,29 @@ pub fn WindowManager() -> impl IntoView {
                     let style_str = move || {
                         let win = windows.get().iter().find(|w| w.id == window_id).cloned();
                         if let Some(w) = win {
+                            let dx = stage_dx(&w);
                             let base_style = format!(
                                 "left: {}px; top: {}px; width: {}px; height: {}px; z-index: {};",
-                                w.x, w.y, w.width, w.height, w.z_index
+                                w.x + dx, w.y, w.width, w.height, w.z_index
                             );
+                            let thumbnail = mission_control_frames
+                                .with(|frames| {
+                                    frames.iter().find(|(id, _)| *id == window_id).map(|(_, f)| *f)
+                                })
+                                .or_else(|| strip_frame(window_id));
                             // Add animation target position as CSS custom property
-                            if let Some(target_x) = w.animation_target_x {
+                            if let Some((thumb_x, thumb_y, thumb_width, _)) = thumbnail {
+                                let (x, y, width, _) = w.frame(viewport.get_untracked());
+                                format!(
+                                    "{} transform-origin: 0 0; transform: translate({}px, {}px) scale({});",
+                                    base_style,
+                                    thumb_x - x - dx,
+                                    thumb_y - y,
+                                    thumb_width / width
+                                )
+                            } else if let Some(target_x) = w.animation_target_x {
                                 // Calculate the horizontal offset from window center to dock target
-                                let window_center_x = w.x + w.width / 2.0;
+                                let window_center_x = w.x + dx + w.width / 2.0;
                                 let offset_x = target_x - window_center_x;
                                 format!("{} --dock-target-x: {}px;", base_style, offset_x)
                             } else {
@@ -1258,6 +2590,7 @@ pub fn WindowManager() -> impl IntoView {
                                     view! { <p>"Window: " {title_for_content}</p> }.into_any()
                                 }}
                             </div>
+                            <SheetView sheet=window_sheet />
 
                             // Resize handles
                             <div class="resize-handle n" on:mousedown=move |e| start_resize(window_id, ResizeDirection::N, e) />
@@ -1273,6 +2606,129 @@ pub fn WindowManager() -> impl IntoView {
                 }
             />
 
+            // Mission Control: each thumbnail is a target that brings its
+            // window to front; clicking elsewhere just leaves
+            <Show when=move || mission_control.get() == MissionControl::Open>
+                <div
+                    class=move || {
+                        if space_drag.get().is_some() {
+                            "mission-control-overlay dragging"
+                        } else {
+                            "mission-control-overlay"
+                        }
+                    }
+                    style=move || format!("z-index: {};", top_z_index.get() + 1)
+                    on:click=move |_| {
+                        // A drag onto a Space ends here rather than leaving
+                        if space_drag.get_untracked().is_some() {
+                            set_space_drag.set(None);
+                        } else {
+                            toggle_mission_control();
+                        }
+                    }
+                >
+                    <div class="spaces-bar" on:click=move |e: MouseEvent| e.stop_propagation()>
+                        <For
+                            each=move || 0..spaces.get().count
+                            key=|index| *index
+                            children=move |index| {
+                                // A full-screen Space is named after its window;
+                                // the others are numbered desktops
+                                let full_screen_title = move || {
+                                    let owner = spaces.with(|spaces| spaces.full_screen.get(index).copied().flatten())?;
+                                    windows.with(|windows| {
+                                        windows.iter().find(|w| w.id == owner).map(|w| w.title.clone())
+                                    })
+                                };
+                                let label = move || {
+                                    full_screen_title().unwrap_or_else(|| {
+                                        let desktops = spaces.with(|spaces| {
+                                            (0..index).filter(|space| !spaces.is_full_screen(*space)).count()
+                                        });
+                                        format!("Desktop {}", desktops + 1)
+                                    })
+                                };
+                                let class = move || {
+                                    if spaces.get().current == index {
+                                        "space-button current"
+                                    } else {
+                                        "space-button"
+                                    }
+                                };
+                                view! {
+                                    <div
+                                        class=class
+                                        on:click=move |_| {
+                                            switch_space(index);
+                                            toggle_mission_control();
+                                        }
+                                        on:mouseup=move |_| {
+                                            if let Some(window_id) = space_drag.get_untracked() {
+                                                move_window_to_space(window_id, index);
+                                            }
+                                        }
+                                    >
+                                        <span class="space-name">{label}</span>
+                                        <Show when=move || {
+                                            spaces.get().count > 1
+                                                && !spaces.with(|spaces| spaces.is_full_screen(index))
+                                        }>
+                                            <button
+                                                class="space-remove"
+                                                title="Remove Desktop"
+                                                on:click=move |e: MouseEvent| {
+                                                    e.stop_propagation();
+                                                    remove_space(index);
+                                                }
+                                            >
+                                                "×"
+                                            </button>
+                                        </Show>
+                                    </div>
+                                }
+                            }
+                        />
+                        <button class="space-add" title="Add Desktop" on:click=move |_| add_space()>
+                            "+"
+                        </button>
+                    </div>
+                    <For
+                        each=move || mission_control_frames.get()
+                        key=|(id, _)| *id
+                        children=move |(window_id, (x, y, width, height))| {
+                            let title = windows.with_untracked(|windows| {
+                                windows
+                                    .iter()
+                                    .find(|w| w.id == window_id)
+                                    .map(|w| w.title.clone())
+                                    .unwrap_or_default()
+                            });
+                            let style = format!(
+                                "left: {}px; top: {}px; width: {}px; height: {}px;",
+                                x, y, width, height
+                            );
+                            view! {
+                                <div
+                                    class="mission-control-thumb"
+                                    style=style
+                                    on:mousedown=move |e: MouseEvent| {
+                                        e.prevent_default();
+                                        set_space_drag.set(Some(window_id));
+                                    }
+                                    on:click=move |e: MouseEvent| {
+                                        set_space_drag.set(None);
+                                        e.stop_propagation();
+                                        bring_to_front(window_id);
+                                        toggle_mission_control();
+                                    }
+                                >
+                                    <div class="mission-control-title">{title}</div>
+                                </div>
+                            }
+                        }
+                    />
+                </div>
+            </Show>
         </div>
     }
 }